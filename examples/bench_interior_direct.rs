@@ -0,0 +1,50 @@
+use nhls::bench::BenchReport;
+use nhls::domain::*;
+use nhls::par_stencil;
+use nhls::util::*;
+
+/// Compare the checked per-cell apply against the interior fast path
+/// for a box whose neighborhood lies inside the input.
+fn main() {
+    let chunk_size = 1000;
+    let steps = 20;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let input_bound = AABB::new(matrix![0, 2047; 0, 2047]);
+    let output_bound = AABB::new(matrix![1, 2046; 1, 2046]);
+    let bc = ConstantCheck::new(1.0, input_bound);
+
+    let mut input_domain = OwnedDomain::new(input_bound);
+    nhls::init::normal_ic_2d(&mut input_domain, chunk_size);
+    let mut checked = OwnedDomain::new(output_bound);
+    let mut interior = OwnedDomain::new(output_bound);
+
+    let start = std::time::Instant::now();
+    for t in 0..steps {
+        par_stencil::apply(
+            &bc,
+            &stencil,
+            &input_domain,
+            &mut checked,
+            t,
+            chunk_size,
+        );
+    }
+    let checked_seconds = start.elapsed().as_secs_f64();
+
+    let start = std::time::Instant::now();
+    for _ in 0..steps {
+        par_stencil::apply_interior(
+            &stencil,
+            &input_domain,
+            &mut interior,
+            chunk_size,
+        );
+    }
+    let interior_seconds = start.elapsed().as_secs_f64();
+
+    assert_eq!(checked.buffer(), interior.buffer());
+    let cells = output_bound.buffer_size();
+    BenchReport::new("apply", cells, steps, checked_seconds).print();
+    BenchReport::new("apply_interior", cells, steps, interior_seconds).print();
+    println!("speedup: {:.3}", checked_seconds / interior_seconds);
+}
@@ -15,7 +15,7 @@ fn main() {
     let mut output_domain = OwnedDomain::new(grid_bound);
 
     if args.rand_init {
-        init::rand(&mut input_domain, 1024, args.chunk_size);
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
     } else {
         init::normal_ic_1d(&mut input_domain, args.chunk_size);
     }
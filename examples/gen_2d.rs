@@ -1,3 +1,5 @@
+#[cfg(feature = "serde")]
+use nhls::config::BCSpec;
 use nhls::domain::*;
 use nhls::image_2d_example::*;
 use nhls::init;
@@ -11,19 +13,22 @@ fn main() {
     let grid_bound = args.grid_bounds();
 
     let stencil = include!("gen_2d.stencil");
+    #[cfg(feature = "serde")]
+    args.check_config("gen_2d", Some(BCSpec::Periodic));
 
     // Create domains
     let mut input_domain = OwnedDomain::new(grid_bound);
     let mut output_domain = OwnedDomain::new(grid_bound);
 
     if args.rand_init {
-        init::rand(&mut input_domain, 1024, args.chunk_size);
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
     } else {
         init::normal_ic_2d(&mut input_domain, args.chunk_size);
     }
 
     // Make image
     nhls::image::image2d(&input_domain, &args.frame_name(0));
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
         direct_periodic_apply(
             &stencil,
@@ -34,5 +39,7 @@ fn main() {
         );
         std::mem::swap(&mut input_domain, &mut output_domain);
         nhls::image::image2d(&input_domain, &args.frame_name(t));
+        bench_timer.frame();
     }
+    args.report_bench("gen_2d", &bench_timer);
 }
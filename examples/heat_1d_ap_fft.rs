@@ -9,10 +9,8 @@ fn main() {
     let grid_bound = args.grid_bounds();
 
     // Create domains
-    let mut buffer_1 = OwnedDomain::new(grid_bound);
-    let mut buffer_2 = OwnedDomain::new(grid_bound);
-    let mut input_domain = buffer_1.as_slice_domain();
-    let mut output_domain = buffer_2.as_slice_domain();
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
 
     // Create BC
     let bc = ConstantCheck::new(1.0, grid_bound);
@@ -20,9 +18,11 @@ fn main() {
     // Create AP Solver
     let planner_params = PlannerParameters {
         plan_type: args.plan_type,
+        plan_fallback: args.plan_fallback,
         cutoff: args.cutoff,
         ratio: args.ratio,
         chunk_size: args.chunk_size,
+        max_steps: None,
     };
     let solver = APSolver::new(
         &bc,
@@ -32,6 +32,9 @@ fn main() {
         &planner_params,
     );
     solver.print_report();
+    if args.plan_stats {
+        println!("{}", solver.plan.statistics());
+    }
     if args.write_dot {
         println!("WRITING DOT FILE");
         let mut dot_path = args.output_dir.clone();
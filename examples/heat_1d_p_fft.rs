@@ -14,7 +14,7 @@ fn main() {
     let mut output_domain = OwnedDomain::new(grid_bound);
 
     if args.rand_init {
-        init::rand(&mut input_domain, 1024, args.chunk_size);
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
     } else {
         init::normal_ic_1d(&mut input_domain, args.chunk_size);
     }
@@ -32,6 +32,7 @@ fn main() {
         &grid_bound,
         args.steps_per_line,
         args.plan_type,
+        args.plan_fallback,
         args.chunk_size,
     );
     for t in 1..args.lines as u32 {
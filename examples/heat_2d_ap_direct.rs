@@ -1,6 +1,10 @@
+#[cfg(feature = "serde")]
+use nhls::config::BCSpec;
 use nhls::domain::*;
 use nhls::image::*;
 use nhls::image_2d_example::*;
+use nhls::init;
+use nhls::solver::*;
 
 fn main() {
     let args = Args::cli_parse("heat_2d_ap_direct");
@@ -14,29 +18,39 @@ fn main() {
     let mut input_domain = OwnedDomain::new(grid_bound);
     let mut output_domain = OwnedDomain::new(grid_bound);
 
+    if args.rand_init {
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
+    } else {
+        init::normal_ic_2d(&mut input_domain, args.chunk_size);
+    }
     if args.write_images {
         image2d(&input_domain, &args.frame_name(0));
     }
 
-    // Create boundary condition
-    let bc = ConstantCheck::new(0.0, grid_bound);
+    // Create boundary condition, matches heat_2d_ap_fft
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    #[cfg(feature = "serde")]
+    args.check_config("heat_2d", Some(BCSpec::Constant { value: 1.0 }));
 
     // Apply direct solver
-    let mut global_time = 0;
+    let mut clock = args.clock();
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
-        nhls::solver::direct::box_apply(
+        box_apply(
             &bc,
             &stencil,
             &mut input_domain,
             &mut output_domain,
             args.steps_per_image,
-            global_time,
+            clock.step(),
             args.chunk_size,
         );
-        global_time += args.steps_per_image;
+        clock.advance(args.steps_per_image);
         std::mem::swap(&mut input_domain, &mut output_domain);
-        if args.write_images {
+        if args.write_frame(t) {
             image2d(&input_domain, &args.frame_name(t));
         }
+        bench_timer.frame();
     }
+    args.report_bench("heat_2d_ap_direct", &bench_timer);
 }
@@ -1,3 +1,5 @@
+#[cfg(feature = "serde")]
+use nhls::config::BCSpec;
 use nhls::domain::*;
 use nhls::fft_solver::PeriodicSolver;
 use nhls::image::*;
@@ -8,6 +10,8 @@ fn main() {
     let args = Args::cli_parse("heat_2d_p_fft");
 
     let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    #[cfg(feature = "serde")]
+    args.check_config("heat_2d", Some(BCSpec::Periodic));
 
     // Create domains
     let grid_bound = args.grid_bounds();
@@ -15,7 +19,7 @@ fn main() {
     let mut output_domain = OwnedDomain::new(grid_bound);
 
     if args.rand_init {
-        init::rand(&mut input_domain, 1024, args.chunk_size);
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
     } else {
         init::normal_ic_2d(&mut input_domain, args.chunk_size);
     }
@@ -29,15 +33,19 @@ fn main() {
         &grid_bound,
         args.steps_per_image,
         args.plan_type,
+        args.plan_fallback,
         args.chunk_size,
     );
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
         periodic_solver.apply(&mut input_domain, &mut output_domain);
         std::mem::swap(&mut input_domain, &mut output_domain);
-        if args.write_images {
+        if args.write_frame(t) {
             image2d(&input_domain, &args.frame_name(t));
         }
+        bench_timer.frame();
     }
+    args.report_bench("heat_2d_p_fft", &bench_timer);
 
     args.save_wisdom();
 }
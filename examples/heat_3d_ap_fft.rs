@@ -1,6 +1,7 @@
 use nhls::domain::*;
 use nhls::fft_solver::*;
 use nhls::image_3d_example::*;
+use nhls::sidecar::*;
 use nhls::vtk::*;
 
 fn main() {
@@ -17,9 +18,11 @@ fn main() {
     // Create AP Solver
     let planner_params = PlannerParameters {
         plan_type: args.plan_type,
+        plan_fallback: args.plan_fallback,
         cutoff: args.cutoff,
         ratio: args.ratio,
         chunk_size: args.chunk_size,
+        max_steps: None,
     };
     let solver = APSolver::new(
         &bc,
@@ -29,6 +32,9 @@ fn main() {
         &planner_params,
     );
     solver.print_report();
+    if args.plan_stats {
+        println!("{}", solver.plan.statistics());
+    }
     if args.write_dot {
         let mut dot_path = args.output_dir.clone();
         dot_path.push("plan.dot");
@@ -40,23 +46,58 @@ fn main() {
     }
 
     // Create domains
-    let mut buffer_1 = OwnedDomain::new(grid_bound);
-    let mut buffer_2 = OwnedDomain::new(grid_bound);
-    let mut input_domain = buffer_1.as_slice_domain();
-    let mut output_domain = buffer_2.as_slice_domain();
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
+    if args.rand_init {
+        nhls::init::rand_seeded(
+            &mut input_domain,
+            1024,
+            args.seed,
+            args.chunk_size,
+        );
+    }
+    let plan_hash = solver.plan.plan_hash();
+    let sidecar = |t: usize| {
+        args.sidecar.then(|| {
+            let metadata = FrameMetadata::new(
+                t,
+                args.steps_per_image,
+                args.dt,
+                grid_bound,
+                "heat_3d",
+            )
+            .with_plan_hash(plan_hash);
+            if args.rand_init {
+                metadata.with_seed(args.seed)
+            } else {
+                metadata
+            }
+        })
+    };
     if args.write_images {
-        write_vtk3d(&input_domain, &args.frame_name(0));
+        write_vtk3d_with_sidecar(
+            &input_domain,
+            &args.frame_name(0),
+            sidecar(0).as_ref(),
+        );
     }
 
-    let mut global_time = 0;
+    let mut clock = args.clock();
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
-        solver.apply(&mut input_domain, &mut output_domain, global_time);
-        global_time += args.steps_per_image;
+        solver.apply(&mut input_domain, &mut output_domain, clock.step());
+        clock.advance(args.steps_per_image);
         std::mem::swap(&mut input_domain, &mut output_domain);
-        if args.write_images {
-            write_vtk3d(&input_domain, &args.frame_name(t));
+        if args.write_frame(t) {
+            write_vtk3d_with_sidecar(
+                &input_domain,
+                &args.frame_name(t),
+                sidecar(t).as_ref(),
+            );
         }
+        bench_timer.frame();
     }
+    args.report_bench("heat_3d_ap_fft", &bench_timer);
 
     args.save_wisdom();
 }
@@ -16,7 +16,7 @@ fn main() {
     let mut output_domain = OwnedDomain::new(grid_bound);
 
     if args.rand_init {
-        init::rand(&mut input_domain, 1024, args.chunk_size);
+        init::rand_seeded(&mut input_domain, 1024, args.seed, args.chunk_size);
     } else {
         init::normal_ic_3d(&mut input_domain, args.chunk_size);
     }
@@ -31,15 +31,19 @@ fn main() {
         &grid_bound,
         args.steps_per_image,
         args.plan_type,
+        args.plan_fallback,
         args.chunk_size,
     );
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
         periodic_solver.apply(&mut input_domain, &mut output_domain);
         std::mem::swap(&mut input_domain, &mut output_domain);
-        if args.write_images {
+        if args.write_frame(t) {
             write_vtk3d(&input_domain, &args.frame_name(t));
         }
+        bench_timer.frame();
     }
+    args.report_bench("heat_3d_p_fft", &bench_timer);
 
     args.save_wisdom();
 }
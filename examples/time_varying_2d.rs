@@ -45,6 +45,10 @@ impl<const GRID_DIMENSION: usize> BCCheck<GRID_DIMENSION>
             Some(0.0)
         }
     }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
 }
 
 fn main() {
@@ -69,13 +73,17 @@ fn main() {
 
     // Create BC
     let bc = PulseBC::new((2.0 * std::f64::consts::PI) / 1000.0, grid_bound);
+    #[cfg(feature = "serde")]
+    args.check_config("time_varying_2d", None);
 
     // Create AP Solver
     let planner_params = PlannerParameters {
         plan_type: args.plan_type,
+        plan_fallback: args.plan_fallback,
         cutoff: args.cutoff,
         ratio: args.ratio,
         chunk_size: args.chunk_size,
+        max_steps: None,
     };
     let solver = APSolver::new(
         &bc,
@@ -84,6 +92,9 @@ fn main() {
         args.steps_per_image,
         &planner_params,
     );
+    if args.plan_stats {
+        println!("{}", solver.plan.statistics());
+    }
     if args.write_dot {
         let mut dot_path = args.output_dir.clone();
         dot_path.push("plan.dot");
@@ -99,23 +110,24 @@ fn main() {
     }
 
     // Create domains
-    let mut buffer_1 = OwnedDomain::new(grid_bound);
-    let mut buffer_2 = OwnedDomain::new(grid_bound);
-    let mut input_domain = buffer_1.as_slice_domain();
-    let mut output_domain = buffer_2.as_slice_domain();
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
     if args.write_images {
         image2d(&input_domain, &args.frame_name(0));
     }
 
     let mut global_time = 0;
+    let mut bench_timer = args.bench_timer();
     for t in 1..args.images {
         solver.apply(&mut input_domain, &mut output_domain, global_time);
         global_time += args.steps_per_image;
         std::mem::swap(&mut input_domain, &mut output_domain);
-        if args.write_images {
+        if args.write_frame(t) {
             image2d(&input_domain, &args.frame_name(t));
         }
+        bench_timer.frame();
     }
+    args.report_bench("time_varying_2d", &bench_timer);
 
     args.save_wisdom();
 }
@@ -0,0 +1,133 @@
+//! Benchmark Reporting
+//!
+//! Throughput in cell updates per second,
+//! comparable across grid sizes, step counts, and implementations.
+
+use std::time::Instant;
+
+/// Timing for a solve of `steps` steps over `cells` cells.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchReport {
+    pub name: String,
+    pub cells: usize,
+    pub steps: usize,
+    pub solve_seconds: f64,
+}
+
+impl BenchReport {
+    pub fn new(
+        name: &str,
+        cells: usize,
+        steps: usize,
+        solve_seconds: f64,
+    ) -> Self {
+        BenchReport {
+            name: name.to_string(),
+            cells,
+            steps,
+            solve_seconds,
+        }
+    }
+
+    /// `cells * steps / solve_seconds`, the standard stencil benchmark metric.
+    pub fn cell_updates_per_second(&self) -> f64 {
+        (self.cells as f64 * self.steps as f64) / self.solve_seconds
+    }
+
+    pub fn print(&self) {
+        println!("BENCH: {}", self.name);
+        println!("  - cells: {}", self.cells);
+        println!("  - steps: {}", self.steps);
+        println!("  - solve seconds: {:.6}", self.solve_seconds);
+        println!(
+            "  - cell updates per second: {:.6e}",
+            self.cell_updates_per_second()
+        );
+    }
+}
+
+/// Times a frame loop, leaving out the first `warmup` frames,
+/// which pay for FFTW planning and first touch page faults.
+/// Call `frame` at the end of each frame.
+#[derive(Clone, Debug)]
+pub struct BenchTimer {
+    warmup: usize,
+    frames: usize,
+    start: Option<Instant>,
+}
+
+impl BenchTimer {
+    pub fn new(warmup: usize) -> Self {
+        BenchTimer {
+            warmup,
+            frames: 0,
+            start: (warmup == 0).then(Instant::now),
+        }
+    }
+
+    pub fn frame(&mut self) {
+        self.frames += 1;
+        if self.frames == self.warmup {
+            self.start = Some(Instant::now());
+        }
+    }
+
+    /// Frames finished since the timer started.
+    pub fn timed_frames(&self) -> usize {
+        self.frames.saturating_sub(self.warmup)
+    }
+
+    /// Seconds since the warmup finished, zero if it hasn't.
+    pub fn solve_seconds(&self) -> f64 {
+        self.start
+            .map_or(0.0, |start| start.elapsed().as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+
+    #[test]
+    fn warmup_test() {
+        let mut timer = BenchTimer::new(2);
+        assert!(timer.start.is_none());
+        timer.frame();
+        assert!(timer.start.is_none());
+        assert_eq!(timer.timed_frames(), 0);
+        assert_eq!(timer.solve_seconds(), 0.0);
+
+        // The timer starts once the last warmup frame is done
+        timer.frame();
+        assert!(timer.start.is_some());
+        assert_eq!(timer.timed_frames(), 0);
+        for _ in 0..3 {
+            timer.frame();
+        }
+        assert_eq!(timer.timed_frames(), 3);
+
+        let mut timer = BenchTimer::new(0);
+        assert!(timer.start.is_some());
+        timer.frame();
+        assert_eq!(timer.timed_frames(), 1);
+    }
+
+    #[test]
+    fn throughput_test() {
+        let report = BenchReport::new("mock", 1000 * 1000, 160, 2.5);
+        assert_approx_eq!(
+            f64,
+            report.cell_updates_per_second(),
+            1000.0 * 1000.0 * 160.0 / 2.5
+        );
+
+        let report = BenchReport::new("mock", 1 << 30, 1 << 20, 0.125);
+        assert_approx_eq!(
+            f64,
+            report.cell_updates_per_second(),
+            (1u64 << 53) as f64,
+            ulps = 2
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Checkpoints
+//!
+//! A domain saved to disk so long runs can resume after a crash.
+//! The header is all little-endian:
+//! * `CHECKPOINT_MAGIC`, 8 bytes
+//! * `CHECKPOINT_VERSION`, u32
+//! * dimension, u32
+//! * min and max corner for each dimension, i32
+//!
+//! followed by the buffer as raw row-major f64 values.
+
+use crate::domain::*;
+use crate::raw::*;
+use crate::util::*;
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub const CHECKPOINT_MAGIC: &[u8; 8] = b"NHLSCKPT";
+
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Reasons `OwnedDomain::load_checkpoint` can fail.
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    DimensionMismatch { expected: usize, found: usize },
+    InvalidBounds,
+    SizeMismatch { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "{}", error),
+            CheckpointError::BadMagic => write!(f, "not a checkpoint file"),
+            CheckpointError::UnsupportedVersion(version) => {
+                write!(f, "unsupported checkpoint version {}", version)
+            }
+            CheckpointError::DimensionMismatch { expected, found } => write!(
+                f,
+                "checkpoint has dimension {}, expected {}",
+                found, expected
+            ),
+            CheckpointError::InvalidBounds => {
+                write!(f, "checkpoint has invalid bounds")
+            }
+            CheckpointError::SizeMismatch { expected, found } => write!(
+                f,
+                "checkpoint has {} bytes of values, expected {}",
+                found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {}
+
+impl From<std::io::Error> for CheckpointError {
+    fn from(error: std::io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+impl<const GRID_DIMENSION: usize> SliceDomain<'_, GRID_DIMENSION> {
+    /// Write the AABB and values to `path`,
+    /// see `OwnedDomain::load_checkpoint`.
+    pub fn save_checkpoint<P: AsRef<Path>>(
+        &self,
+        path: &P,
+    ) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writer.write_all(CHECKPOINT_MAGIC)?;
+        writer.write_all(&CHECKPOINT_VERSION.to_le_bytes())?;
+        writer.write_all(&(GRID_DIMENSION as u32).to_le_bytes())?;
+        for d in 0..GRID_DIMENSION {
+            writer.write_all(&self.aabb().bounds[(d, 0)].to_le_bytes())?;
+            writer.write_all(&self.aabb().bounds[(d, 1)].to_le_bytes())?;
+        }
+        write_values(self, Layout::RowMajor, &mut writer)?;
+        writer.flush()
+    }
+}
+
+impl<const GRID_DIMENSION: usize> OwnedDomain<GRID_DIMENSION> {
+    /// Read a domain written by `SliceDomain::save_checkpoint`.
+    pub fn load_checkpoint<P: AsRef<Path>>(
+        path: &P,
+    ) -> Result<Self, CheckpointError> {
+        let mut bytes = Vec::new();
+        std::fs::File::open(path)?.read_to_end(&mut bytes)?;
+        let mut reader = bytes.as_slice();
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CHECKPOINT_MAGIC {
+            return Err(CheckpointError::BadMagic);
+        }
+        let version = read_u32(&mut reader)?;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version));
+        }
+        let dimension = read_u32(&mut reader)? as usize;
+        if dimension != GRID_DIMENSION {
+            return Err(CheckpointError::DimensionMismatch {
+                expected: GRID_DIMENSION,
+                found: dimension,
+            });
+        }
+        let mut bounds = Bounds::zero();
+        for d in 0..GRID_DIMENSION {
+            bounds[(d, 0)] = read_u32(&mut reader)? as i32;
+            bounds[(d, 1)] = read_u32(&mut reader)? as i32;
+        }
+        let aabb = AABB::new(bounds);
+        if !aabb.check_validity() {
+            return Err(CheckpointError::InvalidBounds);
+        }
+
+        let Some(expected) = value_bytes(&aabb) else {
+            return Err(CheckpointError::InvalidBounds);
+        };
+        if reader.len() != expected {
+            return Err(CheckpointError::SizeMismatch {
+                expected,
+                found: reader.len(),
+            });
+        }
+        let mut result = OwnedDomain::new(aabb);
+        read_values(reader, Layout::RowMajor, &mut result);
+        Ok(result)
+    }
+}
+
+/// Size of the values for `aabb` in bytes, `None` on overflow.
+fn value_bytes<const GRID_DIMENSION: usize>(
+    aabb: &AABB<GRID_DIMENSION>,
+) -> Option<usize> {
+    let mut result = 8usize;
+    for d in 0..GRID_DIMENSION {
+        let length = aabb.bounds[(d, 1)]
+            .checked_sub(aabb.bounds[(d, 0)])?
+            .checked_add(1)?;
+        result = result.checked_mul(usize::try_from(length).ok()?)?;
+    }
+    Some(result)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn test_path(name: &str) -> std::path::PathBuf {
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_{}_{}.ckpt", name, std::process::id()));
+        path
+    }
+
+    #[test]
+    fn checkpoint_round_trip_test() {
+        let aabb = AABB::new(matrix![-2, 5; 3, 7; 0, 2]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(
+            |c| (c[0] * 100 + c[1] * 10 + c[2]) as f64 / 3.0,
+            4,
+        );
+
+        let path = test_path("checkpoint_round_trip");
+        domain.as_slice_domain().save_checkpoint(&path).unwrap();
+        let loaded = OwnedDomain::<3>::load_checkpoint(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.aabb(), domain.aabb());
+        assert_eq!(loaded.buffer(), domain.buffer());
+    }
+
+    #[test]
+    fn checkpoint_corrupt_test() {
+        let aabb = AABB::new(matrix![0, 9]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| c[0] as f64, 4);
+        let path = test_path("checkpoint_corrupt");
+        domain.as_slice_domain().save_checkpoint(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        // Wrong dimension
+        assert!(matches!(
+            OwnedDomain::<2>::load_checkpoint(&path),
+            Err(CheckpointError::DimensionMismatch {
+                expected: 2,
+                found: 1
+            })
+        ));
+
+        // Corrupted magic
+        let mut corrupt = bytes.clone();
+        corrupt[0] = b'X';
+        std::fs::write(&path, &corrupt).unwrap();
+        assert!(matches!(
+            OwnedDomain::<1>::load_checkpoint(&path),
+            Err(CheckpointError::BadMagic)
+        ));
+
+        // Future version
+        let mut corrupt = bytes.clone();
+        corrupt[8..12].copy_from_slice(&7u32.to_le_bytes());
+        std::fs::write(&path, &corrupt).unwrap();
+        assert!(matches!(
+            OwnedDomain::<1>::load_checkpoint(&path),
+            Err(CheckpointError::UnsupportedVersion(7))
+        ));
+
+        // Bounds whose size overflows
+        let mut corrupt = bytes.clone();
+        corrupt[16..20].copy_from_slice(&i32::MIN.to_le_bytes());
+        corrupt[20..24].copy_from_slice(&i32::MAX.to_le_bytes());
+        std::fs::write(&path, &corrupt).unwrap();
+        assert!(matches!(
+            OwnedDomain::<1>::load_checkpoint(&path),
+            Err(CheckpointError::InvalidBounds)
+        ));
+
+        // Truncated values
+        std::fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+        assert!(matches!(
+            OwnedDomain::<1>::load_checkpoint(&path),
+            Err(CheckpointError::SizeMismatch {
+                expected: 80,
+                found: 72
+            })
+        ));
+
+        // Truncated header
+        std::fs::write(&path, &bytes[..10]).unwrap();
+        assert!(matches!(
+            OwnedDomain::<1>::load_checkpoint(&path),
+            Err(CheckpointError::Io(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
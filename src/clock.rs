@@ -0,0 +1,69 @@
+//! Simulated Time
+//!
+//! Solvers count steps, `SolveClock` maps those to simulated time.
+
+/// Tracks steps taken and the matching simulated time.
+/// Time is computed as `step * dt` rather than accumulated,
+/// so it doesn't drift over long runs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SolveClock {
+    pub dt: f64,
+    step: usize,
+}
+
+impl Default for SolveClock {
+    fn default() -> Self {
+        SolveClock::new(1.0)
+    }
+}
+
+impl SolveClock {
+    pub fn new(dt: f64) -> Self {
+        assert!(dt > 0.0, "ERROR: dt must be positive, got {}", dt);
+        SolveClock { dt, step: 0 }
+    }
+
+    /// Record that `steps` more steps have been taken.
+    pub fn advance(&mut self, steps: usize) {
+        self.step += steps;
+    }
+
+    /// Steps taken so far, this is the solver's `global_time`.
+    pub fn step(&self) -> usize {
+        self.step
+    }
+
+    /// Simulated time after the steps taken so far.
+    pub fn current_time(&self) -> f64 {
+        self.step as f64 * self.dt
+    }
+
+    /// Simulated time at an arbitrary step.
+    pub fn time_at(&self, step: usize) -> f64 {
+        step as f64 * self.dt
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+
+    #[test]
+    fn clock_test() {
+        let default_clock = SolveClock::default();
+        assert_eq!(default_clock.dt, 1.0);
+
+        let mut clock = SolveClock::new(0.1);
+        let n = 1000;
+        for _ in 0..n {
+            clock.advance(1);
+        }
+        assert_eq!(clock.step(), n);
+        assert_approx_eq!(f64, clock.current_time(), 0.1 * n as f64, ulps = 2);
+
+        clock.advance(16);
+        assert_approx_eq!(f64, clock.current_time(), 101.6, epsilon = 1e-12);
+        assert_approx_eq!(f64, clock.time_at(5), 0.5, ulps = 2);
+    }
+}
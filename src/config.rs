@@ -0,0 +1,135 @@
+//! Run Configuration
+//!
+//! Everything needed to reproduce a run in one TOML file.
+//! Missing keys take the same defaults as the example executables.
+
+use crate::fft_solver::PlanType;
+use crate::init::DEFAULT_SEED;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Boundary condition used for a run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum BCSpec {
+    Constant { value: f64 },
+    Periodic,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SolverConfig {
+    /// Inclusive bounds per dimension, `[[min, max], ...]`.
+    pub grid_bounds: Vec<[i32; 2]>,
+    pub images: usize,
+    pub steps_per_image: usize,
+    pub dt: f64,
+    pub plan_type: PlanType,
+    pub plan_fallback: bool,
+    pub cutoff: i32,
+    pub ratio: f64,
+    pub chunk_size: usize,
+    pub threads: usize,
+
+    /// Name of the stencil, i.e. `heat_2d`.
+    pub stencil: String,
+    pub bc: BCSpec,
+    pub rand_init: bool,
+    pub seed: u64,
+    pub write_images: bool,
+    pub log_frames: Option<usize>,
+    pub sidecar: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            grid_bounds: vec![[0, 999], [0, 999]],
+            images: 100,
+            steps_per_image: 16,
+            dt: 1.0,
+            plan_type: PlanType::Estimate,
+            plan_fallback: false,
+            cutoff: 40,
+            ratio: 0.5,
+            chunk_size: 1000,
+            threads: 8,
+            stencil: "heat_2d".to_string(),
+            bc: BCSpec::Constant { value: 1.0 },
+            rand_init: false,
+            seed: DEFAULT_SEED,
+            write_images: false,
+            log_frames: None,
+            sidecar: false,
+        }
+    }
+}
+
+impl SolverConfig {
+    pub fn from_toml(toml: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str(toml)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    pub fn read<P: AsRef<Path>>(path: &P) -> Self {
+        let toml = std::fs::read_to_string(path).unwrap_or_else(|error| {
+            panic!("ERROR: Can't read config {:?}: {}", path.as_ref(), error)
+        });
+        Self::from_toml(&toml).unwrap_or_else(|error| {
+            panic!("ERROR: Invalid config {:?}: {}", path.as_ref(), error)
+        })
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: &P) {
+        std::fs::write(path, self.to_toml()).unwrap();
+    }
+
+    /// Side length for examples with square grids at the origin.
+    #[track_caller]
+    pub fn domain_size(&self) -> usize {
+        let max = self.grid_bounds[0][1];
+        for bounds in self.grid_bounds.iter() {
+            assert!(
+                *bounds == [0, max],
+                "ERROR: Examples need square grids at the origin, found {:?}",
+                self.grid_bounds
+            );
+        }
+        max as usize + 1
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_test() {
+        let config = SolverConfig {
+            grid_bounds: vec![[0, 511], [0, 511]],
+            plan_type: PlanType::WisdomOnly,
+            cutoff: 20,
+            ratio: 0.25,
+            bc: BCSpec::Periodic,
+            seed: 99,
+            log_frames: Some(12),
+            ..SolverConfig::default()
+        };
+        let toml = config.to_toml();
+        assert!(toml.contains("plan_type = \"wisdom-only\""));
+        assert_eq!(SolverConfig::from_toml(&toml).unwrap(), config);
+        assert_eq!(config.domain_size(), 512);
+
+        // Missing keys take defaults
+        let config = SolverConfig::from_toml(
+            "cutoff = 10\n[bc]\ntype = \"constant\"\nvalue = 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(config.cutoff, 10);
+        assert_eq!(config.bc, BCSpec::Constant { value: 0.5 });
+        assert_eq!(config.chunk_size, SolverConfig::default().chunk_size);
+    }
+}
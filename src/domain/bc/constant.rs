@@ -25,6 +25,10 @@ impl<const GRID_DIMENSION: usize> BCCheck<GRID_DIMENSION>
         }
         Some(self.value)
     }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,115 @@
+use crate::domain::bc::BCCheck;
+use crate::util::*;
+
+/// Per face values, shaped like `Bounds`,
+/// column 0 for the min face and column 1 for the max face.
+pub type FaceValues<const GRID_DIMENSION: usize> =
+    nalgebra::SMatrix<f64, { GRID_DIMENSION }, 2>;
+
+/// Dirichlet boundary with a constant value per face.
+/// A coordinate past several faces, i.e. in a corner region,
+/// takes the value of the face in its lowest violated dimension.
+pub struct FaceConstantCheck<const GRID_DIMENSION: usize> {
+    values: FaceValues<GRID_DIMENSION>,
+    bound: AABB<GRID_DIMENSION>,
+}
+
+impl<const GRID_DIMENSION: usize> FaceConstantCheck<GRID_DIMENSION> {
+    pub fn new(
+        values: FaceValues<GRID_DIMENSION>,
+        bound: AABB<GRID_DIMENSION>,
+    ) -> Self {
+        FaceConstantCheck { values, bound }
+    }
+
+    /// Every face at `value`, same as `ConstantCheck`.
+    pub fn uniform(value: f64, bound: AABB<GRID_DIMENSION>) -> Self {
+        Self::new(FaceValues::from_element(value), bound)
+    }
+
+    /// Set the value of one face,
+    /// `side` is 0 for the min face and 1 for the max face.
+    pub fn with_face(
+        mut self,
+        dimension: usize,
+        side: usize,
+        value: f64,
+    ) -> Self {
+        self.values[(dimension, side)] = value;
+        self
+    }
+}
+
+impl<const GRID_DIMENSION: usize> BCCheck<GRID_DIMENSION>
+    for FaceConstantCheck<GRID_DIMENSION>
+{
+    fn check(
+        &self,
+        coord: &Coord<GRID_DIMENSION>,
+        _global_time: usize,
+    ) -> Option<f64> {
+        for d in 0..GRID_DIMENSION {
+            if coord[d] < self.bound.bounds[(d, 0)] {
+                return Some(self.values[(d, 0)]);
+            }
+            if coord[d] > self.bound.bounds[(d, 1)] {
+                return Some(self.values[(d, 1)]);
+            }
+        }
+        None
+    }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+    use nalgebra::{matrix, vector};
+
+    #[test]
+    fn face_constant_check_test() {
+        {
+            let bound = AABB::new(matrix![0, 10]);
+            let bc = FaceConstantCheck::new(matrix![100.0, 0.0], bound);
+            for i in 0..bound.buffer_size() {
+                let v = bc.check(&vector![i as i32], 0);
+                assert_eq!(v, None);
+            }
+
+            {
+                let v = bc.check(&vector![-1], 1);
+                assert!(v.is_some());
+                assert_approx_eq!(f64, v.unwrap(), 100.0);
+            }
+
+            {
+                let v = bc.check(&vector![11], 2);
+                assert!(v.is_some());
+                assert_approx_eq!(f64, v.unwrap(), 0.0);
+            }
+        }
+
+        {
+            let bound = AABB::new(matrix![0, 9; 0, 9]);
+            let bc = FaceConstantCheck::uniform(1.0, bound)
+                .with_face(0, 0, 2.0)
+                .with_face(0, 1, 3.0)
+                .with_face(1, 0, 4.0);
+            assert_eq!(bc.check(&vector![5, 5], 0), None);
+
+            // Single faces
+            assert_approx_eq!(f64, bc.check(&vector![-1, 5], 0).unwrap(), 2.0);
+            assert_approx_eq!(f64, bc.check(&vector![10, 5], 0).unwrap(), 3.0);
+            assert_approx_eq!(f64, bc.check(&vector![5, -1], 0).unwrap(), 4.0);
+            assert_approx_eq!(f64, bc.check(&vector![5, 10], 0).unwrap(), 1.0);
+
+            // Corners take the lowest violated dimension
+            assert_approx_eq!(f64, bc.check(&vector![-1, -1], 0).unwrap(), 2.0);
+            assert_approx_eq!(f64, bc.check(&vector![10, 10], 0).unwrap(), 3.0);
+        }
+    }
+}
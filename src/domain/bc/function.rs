@@ -0,0 +1,103 @@
+use crate::domain::*;
+use crate::util::*;
+
+/// Boundary values come from a function of world coord and global time.
+/// Coords inside `bound` are not boundary.
+pub struct FunctionCheck<const GRID_DIMENSION: usize, CheckFn>
+where
+    CheckFn: Fn(Coord<GRID_DIMENSION>, usize) -> f64 + Sync,
+{
+    bound: AABB<GRID_DIMENSION>,
+    f: CheckFn,
+}
+
+impl<const GRID_DIMENSION: usize, CheckFn>
+    FunctionCheck<GRID_DIMENSION, CheckFn>
+where
+    CheckFn: Fn(Coord<GRID_DIMENSION>, usize) -> f64 + Sync,
+{
+    pub fn new(bound: AABB<GRID_DIMENSION>, f: CheckFn) -> Self {
+        FunctionCheck { bound, f }
+    }
+}
+
+impl<const GRID_DIMENSION: usize, CheckFn> BCCheck<GRID_DIMENSION>
+    for FunctionCheck<GRID_DIMENSION, CheckFn>
+where
+    CheckFn: Fn(Coord<GRID_DIMENSION>, usize) -> f64 + Sync,
+{
+    fn check(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        global_time: usize,
+    ) -> Option<f64> {
+        if self.bound.contains(world_coord) {
+            return None;
+        }
+        Some((self.f)(*world_coord, global_time))
+    }
+}
+
+/// Pin the values around `window` to those in `full_domain`.
+/// The halo, `window` grown by `stencil_slopes`, is copied out,
+/// so the check does not borrow `full_domain`.
+/// Use with `APSolver::apply_subregion`.
+pub fn exterior_check<
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    full_domain: &DomainType,
+    window: AABB<GRID_DIMENSION>,
+    stencil_slopes: &Bounds<GRID_DIMENSION>,
+    chunk_size: usize,
+) -> FunctionCheck<
+    GRID_DIMENSION,
+    impl Fn(Coord<GRID_DIMENSION>, usize) -> f64 + Sync,
+> {
+    let mut halo_diff = *stencil_slopes;
+    for d in 0..GRID_DIMENSION {
+        halo_diff[(d, 0)] *= -1;
+    }
+    let halo_aabb = window.add_bounds_diff(halo_diff);
+    assert!(
+        full_domain.aabb().contains_aabb(&halo_aabb),
+        "ERROR: window {:?} with halo {:?} not contained in {:?}",
+        window,
+        halo_aabb,
+        full_domain.aabb()
+    );
+    let mut halo = OwnedDomain::new(halo_aabb);
+    halo.par_from_superset(full_domain, chunk_size);
+    FunctionCheck::new(window, move |world_coord, _global_time| {
+        halo.view(&world_coord)
+    })
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn function_check_test() {
+        let bound = AABB::new(matrix![0, 10]);
+        let bc =
+            FunctionCheck::new(bound, |c: Coord<1>, t| c[0] as f64 + t as f64);
+        assert_eq!(bc.check(&vector![5], 0), None);
+        assert_eq!(bc.check(&vector![-1], 3), Some(2.0));
+        assert_eq!(bc.check(&vector![12], 1), Some(13.0));
+    }
+
+    #[test]
+    fn exterior_check_test() {
+        let full_aabb = AABB::new(matrix![0, 19; 0, 19]);
+        let mut full_domain = OwnedDomain::new(full_aabb);
+        full_domain.par_set_values(|c: Coord<2>| (c[0] * 20 + c[1]) as f64, 7);
+
+        let window = AABB::new(matrix![5, 14; 5, 14]);
+        let slopes = matrix![1, 2; 1, 1];
+        let bc = exterior_check(&full_domain, window, &slopes, 7);
+        assert_eq!(bc.check(&vector![5, 5], 0), None);
+        assert_eq!(bc.check(&vector![4, 5], 0), Some(85.0));
+        assert_eq!(bc.check(&vector![16, 15], 0), Some(335.0));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::domain::*;
+use crate::util::*;
+
+/// Stack two checks, `first` takes priority and
+/// `fallback` is only consulted where `first` returns `None`.
+/// Layers nest, so any number of checks can be combined.
+pub struct LayeredCheck<First, Fallback> {
+    first: First,
+    fallback: Fallback,
+}
+
+impl<First, Fallback> LayeredCheck<First, Fallback> {
+    pub fn new(first: First, fallback: Fallback) -> Self {
+        LayeredCheck { first, fallback }
+    }
+}
+
+impl<const GRID_DIMENSION: usize, First, Fallback> BCCheck<GRID_DIMENSION>
+    for LayeredCheck<First, Fallback>
+where
+    First: BCCheck<GRID_DIMENSION>,
+    Fallback: BCCheck<GRID_DIMENSION>,
+{
+    fn check(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        global_time: usize,
+    ) -> Option<f64> {
+        self.first
+            .check(world_coord, global_time)
+            .or_else(|| self.fallback.check(world_coord, global_time))
+    }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        self.first.interior_is_unconstrained()
+            && self.fallback.interior_is_unconstrained()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+    use nalgebra::{matrix, vector};
+
+    #[test]
+    fn layered_check_test() {
+        let chunk_size = 3;
+        let aabb = AABB::new(matrix![0, 9]);
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let mut input = OwnedDomain::new(aabb);
+        let mut output = OwnedDomain::new(aabb);
+        input.par_set_values(|coord| coord[0] as f64, chunk_size);
+
+        // Dirichlet on the left wall only, periodic elsewhere
+        let left_wall =
+            FunctionCheck::new(AABB::new(matrix![0, 100]), |_, _| 5.0);
+        let bc = LayeredCheck::new(left_wall, PeriodicCheck::new(&input));
+        assert_eq!(bc.check(&vector![-1], 0), Some(5.0));
+        assert_eq!(bc.check(&vector![10], 0), Some(0.0));
+        assert_eq!(bc.check(&vector![4], 0), None);
+
+        crate::par_stencil::apply(
+            &bc,
+            &stencil,
+            &input,
+            &mut output,
+            1,
+            chunk_size,
+        );
+        // 0 + 0.25 * (5 - 0 + 1), and 9 + 0.25 * (8 - 18 + 0)
+        assert_approx_eq!(f64, output.view(&vector![0]), 1.5);
+        assert_approx_eq!(f64, output.view(&vector![9]), 6.5);
+        assert_approx_eq!(f64, output.view(&vector![4]), 4.0);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::domain::*;
+use crate::util::*;
+
+/// Periodic along the dimensions set in `periodic`,
+/// constant `value` past the faces of the others.
+/// Coordinates past a constant face take `value`
+/// even if they also wrap in a periodic dimension.
+/// Like `PeriodicCheck` this reads from `domain`,
+/// so create one per step over the current input.
+pub struct MixedPeriodicCheck<
+    'a,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+> {
+    domain: &'a DomainType,
+    periodic: [bool; GRID_DIMENSION],
+    value: f64,
+}
+
+impl<
+        'a,
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    > MixedPeriodicCheck<'a, GRID_DIMENSION, DomainType>
+{
+    pub fn new(
+        domain: &'a DomainType,
+        periodic: [bool; GRID_DIMENSION],
+        value: f64,
+    ) -> Self {
+        MixedPeriodicCheck {
+            domain,
+            periodic,
+            value,
+        }
+    }
+}
+
+impl<const GRID_DIMENSION: usize, DomainType: DomainView<GRID_DIMENSION>>
+    BCCheck<GRID_DIMENSION>
+    for MixedPeriodicCheck<'_, GRID_DIMENSION, DomainType>
+{
+    fn check(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        _global_time: usize,
+    ) -> Option<f64> {
+        let aabb = self.domain.aabb();
+        if aabb.contains(world_coord) {
+            return None;
+        }
+        for d in 0..GRID_DIMENSION {
+            let outside = world_coord[d] < aabb.bounds[(d, 0)]
+                || world_coord[d] > aabb.bounds[(d, 1)];
+            if outside && !self.periodic[d] {
+                return Some(self.value);
+            }
+        }
+
+        // Only periodic dimensions are outside, so wrapping all is fine
+        let p_coord = aabb.periodic_coord_wrapping(world_coord);
+        Some(self.domain.view(&p_coord))
+    }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+    use nalgebra::{matrix, vector};
+
+    #[test]
+    fn mixed_periodic_check_test() {
+        let aabb = AABB::new(matrix![0, 9; 0, 4]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| (c[0] * 10 + c[1]) as f64, 1);
+        let bc = MixedPeriodicCheck::new(&domain, [true, false], -1.0);
+        for c in aabb.coord_iter() {
+            assert_eq!(bc.check(&c, 0), None);
+        }
+
+        // x wraps
+        assert_approx_eq!(f64, bc.check(&vector![-1, 2], 0).unwrap(), 92.0);
+        assert_approx_eq!(f64, bc.check(&vector![10, 3], 0).unwrap(), 3.0);
+        assert_approx_eq!(f64, bc.check(&vector![12, 0], 0).unwrap(), 20.0);
+
+        // y walls
+        assert_approx_eq!(f64, bc.check(&vector![4, -1], 0).unwrap(), -1.0);
+        assert_approx_eq!(f64, bc.check(&vector![4, 5], 0).unwrap(), -1.0);
+
+        // Corners hit the wall
+        assert_approx_eq!(f64, bc.check(&vector![-1, -1], 0).unwrap(), -1.0);
+        assert_approx_eq!(f64, bc.check(&vector![10, 5], 0).unwrap(), -1.0);
+    }
+}
@@ -1,8 +1,20 @@
 mod constant;
+mod face_constant;
+mod function;
+mod layered;
+mod mixed_periodic;
+mod neumann;
 mod periodic;
+mod sponge;
 
 pub use constant::*;
+pub use face_constant::*;
+pub use function::*;
+pub use layered::*;
+pub use mixed_periodic::*;
+pub use neumann::*;
 pub use periodic::*;
+pub use sponge::*;
 
 use crate::util::*;
 
@@ -12,4 +24,14 @@ pub trait BCCheck<const GRID_DIMENSION: usize>: Sync {
         world_coord: &Coord<GRID_DIMENSION>,
         global_time: usize,
     ) -> Option<f64>;
+
+    /// Whether `check` returns `None` everywhere inside the bound
+    /// or domain the check guards.
+    /// Fast paths like `par_stencil::apply_rows` only skip `check`
+    /// for inputs within that bound when this is true.
+    /// Checks that may return values inside, like `SpongeCheck`,
+    /// must keep the default.
+    fn interior_is_unconstrained(&self) -> bool {
+        false
+    }
 }
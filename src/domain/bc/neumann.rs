@@ -0,0 +1,138 @@
+use crate::domain::*;
+use crate::util::*;
+
+/// Zero gradient boundary,
+/// coordinates outside the domain mirror back across the nearest face,
+/// so `min - 1` reads `min` and `max + 1` reads `max`.
+/// Each dimension reflects independently, which handles corners.
+/// Like `PeriodicCheck` this reads from `domain`,
+/// so create one per step over the current input.
+pub struct NeumannCheck<
+    'a,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+> {
+    domain: &'a DomainType,
+}
+
+impl<
+        'a,
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    > NeumannCheck<'a, GRID_DIMENSION, DomainType>
+{
+    pub fn new(domain: &'a DomainType) -> Self {
+        NeumannCheck { domain }
+    }
+}
+
+/// Mirror `coord` into `aabb` about the half cell past each face.
+/// Coordinates further out than the domain is wide are clamped.
+pub fn reflect_coord<const GRID_DIMENSION: usize>(
+    aabb: &AABB<GRID_DIMENSION>,
+    coord: &Coord<GRID_DIMENSION>,
+) -> Coord<GRID_DIMENSION> {
+    let mut result = *coord;
+    for d in 0..GRID_DIMENSION {
+        let min = aabb.bounds[(d, 0)];
+        let max = aabb.bounds[(d, 1)];
+        if result[d] < min {
+            result[d] = 2 * min - 1 - result[d];
+        } else if result[d] > max {
+            result[d] = 2 * max + 1 - result[d];
+        }
+        result[d] = result[d].clamp(min, max);
+    }
+    result
+}
+
+impl<const GRID_DIMENSION: usize, DomainType: DomainView<GRID_DIMENSION>>
+    BCCheck<GRID_DIMENSION> for NeumannCheck<'_, GRID_DIMENSION, DomainType>
+{
+    fn check(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        _global_time: usize,
+    ) -> Option<f64> {
+        let aabb = self.domain.aabb();
+        if aabb.contains(world_coord) {
+            return None;
+        }
+        Some(self.domain.view(&reflect_coord(aabb, world_coord)))
+    }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+    use nalgebra::{matrix, vector};
+
+    #[test]
+    fn neumann_check_test() {
+        {
+            let aabb = AABB::new(matrix![0, 10]);
+            let mut domain = OwnedDomain::new(aabb);
+            domain.par_set_values(|coord| coord[0] as f64, 1);
+            let bc = NeumannCheck::new(&domain);
+            for (i, _) in domain.buffer().iter().enumerate() {
+                let v = bc.check(&vector![i as i32], 0);
+                assert_eq!(v, None);
+            }
+
+            {
+                let v = bc.check(&vector![-1], 1);
+                assert!(v.is_some());
+                assert_approx_eq!(f64, v.unwrap(), 0.0);
+            }
+
+            {
+                let v = bc.check(&vector![-2], 1);
+                assert_approx_eq!(f64, v.unwrap(), 1.0);
+            }
+
+            {
+                let v = bc.check(&vector![11], 2);
+                assert!(v.is_some());
+                assert_approx_eq!(f64, v.unwrap(), 10.0);
+            }
+
+            {
+                let v = bc.check(&vector![13], 2);
+                assert_approx_eq!(f64, v.unwrap(), 8.0);
+            }
+        }
+
+        {
+            let aabb = AABB::new(matrix![0, 4; 10, 14]);
+            let mut domain = OwnedDomain::new(aabb);
+            domain.par_set_values(|c| (c[0] * 100 + c[1]) as f64, 1);
+            let bc = NeumannCheck::new(&domain);
+            assert_eq!(bc.check(&vector![2, 12], 0), None);
+
+            // Faces
+            assert_approx_eq!(
+                f64,
+                bc.check(&vector![-1, 12], 0).unwrap(),
+                12.0
+            );
+            assert_approx_eq!(
+                f64,
+                bc.check(&vector![2, 15], 0).unwrap(),
+                214.0
+            );
+
+            // Corners reflect in both dimensions
+            assert_approx_eq!(f64, bc.check(&vector![-1, 9], 0).unwrap(), 10.0);
+            assert_approx_eq!(
+                f64,
+                bc.check(&vector![6, 16], 0).unwrap(),
+                313.0
+            );
+        }
+    }
+}
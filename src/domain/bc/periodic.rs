@@ -28,12 +28,16 @@ impl<const GRID_DIMENSION: usize, DomainType: DomainView<GRID_DIMENSION>>
         world_coord: &Coord<GRID_DIMENSION>,
         _global_time: usize,
     ) -> Option<f64> {
-        let p_coord = &self.domain.aabb().periodic_coord(world_coord);
+        let p_coord = &self.domain.aabb().periodic_coord_wrapping(world_coord);
         if p_coord != world_coord {
             return Some(self.domain.view(p_coord));
         }
         None
     }
+
+    fn interior_is_unconstrained(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
@@ -66,6 +70,14 @@ mod unit_tests {
                 assert!(v.is_some());
                 assert_approx_eq!(f64, v.unwrap(), 0.0);
             }
+
+            // More than one box away
+            {
+                let v = bc.check(&vector![7 + 3 * 11], 3);
+                assert_approx_eq!(f64, v.unwrap(), 7.0);
+                let v = bc.check(&vector![7 - 2 * 11], 4);
+                assert_approx_eq!(f64, v.unwrap(), 7.0);
+            }
         }
     }
 }
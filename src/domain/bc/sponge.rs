@@ -0,0 +1,114 @@
+use crate::domain::*;
+use crate::util::*;
+
+/// Absorbing layer to damp reflections off the faces of `domain`.
+/// Coordinates within `width` cells of a face read the domain value
+/// scaled by a factor that ramps linearly from 1.0 at the inner edge
+/// of the layer to `min_factor` on the face.
+/// Coordinates past the faces take `value`.
+/// Like `PeriodicCheck` this reads from `domain`,
+/// so create one per step over the current input.
+pub struct SpongeCheck<
+    'a,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+> {
+    domain: &'a DomainType,
+    width: i32,
+    min_factor: f64,
+    value: f64,
+}
+
+impl<
+        'a,
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    > SpongeCheck<'a, GRID_DIMENSION, DomainType>
+{
+    pub fn new(
+        domain: &'a DomainType,
+        width: i32,
+        min_factor: f64,
+        value: f64,
+    ) -> Self {
+        assert!(width > 0, "ERROR: sponge width {} must be > 0", width);
+        assert!(
+            (0.0..=1.0).contains(&min_factor),
+            "ERROR: sponge min factor must be in [0, 1], got {}",
+            min_factor
+        );
+        SpongeCheck {
+            domain,
+            width,
+            min_factor,
+            value,
+        }
+    }
+
+    /// Damping applied at an in-bounds `world_coord`,
+    /// 1.0 outside the layer.
+    pub fn factor(&self, world_coord: &Coord<GRID_DIMENSION>) -> f64 {
+        let aabb = self.domain.aabb();
+        let distance = (0..GRID_DIMENSION)
+            .map(|d| {
+                (world_coord[d] - aabb.bounds[(d, 0)])
+                    .min(aabb.bounds[(d, 1)] - world_coord[d])
+            })
+            .min()
+            .unwrap_or(i32::MAX);
+        if distance >= self.width {
+            return 1.0;
+        }
+        let t = distance as f64 / self.width as f64;
+        self.min_factor + (1.0 - self.min_factor) * t
+    }
+}
+
+impl<const GRID_DIMENSION: usize, DomainType: DomainView<GRID_DIMENSION>>
+    BCCheck<GRID_DIMENSION> for SpongeCheck<'_, GRID_DIMENSION, DomainType>
+{
+    fn check(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        _global_time: usize,
+    ) -> Option<f64> {
+        if !self.domain.aabb().contains(world_coord) {
+            return Some(self.value);
+        }
+        let factor = self.factor(world_coord);
+        if factor < 1.0 {
+            return Some(self.domain.view(world_coord) * factor);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use float_cmp::assert_approx_eq;
+    use nalgebra::{matrix, vector};
+
+    #[test]
+    fn sponge_check_test() {
+        let aabb = AABB::new(matrix![0, 9]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|_| 2.0, 1);
+        let bc = SpongeCheck::new(&domain, 4, 0.2, -1.0);
+
+        // Ramp from 0.2 on the faces to 1.0 four cells in
+        let expected = [0.4, 0.8, 1.2, 1.6];
+        for (i, e) in expected.iter().enumerate() {
+            let i = i as i32;
+            assert_approx_eq!(f64, bc.check(&vector![i], 0).unwrap(), *e);
+            assert_approx_eq!(f64, bc.check(&vector![9 - i], 0).unwrap(), *e);
+        }
+        assert_approx_eq!(f64, bc.factor(&vector![2]), 0.6);
+        assert_eq!(bc.check(&vector![4], 0), None);
+        assert_eq!(bc.check(&vector![5], 0), None);
+
+        // Constant past the faces
+        assert_eq!(bc.check(&vector![-1], 0), Some(-1.0));
+        assert_eq!(bc.check(&vector![10], 0), Some(-1.0));
+    }
+}
@@ -0,0 +1,86 @@
+use crate::domain::*;
+use crate::util::*;
+use std::collections::HashMap;
+
+/// Recycles `AlignedVec<f64>` buffers by size.
+/// Drivers that build many domains of the same size,
+/// like parameter sweeps, can avoid repeated large aligned allocations
+/// and first touch page faults by returning buffers here.
+#[derive(Default)]
+pub struct AlignedBufferPool {
+    free: HashMap<usize, Vec<AlignedVec<f64>>>,
+}
+
+impl AlignedBufferPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a zeroed buffer of exactly `size` elements,
+    /// reusing a returned buffer if one is available.
+    pub fn take(&mut self, size: usize) -> AlignedVec<f64> {
+        match self.free.get_mut(&size).and_then(|buffers| buffers.pop()) {
+            Some(mut buffer) => {
+                buffer.as_slice_mut().fill(0.0);
+                buffer
+            }
+            None => AlignedVec::new(size),
+        }
+    }
+
+    /// Return a buffer to the pool for later reuse.
+    pub fn give(&mut self, buffer: AlignedVec<f64>) {
+        self.free.entry(buffer.len()).or_default().push(buffer);
+    }
+
+    /// Create a zeroed domain backed by a pooled buffer.
+    pub fn domain<const GRID_DIMENSION: usize>(
+        &mut self,
+        aabb: AABB<GRID_DIMENSION>,
+    ) -> OwnedDomain<GRID_DIMENSION> {
+        OwnedDomain::from_buffer(aabb, self.take(aabb.buffer_size()))
+    }
+
+    /// Return a domain's buffer to the pool.
+    pub fn recycle<const GRID_DIMENSION: usize>(
+        &mut self,
+        domain: OwnedDomain<GRID_DIMENSION>,
+    ) {
+        self.give(domain.into_buffer());
+    }
+
+    /// Number of buffers currently held by the pool.
+    pub fn free_count(&self) -> usize {
+        self.free.values().map(|buffers| buffers.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn reuse_test() {
+        let mut pool = AlignedBufferPool::new();
+        let aabb = AABB::new(matrix![0, 9; 0, 9]);
+
+        let mut a = pool.domain(aabb);
+        a.par_set_values(|_| 1.0, 10);
+        let a_ptr = a.buffer().as_ptr();
+        pool.recycle(a);
+        assert_eq!(pool.free_count(), 1);
+
+        // Different size doesn't reuse
+        let c = pool.take(50);
+        assert_eq!(c.len(), 50);
+        assert_eq!(pool.free_count(), 1);
+
+        // Same size gets the same buffer back, zeroed
+        let b = pool.domain(aabb);
+        assert_eq!(b.buffer().as_ptr(), a_ptr);
+        assert_eq!(pool.free_count(), 0);
+        for v in b.buffer() {
+            assert_eq!(*v, 0.0);
+        }
+    }
+}
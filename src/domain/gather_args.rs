@@ -18,9 +18,29 @@ pub fn gather_args<
 where
     Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
     BC: BCCheck<GRID_DIMENSION>,
+{
+    gather_offset_args(stencil.offsets(), bc, input, world_coord, global_time)
+}
+
+/// Like `gather_args`, for any set of neighbor offsets,
+/// i.e. those of a `VariableStencil`.
+pub fn gather_offset_args<
+    BC,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    offsets: &[Coord<GRID_DIMENSION>; NEIGHBORHOOD_SIZE],
+    bc: &BC,
+    input: &DomainType,
+    world_coord: &Coord<GRID_DIMENSION>,
+    global_time: usize,
+) -> [f64; NEIGHBORHOOD_SIZE]
+where
+    BC: BCCheck<GRID_DIMENSION>,
 {
     let mut result = [0.0; NEIGHBORHOOD_SIZE];
-    for (i, n_i) in stencil.offsets().iter().enumerate() {
+    for (i, n_i) in offsets.iter().enumerate() {
         let n_world_coord = world_coord + n_i;
         result[i] = bc
             .check(&n_world_coord, global_time)
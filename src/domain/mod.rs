@@ -6,9 +6,11 @@
 //! and translate from world coordinates into view coordinates.
 
 mod bc;
+mod buffer_pool;
 mod gather_args;
 mod view;
 
 pub use bc::*;
+pub use buffer_pool::*;
 pub use gather_args::*;
 pub use view::*;
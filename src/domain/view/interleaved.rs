@@ -0,0 +1,154 @@
+use super::*;
+use crate::util::*;
+use fftw::array::*;
+use rayon::prelude::*;
+
+/// A domain holding `COMPONENTS` values per cell,
+/// i.e. several species of a reaction-diffusion system.
+/// Components of one cell are contiguous,
+/// so component `c` of the cell with linear index `l`
+/// is at `l * COMPONENTS + c`.
+///
+/// Solvers that only know scalar domains,
+/// like the FFT solvers, can work on each component independently
+/// via `extract_component` and `insert_component`.
+pub struct InterleavedDomain<
+    const GRID_DIMENSION: usize,
+    const COMPONENTS: usize,
+> {
+    aabb: AABB<GRID_DIMENSION>,
+    buffer: AlignedVec<f64>,
+}
+
+impl<const GRID_DIMENSION: usize, const COMPONENTS: usize>
+    InterleavedDomain<GRID_DIMENSION, COMPONENTS>
+{
+    pub fn new(aabb: AABB<GRID_DIMENSION>) -> Self {
+        let buffer = AlignedVec::new(aabb.buffer_size() * COMPONENTS);
+        InterleavedDomain { aabb, buffer }
+    }
+
+    pub fn aabb(&self) -> &AABB<GRID_DIMENSION> {
+        &self.aabb
+    }
+
+    pub fn buffer(&self) -> &[f64] {
+        &self.buffer
+    }
+
+    pub fn buffer_mut(&mut self) -> &mut [f64] {
+        &mut self.buffer
+    }
+
+    /// Get all components at the given world coord.
+    pub fn cell(&self, world_coord: &Coord<GRID_DIMENSION>) -> &[f64] {
+        let start = self.aabb.coord_to_linear(world_coord) * COMPONENTS;
+        &self.buffer[start..start + COMPONENTS]
+    }
+
+    /// Access one component at the given world coord.
+    pub fn view(
+        &self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        component: usize,
+    ) -> f64 {
+        debug_assert!(component < COMPONENTS);
+        self.buffer
+            [self.aabb.coord_to_linear(world_coord) * COMPONENTS + component]
+    }
+
+    pub fn set_coord(
+        &mut self,
+        world_coord: &Coord<GRID_DIMENSION>,
+        component: usize,
+        value: f64,
+    ) {
+        debug_assert!(component < COMPONENTS);
+        let index =
+            self.aabb.coord_to_linear(world_coord) * COMPONENTS + component;
+        self.buffer[index] = value;
+    }
+
+    /// Set every cell, `f` returns all components for a coord.
+    pub fn par_set_values<
+        F: Fn(Coord<GRID_DIMENSION>) -> [f64; COMPONENTS] + Send + Sync,
+    >(
+        &mut self,
+        f: F,
+        chunk_size: usize,
+    ) {
+        let aabb = self.aabb;
+        self.buffer
+            .par_chunks_mut(chunk_size * COMPONENTS)
+            .enumerate()
+            .for_each(|(i, buffer_chunk): (usize, &mut [f64])| {
+                let offset = i * chunk_size;
+                for (j, cell) in buffer_chunk.chunks_mut(COMPONENTS).enumerate()
+                {
+                    let world_coord = aabb.linear_to_coord(offset + j);
+                    cell.copy_from_slice(&f(world_coord));
+                }
+            });
+    }
+
+    /// Copy one component into a scalar domain with the same AABB.
+    pub fn extract_component<DomainType: DomainView<GRID_DIMENSION>>(
+        &self,
+        component: usize,
+        domain: &mut DomainType,
+    ) {
+        debug_assert!(component < COMPONENTS);
+        debug_assert_eq!(*domain.aabb(), self.aabb);
+        domain
+            .buffer_mut()
+            .par_iter_mut()
+            .zip(self.buffer.par_chunks(COMPONENTS))
+            .for_each(|(value, cell)| *value = cell[component]);
+    }
+
+    /// Copy a scalar domain with the same AABB into one component.
+    pub fn insert_component<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        component: usize,
+        domain: &DomainType,
+    ) {
+        debug_assert!(component < COMPONENTS);
+        debug_assert_eq!(*domain.aabb(), self.aabb);
+        self.buffer
+            .par_chunks_mut(COMPONENTS)
+            .zip(domain.buffer().par_iter())
+            .for_each(|(cell, value)| cell[component] = *value);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn component_round_trip_test() {
+        let aabb = AABB::new(matrix![0, 4; -2, 3]);
+        let mut interleaved = InterleavedDomain::<2, 3>::new(aabb);
+        interleaved.par_set_values(
+            |c| [c[0] as f64, c[1] as f64, (c[0] * c[1]) as f64],
+            4,
+        );
+        assert_eq!(interleaved.view(&vector![3, -1], 2), -3.0);
+        assert_eq!(interleaved.cell(&vector![4, 2]), &[4.0, 2.0, 8.0]);
+
+        let mut scalar = OwnedDomain::new(aabb);
+        interleaved.extract_component(1, &mut scalar);
+        for c in aabb.coord_iter() {
+            assert_eq!(scalar.view(&c), c[1] as f64);
+        }
+
+        scalar.par_set_values(|c| -(c[0] as f64), 4);
+        interleaved.insert_component(0, &scalar);
+        for c in aabb.coord_iter() {
+            assert_eq!(
+                interleaved.cell(&c),
+                &[-(c[0] as f64), c[1] as f64, (c[0] * c[1]) as f64]
+            );
+        }
+    }
+}
@@ -1,8 +1,12 @@
 mod chunk;
+mod interleaved;
+#[cfg(feature = "ndarray")]
+mod ndarray_convert;
 mod owned;
 mod slice;
 
 pub use chunk::*;
+pub use interleaved::*;
 pub use owned::*;
 pub use slice::*;
 
@@ -43,6 +47,15 @@ pub trait DomainView<const GRID_DIMENSION: usize>: Sync {
         par_modify_access_impl(buffer, aabb, chunk_size)
     }
 
+    /// Like `par_modify_access`, but iterates chunks on the calling thread.
+    fn modify_access(
+        &mut self,
+        chunk_size: usize,
+    ) -> impl Iterator<Item = DomainChunk<'_, GRID_DIMENSION>> {
+        let (aabb, buffer) = self.aabb_buffer_mut();
+        modify_access_impl(buffer, aabb, chunk_size)
+    }
+
     fn par_set_values<
         F: FnOnce(Coord<GRID_DIMENSION>) -> f64 + Send + Sync + Copy,
     >(
@@ -59,12 +72,39 @@ pub trait DomainView<const GRID_DIMENSION: usize>: Sync {
         );
     }
 
-    /// Copy other domain into self
+    /// Fill with `amplitude * exp(-|x - center|^2 / (2 sigma^2))`,
+    /// see `init::normal_ic_1d` and friends for fixed width spikes.
+    fn par_set_gaussian(
+        &mut self,
+        center: Coord<GRID_DIMENSION>,
+        sigma: f64,
+        amplitude: f64,
+        chunk_size: usize,
+    ) {
+        assert!(sigma > 0.0, "ERROR: gaussian sigma {} must be > 0", sigma);
+        let denominator = 2.0 * sigma * sigma;
+        self.par_set_values(
+            |coord: Coord<GRID_DIMENSION>| {
+                let r_sq = (coord - center).cast::<f64>().norm_squared();
+                amplitude * (-r_sq / denominator).exp()
+            },
+            chunk_size,
+        );
+    }
+
+    /// Copy other domain into self,
+    /// other must be contained by self.
     fn par_set_subdomain<DomainType: DomainView<GRID_DIMENSION>>(
         &mut self,
         other: &DomainType,
         chunk_size: usize,
     ) {
+        debug_assert!(
+            self.aabb().contains_aabb(other.aabb()),
+            "ERROR: {} does not contain subdomain {}",
+            self.aabb(),
+            other.aabb()
+        );
         let const_self_ref: &Self = self;
         other.buffer()[0..other.aabb().buffer_size()]
             .par_chunks(chunk_size)
@@ -86,15 +126,155 @@ pub trait DomainView<const GRID_DIMENSION: usize>: Sync {
             });
     }
 
-    /// Copy self coords from other into self
+    /// Copy self coords from other into self,
+    /// other must contain self.
     fn par_from_superset<DomainType: DomainView<GRID_DIMENSION>>(
         &mut self,
         other: &DomainType,
         chunk_size: usize,
     ) {
+        debug_assert!(
+            other.aabb().contains_aabb(self.aabb()),
+            "ERROR: superset {} does not contain {}",
+            other.aabb(),
+            self.aabb()
+        );
         self.par_set_values(|world_coord| other.view(&world_coord), chunk_size);
     }
 
+    /// Like `par_set_subdomain`, but other only needs to overlap self.
+    /// Only coords in both domains are copied,
+    /// returns the number of cells copied.
+    fn par_set_subdomain_checked<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        other: &DomainType,
+        chunk_size: usize,
+    ) -> usize {
+        let self_aabb = *self.aabb();
+        let const_self_ref: &Self = self;
+        other.buffer()[0..other.aabb().buffer_size()]
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(move |(i, buffer_chunk): (usize, &[f64])| {
+                let self_ptr = const_self_ref as *const Self;
+                let mut_self_ref: &mut Self =
+                    unsafe { &mut *(self_ptr as *mut Self) as &mut Self };
+                let offset = i * chunk_size;
+                let mut copied = 0;
+                for (j, value) in buffer_chunk.iter().enumerate() {
+                    let world_coord = other.aabb().linear_to_coord(j + offset);
+                    if self_aabb.contains(&world_coord) {
+                        let self_linear_index =
+                            self_aabb.coord_to_linear(&world_coord);
+                        mut_self_ref.buffer_mut()[self_linear_index] = *value;
+                        copied += 1;
+                    }
+                }
+                copied
+            })
+            .sum()
+    }
+
+    /// Like `par_from_superset`, but other only needs to overlap self.
+    /// Only coords in both domains are copied,
+    /// returns the number of cells copied.
+    fn par_from_superset_checked<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        other: &DomainType,
+        chunk_size: usize,
+    ) -> usize {
+        let other_aabb = *other.aabb();
+        self.par_modify_access(chunk_size)
+            .map(|mut d: DomainChunk<'_, GRID_DIMENSION>| {
+                let mut copied = 0;
+                for (world_coord, value_mut) in d.coord_iter_mut() {
+                    if other_aabb.contains(&world_coord) {
+                        *value_mut = other.view(&world_coord);
+                        copied += 1;
+                    }
+                }
+                copied
+            })
+            .sum()
+    }
+
+    /// Smallest and largest values, NaNs are ignored.
+    fn minmax(&self, chunk_size: usize) -> (f64, f64) {
+        self.buffer()
+            .par_chunks(chunk_size)
+            .map(|chunk: &[f64]| {
+                chunk
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |m, v| {
+                        (m.0.min(*v), m.1.max(*v))
+                    })
+            })
+            .reduce(
+                || (f64::INFINITY, f64::NEG_INFINITY),
+                |a, b| (a.0.min(b.0), a.1.max(b.1)),
+            )
+    }
+
+    /// Largest value, NaNs are ignored, see `minmax`.
+    fn par_max(&self, chunk_size: usize) -> f64 {
+        self.minmax(chunk_size).1
+    }
+
+    /// Smallest value, NaNs are ignored, see `minmax`.
+    fn par_min(&self, chunk_size: usize) -> f64 {
+        self.minmax(chunk_size).0
+    }
+
+    /// Sum of all values, i.e. the total mass of the field.
+    /// Each chunk is summed serially, then the chunk sums are combined.
+    fn par_sum(&self, chunk_size: usize) -> f64 {
+        self.buffer()
+            .par_chunks(chunk_size)
+            .map(|chunk: &[f64]| chunk.iter().sum::<f64>())
+            .sum()
+    }
+
+    /// Counts of values in `bins` equal width bins over `range`,
+    /// `minmax` when `None`. The range max falls in the last bin,
+    /// values outside the range and NaNs are not counted.
+    /// Each chunk fills a local histogram, these are summed at the end.
+    fn histogram(
+        &self,
+        bins: usize,
+        range: Option<(f64, f64)>,
+        chunk_size: usize,
+    ) -> Vec<u64> {
+        assert!(bins > 0, "ERROR: histogram needs at least one bin");
+        let (min, max) = range.unwrap_or_else(|| self.minmax(chunk_size));
+        let width = (max - min) / bins as f64;
+        self.buffer()
+            .par_chunks(chunk_size)
+            .map(|chunk: &[f64]| {
+                let mut counts = vec![0; bins];
+                for v in chunk {
+                    if !(min <= *v && *v <= max) {
+                        continue;
+                    }
+                    let bin = if width > 0.0 {
+                        (((v - min) / width) as usize).min(bins - 1)
+                    } else {
+                        0
+                    };
+                    counts[bin] += 1;
+                }
+                counts
+            })
+            .reduce(
+                || vec![0; bins],
+                |mut a, b| {
+                    for (a, b) in a.iter_mut().zip(b) {
+                        *a += b;
+                    }
+                    a
+                },
+            )
+    }
+
     /// WARNING, obviously unsafe.
     ///
     /// In parallel situations, if you can gaurentee that threads are accessing
@@ -129,10 +309,91 @@ fn par_modify_access_impl<'a, const GRID_DIMENSION: usize>(
         })
 }
 
+/// Serial version of `par_modify_access_impl`.
+fn modify_access_impl<'a, const GRID_DIMENSION: usize>(
+    buffer: &'a mut [f64],
+    aabb: &'a AABB<GRID_DIMENSION>,
+    chunk_size: usize,
+) -> impl Iterator<Item = DomainChunk<'a, GRID_DIMENSION>> + 'a {
+    buffer[0..aabb.buffer_size()]
+        .chunks_mut(chunk_size)
+        .enumerate()
+        .map(move |(i, buffer_chunk): (usize, &mut [f64])| {
+            let offset = i * chunk_size;
+            DomainChunk::new(offset, aabb, buffer_chunk)
+        })
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
 
+    #[test]
+    fn gaussian_test() {
+        let aabb = AABB::new(matrix![0, 63; -10, 60]);
+        let center = vector![32, 25];
+        let sigma = 4.0;
+        let amplitude = 3.0;
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_gaussian(center, sigma, amplitude, 64);
+
+        assert_eq!(domain.view(&center), amplitude);
+        assert_eq!(domain.par_max(64), amplitude);
+        assert!(domain.view(&vector![33, 25]) < amplitude);
+
+        // Sum over the unit grid approximates the integral
+        let expected = amplitude * 2.0 * std::f64::consts::PI * sigma * sigma;
+        let sum = domain.par_sum(64);
+        assert!(
+            ((sum - expected) / expected).abs() < 1e-6,
+            "{} vs {}",
+            sum,
+            expected
+        );
+    }
+
+    #[test]
+    fn histogram_test() {
+        let chunk_size = 3;
+        let bounds = AABB::new(matrix![0, 9]);
+        let mut domain = OwnedDomain::new(bounds);
+        let values = [0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 9.0, 3.9, 4.0, -1.0];
+        domain.buffer_mut().copy_from_slice(&values);
+        assert_eq!(domain.minmax(chunk_size), (-1.0, 9.0));
+
+        // [0, 1), [1, 2), [2, 3), [3, 4], 9 and -1 are outside
+        assert_eq!(
+            domain.histogram(4, Some((0.0, 4.0)), chunk_size),
+            vec![2, 2, 2, 2]
+        );
+
+        // [-1, 4), [4, 9]
+        assert_eq!(domain.histogram(2, None, chunk_size), vec![8, 2]);
+
+        // Constant fields land in one bin
+        domain.buffer_mut().fill(3.0);
+        assert_eq!(domain.histogram(3, None, chunk_size), vec![10, 0, 0]);
+    }
+
+    #[test]
+    fn reduction_test() {
+        let chunk_size = 7;
+        let bounds = AABB::new(matrix![0, 9; 0, 9]);
+        let mut domain = OwnedDomain::new(bounds);
+        domain.par_set_values(|c| (c[0] * 10 + c[1]) as f64 - 20.0, chunk_size);
+        assert_eq!(domain.par_min(chunk_size), -20.0);
+        assert_eq!(domain.par_max(chunk_size), 79.0);
+        assert_eq!(domain.par_sum(chunk_size), 4950.0 - 2000.0);
+        assert_eq!(
+            domain.minmax(chunk_size),
+            (domain.par_min(chunk_size), domain.par_max(chunk_size))
+        );
+
+        domain.buffer_mut()[3] = f64::NAN;
+        assert_eq!(domain.par_min(chunk_size), -20.0);
+        assert_eq!(domain.par_max(chunk_size), 79.0);
+    }
+
     #[test]
     fn par_set_subdomain_test() {
         {
@@ -173,4 +434,36 @@ mod unit_tests {
             }
         }
     }
+
+    #[test]
+    fn checked_copy_test() {
+        let chunk_size = 4;
+        let bounds = AABB::new(matrix![0, 9; 0, 9]);
+        let mut domain = OwnedDomain::new(bounds);
+
+        // Proper subbox copies all of its cells
+        let i_bounds = AABB::new(matrix![3, 7; 2, 4]);
+        let mut i_domain = OwnedDomain::new(i_bounds);
+        i_domain.par_set_values(|_| 1.0, chunk_size);
+        let copied = domain.par_set_subdomain_checked(&i_domain, chunk_size);
+        assert_eq!(copied, i_bounds.buffer_size());
+        assert_eq!(copied, 15);
+        for c in domain.aabb().coord_iter() {
+            let expected = if i_bounds.contains(&c) { 1.0 } else { 0.0 };
+            assert_eq!(domain.view(&c), expected);
+        }
+
+        // Partial overlap only copies the intersection
+        let o_bounds = AABB::new(matrix![8, 12; -3, 0]);
+        let mut o_domain = OwnedDomain::new(o_bounds);
+        o_domain.par_set_values(|_| 2.0, chunk_size);
+        assert_eq!(domain.par_set_subdomain_checked(&o_domain, chunk_size), 2);
+        assert_eq!(o_domain.par_from_superset_checked(&domain, chunk_size), 2);
+
+        // Disjoint boxes are reported as no-op
+        let d_bounds = AABB::new(matrix![20, 22; 20, 22]);
+        let mut d_domain = OwnedDomain::new(d_bounds);
+        assert_eq!(domain.par_set_subdomain_checked(&d_domain, chunk_size), 0);
+        assert_eq!(d_domain.par_from_superset_checked(&domain, chunk_size), 0);
+    }
 }
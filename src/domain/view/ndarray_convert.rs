@@ -0,0 +1,127 @@
+use crate::domain::*;
+use crate::util::*;
+use ndarray::{ArrayBase, ArrayD, Data, Dimension, IxDyn};
+
+/// Our buffers are row-major with the last dimension fastest,
+/// which is ndarray's standard C layout.
+/// Index `(i, j, k)` in the array is coordinate `min + (i, j, k)`.
+fn ndarray_shape<const GRID_DIMENSION: usize>(
+    aabb: &AABB<GRID_DIMENSION>,
+) -> Vec<usize> {
+    let exclusive_bounds = aabb.exclusive_bounds();
+    exclusive_bounds.iter().map(|e| *e as usize).collect()
+}
+
+fn domain_to_ndarray<
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+) -> ArrayD<f64> {
+    let shape = ndarray_shape(domain.aabb());
+    ArrayD::from_shape_vec(IxDyn(&shape), domain.buffer().to_vec()).unwrap()
+}
+
+impl<const GRID_DIMENSION: usize> SliceDomain<'_, GRID_DIMENSION> {
+    /// Copy the domain into a new standard layout array.
+    pub fn to_ndarray(&self) -> ArrayD<f64> {
+        domain_to_ndarray(self)
+    }
+
+    /// Copy an array with the same shape as our AABB into the domain.
+    /// The array may have any memory layout.
+    pub fn from_ndarray<S, D>(&mut self, array: &ArrayBase<S, D>)
+    where
+        S: Data<Elem = f64>,
+        D: Dimension,
+    {
+        let shape = ndarray_shape(self.aabb());
+        assert_eq!(
+            array.shape(),
+            shape.as_slice(),
+            "ERROR: ndarray shape does not match domain {}",
+            self.aabb()
+        );
+        for (value, a) in self.buffer_mut().iter_mut().zip(array.iter()) {
+            *value = *a;
+        }
+    }
+}
+
+impl<const GRID_DIMENSION: usize> OwnedDomain<GRID_DIMENSION> {
+    /// Copy the domain into a new standard layout array.
+    pub fn to_ndarray(&self) -> ArrayD<f64> {
+        domain_to_ndarray(self)
+    }
+
+    /// Create a domain with min corner `min` holding a copy of the array.
+    pub fn from_ndarray<S, D>(
+        min: Coord<GRID_DIMENSION>,
+        array: &ArrayBase<S, D>,
+    ) -> Self
+    where
+        S: Data<Elem = f64>,
+        D: Dimension,
+    {
+        assert_eq!(
+            array.ndim(),
+            GRID_DIMENSION,
+            "ERROR: ndarray dimension does not match domain"
+        );
+        let mut bounds = Bounds::zeros();
+        for d in 0..GRID_DIMENSION {
+            bounds[(d, 0)] = min[d];
+            bounds[(d, 1)] = min[d] + array.shape()[d] as i32 - 1;
+        }
+        let mut result = OwnedDomain::new(AABB::new(bounds));
+        result.as_slice_domain().from_ndarray(array);
+        result
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn round_trip<const GRID_DIMENSION: usize>(aabb: AABB<GRID_DIMENSION>) {
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| c.iter().sum::<i32>() as f64 * 0.5, 7);
+
+        let array = domain.to_ndarray();
+        let min = aabb.bounds.column(0).into_owned();
+        let round_trip = OwnedDomain::from_ndarray(min, &array);
+        assert_eq!(round_trip.aabb(), domain.aabb());
+        assert_eq!(round_trip.buffer(), domain.buffer());
+    }
+
+    #[test]
+    fn round_trip_test() {
+        round_trip(AABB::new(matrix![-3, 8]));
+        round_trip(AABB::new(matrix![0, 4; 2, 9]));
+        round_trip(AABB::new(matrix![1, 3; -2, 2; 5, 11]));
+    }
+
+    #[test]
+    fn index_test() {
+        let aabb = AABB::new(matrix![1, 3; -2, 2; 5, 11]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| (c[0] * 10000 + c[1] * 100 + c[2]) as f64, 5);
+        let array = domain.to_ndarray();
+        assert_eq!(array.shape(), &[3, 5, 7]);
+        for (index, value) in array.indexed_iter() {
+            let coord = vector![
+                1 + index[0] as i32,
+                -2 + index[1] as i32,
+                5 + index[2] as i32
+            ];
+            assert_eq!(*value, domain.view(&coord));
+        }
+
+        // Transposed views still map by logical index
+        let mut transposed = OwnedDomain::new(AABB::new(matrix![0, 6; 0, 2]));
+        let source =
+            ndarray::Array2::from_shape_fn((3, 7), |(i, j)| (i * 7 + j) as f64);
+        transposed.as_slice_domain().from_ndarray(&source.t());
+        assert_eq!(transposed.view(&vector![4, 2]), source[(2, 4)]);
+    }
+}
@@ -13,11 +13,69 @@ impl<const GRID_DIMENSION: usize> OwnedDomain<GRID_DIMENSION> {
         OwnedDomain { aabb, buffer }
     }
 
+    /// Create a domain using an existing buffer,
+    /// which must be at least as large as the aabb requires.
+    pub fn from_buffer(
+        aabb: AABB<GRID_DIMENSION>,
+        buffer: AlignedVec<f64>,
+    ) -> Self {
+        assert!(
+            aabb.buffer_size() <= buffer.len(),
+            "ERROR: buffer of size {} too small for {:?}",
+            buffer.len(),
+            aabb
+        );
+        OwnedDomain { aabb, buffer }
+    }
+
+    /// Consume the domain, returning the underlying buffer.
+    pub fn into_buffer(self) -> AlignedVec<f64> {
+        self.buffer
+    }
+
+    /// Allocate storage for two domains over `aabb` at once,
+    /// use with `split_halves`.
+    pub fn new_paired(aabb: AABB<GRID_DIMENSION>) -> Self {
+        let buffer = AlignedVec::new(2 * paired_stride(&aabb));
+        OwnedDomain { aabb, buffer }
+    }
+
+    /// Split storage from `new_paired` into two non-aliasing domains.
+    /// The second half starts on an aligned boundary.
+    pub fn split_halves(
+        &mut self,
+    ) -> (
+        SliceDomain<'_, GRID_DIMENSION>,
+        SliceDomain<'_, GRID_DIMENSION>,
+    ) {
+        let stride = paired_stride(&self.aabb);
+        assert!(
+            self.buffer.len() >= 2 * stride,
+            "ERROR: buffer of size {} too small to split for {:?}",
+            self.buffer.len(),
+            self.aabb
+        );
+        let (first, second) = self.buffer.split_at_mut(stride);
+        (
+            SliceDomain::new(self.aabb, first),
+            SliceDomain::new(self.aabb, &mut second[0..stride]),
+        )
+    }
+
     pub fn as_slice_domain(&mut self) -> SliceDomain<'_, GRID_DIMENSION> {
         SliceDomain::new(self.aabb, &mut self.buffer)
     }
 }
 
+/// Buffer size for one half of a paired allocation,
+/// rounded up so the second half stays aligned.
+fn paired_stride<const GRID_DIMENSION: usize>(
+    aabb: &AABB<GRID_DIMENSION>,
+) -> usize {
+    let align = crate::fft_solver::MIN_ALIGNMENT / std::mem::size_of::<f64>();
+    aabb.buffer_size().div_ceil(align) * align
+}
+
 impl<const GRID_DIMENSION: usize> DomainView<GRID_DIMENSION>
     for OwnedDomain<GRID_DIMENSION>
 {
@@ -97,4 +155,25 @@ mod unit_tests {
         assert_eq!(a_ptr, sb_ptr);
         assert_eq!(b_ptr, sa_ptr);
     }
+
+    #[test]
+    fn split_halves_test() {
+        let aabb = AABB::new(matrix![0, 6; 0, 4]);
+        let mut paired = OwnedDomain::new_paired(aabb);
+        let (mut a, mut b) = paired.split_halves();
+        assert_eq!(*a.aabb(), aabb);
+        assert_eq!(*b.aabb(), aabb);
+        assert_eq!(b.buffer().as_ptr() as usize % 16, 0);
+
+        a.par_set_values(|c: Coord<2>| (c[0] + 10 * c[1]) as f64, 4);
+        b.par_set_values(|c: Coord<2>| -((c[0] + 10 * c[1]) as f64), 4);
+        for c in aabb.coord_iter() {
+            let v = (c[0] + 10 * c[1]) as f64;
+            assert_eq!(a.view(&c), v);
+            assert_eq!(b.view(&c), -v);
+        }
+
+        std::mem::swap(&mut a, &mut b);
+        assert_eq!(a.view(&vector![1, 1]), -11.0);
+    }
 }
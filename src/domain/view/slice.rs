@@ -1,5 +1,9 @@
 use super::*;
 use crate::util::*;
+use rayon::prelude::*;
+
+/// Values per task when copying buffers in parallel.
+const COPY_CHUNK_SIZE: usize = 4096;
 
 pub struct SliceDomain<'a, const GRID_DIMENSION: usize> {
     aabb: AABB<GRID_DIMENSION>,
@@ -11,6 +15,29 @@ impl<'a, const GRID_DIMENSION: usize> SliceDomain<'a, GRID_DIMENSION> {
         debug_assert!(buffer.len() >= aabb.buffer_size());
         SliceDomain { aabb, buffer }
     }
+
+    /// Copy the AABB and values into a freshly allocated domain,
+    /// which shares nothing with `self`.
+    pub fn to_owned(&self) -> OwnedDomain<GRID_DIMENSION> {
+        let mut result = OwnedDomain::new(self.aabb);
+        self.clone_into(&mut result.as_slice_domain());
+        result
+    }
+
+    /// Copy the values into `dst`, which must have the same AABB.
+    pub fn clone_into(&self, dst: &mut SliceDomain<'_, GRID_DIMENSION>) {
+        assert_eq!(
+            self.aabb, dst.aabb,
+            "ERROR: clone_into from {} to {}",
+            self.aabb, dst.aabb
+        );
+        dst.buffer_mut()
+            .par_chunks_mut(COPY_CHUNK_SIZE)
+            .zip(self.buffer().par_chunks(COPY_CHUNK_SIZE))
+            .for_each(|(dst_chunk, src_chunk)| {
+                dst_chunk.copy_from_slice(src_chunk)
+            });
+    }
 }
 
 impl<'a, const GRID_DIMENSION: usize> DomainView<GRID_DIMENSION>
@@ -64,3 +91,54 @@ impl<'a, const GRID_DIMENSION: usize> DomainView<GRID_DIMENSION>
         self.buffer[index] = value;
     }
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn to_owned_test() {
+        let aabb = AABB::new(matrix![-3, 80; 0, 70]);
+        let mut source = OwnedDomain::new(aabb);
+        source.par_set_values(|c: Coord<2>| (c[0] + 100 * c[1]) as f64, 64);
+        let source = source.as_slice_domain();
+
+        let mut copy = source.to_owned();
+        assert_eq!(*copy.aabb(), aabb);
+        assert_eq!(copy.buffer(), source.buffer());
+        assert_ne!(copy.buffer().as_ptr(), source.buffer().as_ptr());
+
+        copy.par_set_values(|_| -1.0, 64);
+        for c in aabb.coord_iter() {
+            assert_eq!(source.view(&c), (c[0] + 100 * c[1]) as f64);
+        }
+    }
+
+    #[test]
+    fn clone_into_test() {
+        let aabb = AABB::new(matrix![0, 9999]);
+        let mut source = OwnedDomain::new(aabb);
+        source.par_set_values(|c: Coord<1>| c[0] as f64, 64);
+        let mut source = source.as_slice_domain();
+
+        let mut dst = OwnedDomain::new(aabb);
+        let mut dst = dst.as_slice_domain();
+        source.clone_into(&mut dst);
+        assert_eq!(dst.buffer(), source.buffer());
+
+        dst.set_coord(&vector![5], -1.0);
+        assert_eq!(source.view(&vector![5]), 5.0);
+        source.set_coord(&vector![6], -1.0);
+        assert_eq!(dst.view(&vector![6]), 6.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERROR: clone_into")]
+    fn clone_into_mismatch_test() {
+        let mut source = OwnedDomain::new(AABB::new(matrix![0, 99]));
+        let mut dst = OwnedDomain::new(AABB::new(matrix![0, 49]));
+        source
+            .as_slice_domain()
+            .clone_into(&mut dst.as_slice_domain());
+    }
+}
@@ -24,6 +24,25 @@ impl std::fmt::Display for Side {
 }
 
 impl Side {
+    /// Index into a pair ordered min then max,
+    /// i.e. the column of a `Bounds` object, see also `Index<Side>`.
+    #[inline]
+    pub fn index(&self) -> usize {
+        match self {
+            Side::Min => 0,
+            Side::Max => 1,
+        }
+    }
+
+    /// The other end of the axis.
+    #[inline]
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Min => Side::Max,
+            Side::Max => Side::Min,
+        }
+    }
+
     /// When indexing a `Bounds` object for a given dimension,
     /// which side is away from the boundary condition.
     #[inline]
@@ -57,6 +76,24 @@ impl Side {
     }
 }
 
+/// Pairs ordered min then max, like `AABB::decomposition` results,
+/// can be indexed by side.
+impl<T> std::ops::Index<Side> for [T; 2] {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, side: Side) -> &T {
+        &self[side.index()]
+    }
+}
+
+impl<T> std::ops::IndexMut<Side> for [T; 2] {
+    #[inline]
+    fn index_mut(&mut self, side: Side) -> &mut T {
+        &mut self[side.index()]
+    }
+}
+
 /// `APFrustrum` is the object we manipulate in `APPlanner` to create
 /// an `APPlan`.
 ///
@@ -168,20 +205,26 @@ impl<const GRID_DIMENSION: usize> APFrustrum<GRID_DIMENSION> {
     /// We assume that we have periodic solve that covers all of `self`.
     /// This will be the case if we call `decompose` after `time_cut` after
     /// using `find_periodic_solve`.
+    /// Panics if `self.steps` is zero, there is nothing to decompose.
     pub fn decompose(
         &self,
         stencil_slopes: &Bounds<GRID_DIMENSION>,
     ) -> Vec<APFrustrum<GRID_DIMENSION>> {
+        assert!(
+            self.steps > 0,
+            "ERROR: Can't decompose frustrum {} with zero steps",
+            self.output_aabb
+        );
         let mut result = Vec::new();
 
         // The periodic solve covers `self.steps`,
         // so the output will include `self.steps` in from each boundary face.
         // So the boundary frustrums should be `self.steps - 1` from each face
-        let i_steps = self.steps as i32 - 1;
         let rec_d = self.recursion_dimension;
-        let slope_modifiers: Bounds<GRID_DIMENSION> = i_steps * stencil_slopes;
+        let slope_modifiers: Bounds<GRID_DIMENSION> =
+            scale_bounds(self.steps - 1, stencil_slopes);
         let remainder_modifiers: Bounds<GRID_DIMENSION> =
-            self.steps as i32 * stencil_slopes;
+            scale_bounds(self.steps, stencil_slopes);
 
         // 1 for this dimension,
         let mut output_aabb = self.output_aabb;
@@ -203,6 +246,11 @@ impl<const GRID_DIMENSION: usize> APFrustrum<GRID_DIMENSION> {
         // For each lower dimension we create min and max frustrum
         // and remove from remainder
         for d in self.recursion_dimension + 1..GRID_DIMENSION {
+            // Degenerate axes are never shrunk by the periodic solve
+            if stencil_slopes[(d, 0)] + stencil_slopes[(d, 1)] == 0 {
+                continue;
+            }
+
             let mut min_aabb = remainder;
             let min_bound = min_aabb.bounds[(d, 0)];
             min_aabb.bounds[(d, 1)] =
@@ -230,6 +278,23 @@ impl<const GRID_DIMENSION: usize> APFrustrum<GRID_DIMENSION> {
 mod unit_tests {
     use super::*;
 
+    #[test]
+    fn side_test() {
+        assert_eq!(Side::Min.opposite(), Side::Max);
+        assert_eq!(Side::Max.opposite(), Side::Min);
+        for side in [Side::Min, Side::Max] {
+            assert_eq!(side.opposite().opposite(), side);
+            assert_eq!(side.index(), side.outer_index());
+            assert_eq!(side.opposite().index(), side.inner_index());
+        }
+
+        let mut pair = ["min", "max"];
+        assert_eq!(pair[Side::Min], "min");
+        assert_eq!(pair[Side::Max], "max");
+        pair[Side::Max] = "other";
+        assert_eq!(pair, ["min", "other"]);
+    }
+
     fn test_decomp<const GRID_DIMENSION: usize>(
         frustrum: &APFrustrum<GRID_DIMENSION>,
         solve_output: &AABB<GRID_DIMENSION>,
@@ -269,7 +334,7 @@ mod unit_tests {
 
         let solve_params = PeriodicSolveParams {
             stencil_slopes,
-            cutoff,
+            cutoff: Coord::repeat(cutoff),
             ratio,
             max_steps: None,
         };
@@ -467,23 +532,15 @@ mod unit_tests {
 
         for d in 0..3 {
             for side in [Side::Min, Side::Max] {
-                let frustrum = APFrustrum::new(
-                    decomposition[d][side.outer_index()],
-                    d,
-                    side,
-                    50,
-                );
+                let frustrum =
+                    APFrustrum::new(decomposition[d][side], d, side, 50);
                 let input_aabb = frustrum.input_aabb(&stencil_slopes);
                 debug_assert!(global_aabb.contains_aabb(&input_aabb));
             }
         }
 
-        let mut frustrum = APFrustrum::new(
-            decomposition[0][Side::Min.outer_index()],
-            0,
-            Side::Min,
-            50,
-        );
+        let mut frustrum =
+            APFrustrum::new(decomposition[0][Side::Min], 0, Side::Min, 50);
         let mut time_cut_1 = frustrum.time_cut(25, &stencil_slopes).unwrap();
         let time_cut_2 = time_cut_1.time_cut(18, &stencil_slopes).unwrap();
 
@@ -496,4 +553,12 @@ mod unit_tests {
             time_cut_2.input_aabb(&stencil_slopes)
         );
     }
+
+    #[test]
+    #[should_panic(expected = "zero steps")]
+    fn decompose_zero_steps() {
+        let output_aabb = AABB::new(matrix![0, 10; 0, 10]);
+        let f = APFrustrum::new(output_aabb, 0, Side::Min, 0);
+        f.decompose(&Bounds::from_element(1));
+    }
 }
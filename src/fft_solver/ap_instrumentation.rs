@@ -0,0 +1,201 @@
+use crate::fft_solver::NodeId;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+const N_CATEGORIES: usize = 5;
+
+/// Kinds of work timed by `Instrumentation`,
+/// these don't overlap, so on one thread they sum to at most the wall time.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimingCategory {
+    /// Forward transforms of periodic solve nodes.
+    ForwardFft,
+
+    /// Pointwise multiply with the convolution,
+    /// and the spectral filter if there is one.
+    Multiply,
+
+    /// Backward transforms of periodic solve nodes,
+    /// including the normalization.
+    BackwardFft,
+
+    /// Direct solve nodes.
+    Direct,
+
+    /// Copying values between node buffers,
+    /// i.e. `par_from_superset` and `par_set_subdomain`.
+    Copy,
+}
+
+impl TimingCategory {
+    pub const ALL: [TimingCategory; N_CATEGORIES] = [
+        TimingCategory::ForwardFft,
+        TimingCategory::Multiply,
+        TimingCategory::BackwardFft,
+        TimingCategory::Direct,
+        TimingCategory::Copy,
+    ];
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+type Totals = [AtomicU64; N_CATEGORIES];
+
+fn totals_report(totals: &Totals) -> TimingReport {
+    let get = |category: TimingCategory| {
+        Duration::from_nanos(totals[category.index()].load(Ordering::Relaxed))
+    };
+    TimingReport {
+        forward_fft: get(TimingCategory::ForwardFft),
+        multiply: get(TimingCategory::Multiply),
+        backward_fft: get(TimingCategory::BackwardFft),
+        direct: get(TimingCategory::Direct),
+        copy: get(TimingCategory::Copy),
+    }
+}
+
+/// Wall clock time accumulated for each `TimingCategory`,
+/// both in total and for each plan node.
+/// Updates are atomic, so boundary solves can record from rayon tasks.
+/// Times from parallel tasks add up, so totals may exceed the wall time.
+#[derive(Debug, Default)]
+pub struct Instrumentation {
+    totals: Totals,
+    nodes: Vec<Totals>,
+}
+
+impl Instrumentation {
+    /// Accumulator for a plan with `n_nodes` nodes.
+    pub fn new(n_nodes: usize) -> Self {
+        Instrumentation {
+            totals: Totals::default(),
+            nodes: (0..n_nodes).map(|_| Totals::default()).collect(),
+        }
+    }
+
+    /// Run `f`, adding its wall time to `category`
+    /// in the totals and for `node_id`.
+    pub fn time<R, F: FnOnce() -> R>(
+        &self,
+        category: TimingCategory,
+        node_id: NodeId,
+        f: F,
+    ) -> R {
+        let start = Instant::now();
+        let result = f();
+        let nanos = start.elapsed().as_nanos() as u64;
+        let i = category.index();
+        self.totals[i].fetch_add(nanos, Ordering::Relaxed);
+        self.nodes[node_id][i].fetch_add(nanos, Ordering::Relaxed);
+        result
+    }
+
+    pub fn report(&self) -> TimingReport {
+        totals_report(&self.totals)
+    }
+
+    /// Time spent in `node_id` itself,
+    /// not counting the nodes it solves through.
+    pub fn node_report(&self, node_id: NodeId) -> TimingReport {
+        totals_report(&self.nodes[node_id])
+    }
+
+    /// `node_report` for every node, indexed by `NodeId`.
+    pub fn node_reports(&self) -> Vec<TimingReport> {
+        self.nodes.iter().map(totals_report).collect()
+    }
+
+    /// Zero all the totals.
+    pub fn reset(&self) {
+        for totals in std::iter::once(&self.totals).chain(self.nodes.iter()) {
+            for nanos in totals.iter() {
+                nanos.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Totals from `Instrumentation::report`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimingReport {
+    pub forward_fft: Duration,
+    pub multiply: Duration,
+    pub backward_fft: Duration,
+    pub direct: Duration,
+    pub copy: Duration,
+}
+
+impl TimingReport {
+    pub fn get(&self, category: TimingCategory) -> Duration {
+        match category {
+            TimingCategory::ForwardFft => self.forward_fft,
+            TimingCategory::Multiply => self.multiply,
+            TimingCategory::BackwardFft => self.backward_fft,
+            TimingCategory::Direct => self.direct,
+            TimingCategory::Copy => self.copy,
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        TimingCategory::ALL.iter().map(|c| self.get(*c)).sum()
+    }
+}
+
+impl std::fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "AP Solver Timing:")?;
+        for category in TimingCategory::ALL {
+            writeln!(
+                f,
+                "  - {:?}: {:.6}s",
+                category,
+                self.get(category).as_secs_f64()
+            )?;
+        }
+        write!(f, "  - total: {:.6}s", self.total().as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn instrumentation_test() {
+        let instrumentation = Instrumentation::new(3);
+        assert_eq!(instrumentation.report(), TimingReport::default());
+
+        let r = instrumentation.time(TimingCategory::Direct, 2, || {
+            std::thread::sleep(Duration::from_millis(5));
+            3
+        });
+        assert_eq!(r, 3);
+        instrumentation.time(TimingCategory::Copy, 0, || {});
+        instrumentation.time(TimingCategory::ForwardFft, 1, || {
+            std::thread::sleep(Duration::from_millis(1));
+        });
+        let report = instrumentation.report();
+        assert!(report.direct >= Duration::from_millis(5));
+        assert!(report.forward_fft >= Duration::from_millis(1));
+        assert_eq!(report.backward_fft, Duration::ZERO);
+        assert_eq!(
+            report.total(),
+            report.forward_fft + report.direct + report.copy
+        );
+        assert!(report.to_string().contains("ForwardFft"));
+
+        // Each node only sees its own time
+        let nodes = instrumentation.node_reports();
+        assert_eq!(nodes.len(), 3);
+        assert_eq!(nodes[2].total(), report.direct);
+        assert_eq!(nodes[1].total(), report.forward_fft);
+        assert_eq!(nodes[0].total(), report.copy);
+        assert_eq!(instrumentation.node_report(2), nodes[2]);
+
+        instrumentation.reset();
+        assert_eq!(instrumentation.report(), TimingReport::default());
+        assert_eq!(instrumentation.node_report(2), TimingReport::default());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::fft_solver::*;
+use crate::mem_fmt::*;
 use crate::util::*;
 use std::io::prelude::*;
 use std::ops::Range;
@@ -8,7 +9,8 @@ use std::ops::Range;
 /// periodic solve and the boundary solves.
 /// The boundary solve nodes are assumed to be a contiguous range of
 /// nodes.
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PeriodicSolveNode<const GRID_DIMENSION: usize> {
     /// Required input buffer
     pub input_aabb: AABB<GRID_DIMENSION>,
@@ -33,7 +35,8 @@ pub struct PeriodicSolveNode<const GRID_DIMENSION: usize> {
 /// steps and sloped sides.
 /// Strictly speaking we don't need the output_aabb,
 /// but its remains useful for debugging.
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DirectSolveNode<const GRID_DIMENSION: usize> {
     pub input_aabb: AABB<GRID_DIMENSION>,
     pub output_aabb: AABB<GRID_DIMENSION>,
@@ -46,7 +49,8 @@ pub struct DirectSolveNode<const GRID_DIMENSION: usize> {
 /// need to be repeated many times to achieve the desired number of steps.
 /// Possible followed by a single periodic solve to get the remainder
 /// of steps.
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RepeatNode {
     pub n: usize,
     pub node: NodeId,
@@ -54,7 +58,8 @@ pub struct RepeatNode {
 }
 
 /// These nodes form a tree.
-#[derive(Debug)]
+#[derive(Clone, Debug, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PlanNode<const GRID_DIMENSION: usize> {
     PeriodicSolve(PeriodicSolveNode<GRID_DIMENSION>),
     DirectSolve(DirectSolveNode<GRID_DIMENSION>),
@@ -64,11 +69,57 @@ pub enum PlanNode<const GRID_DIMENSION: usize> {
 /// An `APPlan` describes an aperiodic solve over a fixed AABB
 /// for fixed number of time steps.
 /// The root node should always be the only repeat node in the tree.
+///
+/// With the `serde` feature plans can be saved to and loaded from JSON,
+/// see `APPlan::save_json`.
+#[derive(Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct APPlan<const GRID_DIMENSION: usize> {
     pub nodes: Vec<PlanNode<GRID_DIMENSION>>,
     pub root: NodeId,
 }
 
+/// Summary of an `APPlan`, see `APPlan::statistics`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlanStats<const GRID_DIMENSION: usize> {
+    pub periodic_nodes: usize,
+    pub direct_nodes: usize,
+    pub repeat_nodes: usize,
+
+    /// See `APPlan::required_transform_sizes`.
+    pub transform_sizes: Vec<Coord<GRID_DIMENSION>>,
+
+    /// Scratch space an `APSolver` allocates for the plan.
+    pub scratch_bytes: usize,
+
+    /// Longest chain of links from the root,
+    /// following repeated nodes, boundary nodes, and time cuts.
+    pub max_depth: usize,
+}
+
+impl<const GRID_DIMENSION: usize> std::fmt::Display
+    for PlanStats<GRID_DIMENSION>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "AP Plan Statistics:")?;
+        writeln!(f, "  - periodic nodes: {}", self.periodic_nodes)?;
+        writeln!(f, "  - direct nodes: {}", self.direct_nodes)?;
+        writeln!(f, "  - repeat nodes: {}", self.repeat_nodes)?;
+        let sizes: Vec<String> = self
+            .transform_sizes
+            .iter()
+            .map(|size| format!("{:?}", size.as_slice()))
+            .collect();
+        writeln!(f, "  - transform sizes: {}", sizes.join(", "))?;
+        writeln!(
+            f,
+            "  - scratch size: {}",
+            human_readable_bytes(self.scratch_bytes)
+        )?;
+        write!(f, "  - max depth: {}", self.max_depth)
+    }
+}
+
 impl<const GRID_DIMENSION: usize> APPlan<GRID_DIMENSION> {
     /// Retrieve a node
     pub fn get_node(&self, node: NodeId) -> &PlanNode<GRID_DIMENSION> {
@@ -111,6 +162,86 @@ impl<const GRID_DIMENSION: usize> APPlan<GRID_DIMENSION> {
         }
     }
 
+    /// Retrieve the output AABB of a periodic or direct node.
+    #[track_caller]
+    pub fn node_output_aabb(&self, node_id: NodeId) -> &AABB<GRID_DIMENSION> {
+        match self.get_node(node_id) {
+            PlanNode::PeriodicSolve(periodic_node) => {
+                &periodic_node.output_aabb
+            }
+            PlanNode::DirectSolve(direct_node) => &direct_node.output_aabb,
+            PlanNode::Repeat(_) => {
+                panic!("ERROR: Repeat node has no output aabb, {}", node_id)
+            }
+        }
+    }
+
+    /// Exclusive bounds of every distinct FFT the plan uses,
+    /// in plan order.
+    pub fn required_transform_sizes(&self) -> Vec<Coord<GRID_DIMENSION>> {
+        let mut result = Vec::new();
+        for node in self.nodes.iter() {
+            if let PlanNode::PeriodicSolve(periodic_solve) = node {
+                let size = periodic_solve.input_aabb.exclusive_bounds();
+                if !result.contains(&size) {
+                    result.push(size);
+                }
+            }
+        }
+        result
+    }
+
+    /// Node counts, transform sizes, scratch size, and depth,
+    /// without creating a solver.
+    pub fn statistics(&self) -> PlanStats<GRID_DIMENSION> {
+        let mut periodic_nodes = 0;
+        let mut direct_nodes = 0;
+        let mut repeat_nodes = 0;
+        for node in self.nodes.iter() {
+            match node {
+                PlanNode::PeriodicSolve(_) => periodic_nodes += 1,
+                PlanNode::DirectSolve(_) => direct_nodes += 1,
+                PlanNode::Repeat(_) => repeat_nodes += 1,
+            }
+        }
+        PlanStats {
+            periodic_nodes,
+            direct_nodes,
+            repeat_nodes,
+            transform_sizes: self.required_transform_sizes(),
+            scratch_bytes: APScratchBuilder::scratch_bytes(self),
+            max_depth: self.node_depth(self.root),
+        }
+    }
+
+    fn node_depth(&self, node_id: NodeId) -> usize {
+        let children: Vec<NodeId> = match self.get_node(node_id) {
+            PlanNode::PeriodicSolve(p) => {
+                p.boundary_nodes.clone().chain(p.time_cut).collect()
+            }
+            PlanNode::DirectSolve(_) => Vec::new(),
+            PlanNode::Repeat(r) => {
+                std::iter::once(r.node).chain(r.next).collect()
+            }
+        };
+        children
+            .into_iter()
+            .map(|child| self.node_depth(child) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Order boundary nodes by output size, largest first,
+    /// ties keep plan order.
+    /// Spawning big solves first helps avoid stragglers.
+    pub fn boundary_nodes_by_size(&self, nodes: Range<NodeId>) -> Vec<NodeId> {
+        let mut result: Vec<NodeId> = nodes.collect();
+        result.sort_by_key(|node_id| {
+            std::cmp::Reverse(self.node_output_aabb(*node_id).buffer_size())
+        });
+        result
+    }
+
     /// Number of nodes in the plan
     pub fn len(&self) -> usize {
         self.nodes.len()
@@ -121,6 +252,232 @@ impl<const GRID_DIMENSION: usize> APPlan<GRID_DIMENSION> {
         self.nodes.is_empty()
     }
 
+    /// Hash of the plan structure, used to identify output.
+    /// Stable for a given build, but `DefaultHasher` may change
+    /// between Rust releases.
+    pub fn plan_hash(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Longest chain of dependent node costs from the root.
+    /// Boundary solves run in parallel after their periodic solve,
+    /// time cuts run after both, and repeats run their node `n` times
+    /// before `next`.
+    /// Dividing the total work by this bounds the parallel speedup.
+    pub fn critical_path<F>(&self, node_cost: F) -> f64
+    where
+        F: Fn(&PlanNode<GRID_DIMENSION>) -> f64,
+    {
+        self.node_critical_path(self.root, &node_cost)
+    }
+
+    fn node_critical_path<F>(&self, node_id: NodeId, node_cost: &F) -> f64
+    where
+        F: Fn(&PlanNode<GRID_DIMENSION>) -> f64,
+    {
+        let node = self.get_node(node_id);
+        let cost = node_cost(node);
+        match node {
+            PlanNode::PeriodicSolve(periodic_solve) => {
+                let boundary = periodic_solve
+                    .boundary_nodes
+                    .clone()
+                    .map(|b| self.node_critical_path(b, node_cost))
+                    .fold(0.0, f64::max);
+                let time_cut = periodic_solve
+                    .time_cut
+                    .map_or(0.0, |t| self.node_critical_path(t, node_cost));
+                cost + boundary + time_cut
+            }
+            PlanNode::DirectSolve(_) => cost,
+            PlanNode::Repeat(repeat) => {
+                let next = repeat
+                    .next
+                    .map_or(0.0, |n| self.node_critical_path(n, node_cost));
+                cost + repeat.n as f64
+                    * self.node_critical_path(repeat.node, node_cost)
+                    + next
+            }
+        }
+    }
+
+    /// Fold nested and chained repeats into the root repeat node.
+    /// A body that is itself a repeat without `next` multiplies the counts,
+    /// and a `next` repeating the same body adds them.
+    /// Unreachable nodes are then removed, keeping plan order
+    /// so boundary node ranges stay contiguous.
+    /// `APSolver` expects periodic solves under the root,
+    /// so run this on hand built plans before creating a solver.
+    pub fn coalesce_repeats(&mut self) {
+        loop {
+            let root = self.unwrap_repeat_node(self.root).clone();
+            let mut merged = root.clone();
+            match self.get_node(root.node) {
+                PlanNode::Repeat(body) if body.next.is_none() => {
+                    merged.n *= body.n;
+                    merged.node = body.node;
+                }
+                _ => {
+                    if let Some(PlanNode::Repeat(next)) =
+                        root.next.map(|next| self.get_node(next))
+                    {
+                        if next.node == root.node {
+                            merged.n += next.n;
+                            merged.next = next.next;
+                        }
+                    }
+                }
+            }
+            if merged.node == root.node && merged.next == root.next {
+                break;
+            }
+            self.nodes[self.root] = PlanNode::Repeat(merged);
+        }
+        self.remove_unreachable();
+    }
+
+    fn remove_unreachable(&mut self) {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = vec![self.root];
+        while let Some(node_id) = stack.pop() {
+            if reachable[node_id] {
+                continue;
+            }
+            reachable[node_id] = true;
+            match self.get_node(node_id) {
+                PlanNode::PeriodicSolve(periodic_solve) => {
+                    stack.extend(periodic_solve.boundary_nodes.clone());
+                    stack.extend(periodic_solve.time_cut);
+                }
+                PlanNode::DirectSolve(_) => {}
+                PlanNode::Repeat(repeat) => {
+                    stack.push(repeat.node);
+                    stack.extend(repeat.next);
+                }
+            }
+        }
+
+        let mut new_ids = vec![0; self.nodes.len()];
+        let mut next_id = 0;
+        for (old_id, is_reachable) in reachable.iter().enumerate() {
+            if *is_reachable {
+                new_ids[old_id] = next_id;
+                next_id += 1;
+            }
+        }
+
+        let nodes = std::mem::take(&mut self.nodes);
+        self.nodes = nodes
+            .into_iter()
+            .enumerate()
+            .filter(|(old_id, _)| reachable[*old_id])
+            .map(|(_, mut node)| {
+                match &mut node {
+                    PlanNode::PeriodicSolve(periodic_solve) => {
+                        let range = &periodic_solve.boundary_nodes;
+                        let start = range.start;
+                        let len = range.len();
+                        if len > 0 {
+                            let new_start = new_ids[start];
+                            periodic_solve.boundary_nodes =
+                                new_start..new_start + len;
+                        }
+                        periodic_solve.time_cut =
+                            periodic_solve.time_cut.map(|t| new_ids[t]);
+                    }
+                    PlanNode::DirectSolve(_) => {}
+                    PlanNode::Repeat(repeat) => {
+                        repeat.node = new_ids[repeat.node];
+                        repeat.next = repeat.next.map(|n| new_ids[n]);
+                    }
+                }
+                node
+            })
+            .collect();
+        self.root = new_ids[self.root];
+    }
+
+    /// The plan as a JSON object with `root` and `nodes`,
+    /// each node has an `id` and `type`, as well as its fields.
+    pub fn to_json(&self) -> String {
+        let nodes: Vec<String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| match node {
+                PlanNode::PeriodicSolve(p) => format!(
+                    "{{\"id\": {}, \"type\": \"periodic\", \"input\": {}, \"output\": {}, \"convolution_id\": {}, \"steps\": {}, \"boundary_nodes\": [{}, {}], \"time_cut\": {}}}",
+                    id,
+                    bounds_json(&p.input_aabb.bounds),
+                    bounds_json(&p.output_aabb.bounds),
+                    p.convolution_id,
+                    p.steps,
+                    p.boundary_nodes.start,
+                    p.boundary_nodes.end,
+                    option_json(p.time_cut)
+                ),
+                PlanNode::DirectSolve(d) => format!(
+                    "{{\"id\": {}, \"type\": \"direct\", \"input\": {}, \"output\": {}, \"sloped_sides\": {}, \"steps\": {}}}",
+                    id,
+                    bounds_json(&d.input_aabb.bounds),
+                    bounds_json(&d.output_aabb.bounds),
+                    bounds_json(&d.sloped_sides),
+                    d.steps
+                ),
+                PlanNode::Repeat(r) => format!(
+                    "{{\"id\": {}, \"type\": \"repeat\", \"n\": {}, \"node\": {}, \"next\": {}}}",
+                    id,
+                    r.n,
+                    r.node,
+                    option_json(r.next)
+                ),
+            })
+            .collect();
+        format!(
+            "{{\"root\": {}, \"nodes\": [\n{}\n]}}",
+            self.root,
+            nodes.join(",\n")
+        )
+    }
+
+    /// Write the plan to `path` as JSON.
+    /// Only the nodes are saved, the `ConvolutionStore` holds FFTW plans
+    /// and can't be serialized.
+    /// Periodic nodes refer to convolutions by `convolution_id`,
+    /// so a loaded plan is only usable alongside convolutions rebuilt
+    /// for the same stencil and aabb.
+    #[cfg(feature = "serde")]
+    pub fn save_json<P: AsRef<std::path::Path>>(&self, path: &P) {
+        let writer = std::io::BufWriter::new(
+            std::fs::File::create(path).unwrap_or_else(|error| {
+                panic!(
+                    "ERROR: Can't create plan {:?}: {}",
+                    path.as_ref(),
+                    error
+                )
+            }),
+        );
+        serde_json::to_writer(writer, self).unwrap_or_else(|error| {
+            panic!("ERROR: Can't write plan {:?}: {}", path.as_ref(), error)
+        });
+    }
+
+    /// Read a plan written by `save_json`.
+    #[cfg(feature = "serde")]
+    pub fn load_json<P: AsRef<std::path::Path>>(path: &P) -> Self {
+        let reader = std::io::BufReader::new(
+            std::fs::File::open(path).unwrap_or_else(|error| {
+                panic!("ERROR: Can't read plan {:?}: {}", path.as_ref(), error)
+            }),
+        );
+        serde_json::from_reader(reader).unwrap_or_else(|error| {
+            panic!("ERROR: Invalid plan {:?}: {}", path.as_ref(), error)
+        })
+    }
+
     /// Write out the plan as a dot language graph to specified path.
     pub fn to_dot_file<P: AsRef<std::path::Path>>(&self, path: &P) {
         println!("Writing plan dot: {:?}", path.as_ref());
@@ -193,3 +550,130 @@ impl<const GRID_DIMENSION: usize> APPlan<GRID_DIMENSION> {
         writeln!(writer, "}}").unwrap();
     }
 }
+
+/// `[[min_0, max_0], [min_1, max_1], ...]`
+pub fn bounds_json<const GRID_DIMENSION: usize>(
+    bounds: &Bounds<GRID_DIMENSION>,
+) -> String {
+    let rows: Vec<String> = (0..GRID_DIMENSION)
+        .map(|d| format!("[{}, {}]", bounds[(d, 0)], bounds[(d, 1)]))
+        .collect();
+    format!("[{}]", rows.join(", "))
+}
+
+/// JSON has no NaN or infinity, those become `null`.
+pub fn f64_json(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+fn option_json(value: Option<usize>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "null".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn direct(cost: i32) -> PlanNode<1> {
+        PlanNode::DirectSolve(DirectSolveNode {
+            input_aabb: AABB::new(matrix![0, cost - 1]),
+            output_aabb: AABB::new(matrix![0, cost - 1]),
+            sloped_sides: matrix![1, 1],
+            steps: 1,
+        })
+    }
+
+    fn periodic(
+        cost: usize,
+        boundary_nodes: Range<NodeId>,
+        time_cut: Option<NodeId>,
+    ) -> PlanNode<1> {
+        PlanNode::PeriodicSolve(PeriodicSolveNode {
+            input_aabb: AABB::new(matrix![0, 99]),
+            output_aabb: AABB::new(matrix![0, 99]),
+            convolution_id: 0,
+            steps: cost,
+            boundary_nodes,
+            time_cut,
+        })
+    }
+
+    #[test]
+    fn critical_path_test() {
+        // n_3 has boundaries n_0 and n_1 (which has boundary n_2),
+        // and time cut n_4. n_5 repeats n_3 three times, then n_6.
+        let nodes = vec![
+            direct(5),
+            periodic(1, 2..3, None),
+            direct(3),
+            periodic(10, 0..2, Some(4)),
+            direct(2),
+            PlanNode::Repeat(RepeatNode {
+                n: 3,
+                node: 3,
+                next: Some(6),
+            }),
+            periodic(4, 7..8, None),
+            direct(6),
+        ];
+        let plan = APPlan { nodes, root: 5 };
+        let cost = |node: &PlanNode<1>| match node {
+            PlanNode::PeriodicSolve(p) => p.steps as f64,
+            PlanNode::DirectSolve(d) => d.output_aabb.buffer_size() as f64,
+            PlanNode::Repeat(_) => 0.0,
+        };
+
+        // n_3: 10 + max(5, 1 + 3) + 2 = 17
+        // n_6: 4 + 6 = 10
+        assert_eq!(plan.critical_path(cost), 3.0 * 17.0 + 10.0);
+
+        // With unit cost, the longest chain of single nodes
+        assert_eq!(plan.critical_path(|_| 1.0), 1.0 + 3.0 * 4.0 + 2.0);
+    }
+
+    #[test]
+    fn statistics_test() {
+        // Same plan as critical_path_test
+        let nodes = vec![
+            direct(5),
+            periodic(1, 2..3, None),
+            direct(3),
+            periodic(10, 0..2, Some(4)),
+            direct(2),
+            PlanNode::Repeat(RepeatNode {
+                n: 3,
+                node: 3,
+                next: Some(6),
+            }),
+            periodic(4, 7..8, None),
+            direct(6),
+        ];
+        let plan = APPlan { nodes, root: 5 };
+        let stats = plan.statistics();
+        assert_eq!(stats.periodic_nodes, 3);
+        assert_eq!(stats.direct_nodes, 4);
+        assert_eq!(stats.repeat_nodes, 1);
+        assert_eq!(stats.transform_sizes, vec![vector![100]]);
+
+        // In 128 byte blocks, a real [0, 99] buffer is 800 bytes, 7 blocks,
+        // a complex one 51 values, 816 bytes, also 7 blocks,
+        // and each direct solve's real buffer fits in 1 block.
+        // n_3 with pre-allocated io needs its boundaries,
+        //   n_0: 2 * 1 = 2,
+        //   n_1: max(n_2 = 2, complex 7) + 2 * 7 = 21,
+        // so max(2 + 21, complex 7) = 23, its time cut n_4 needs 0.
+        // n_6 needs max(n_7 = 2, complex 7) = 7.
+        assert_eq!(stats.scratch_bytes, 23 * 128);
+
+        // n_5 -> n_3 -> n_1 -> n_2
+        assert_eq!(stats.max_depth, 3);
+        assert!(stats.to_string().contains("max depth: 3"));
+    }
+}
@@ -0,0 +1,521 @@
+use crate::fft_solver::*;
+use crate::util::*;
+
+/// Describes the first broken invariant found by `APPlan::verify`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlanError<const GRID_DIMENSION: usize> {
+    /// A node refers to an id past the end of the plan.
+    InvalidNodeId {
+        node: NodeId,
+        id: NodeId,
+    },
+
+    /// The root must be the only repeat node.
+    RootNotRepeat {
+        root: NodeId,
+    },
+    UnexpectedRepeat {
+        node: NodeId,
+    },
+
+    /// Repeat nodes must repeat at least once,
+    /// and repeat periodic solves over the whole domain.
+    InvalidRepeat {
+        node: NodeId,
+        n: usize,
+    },
+    RepeatNotGlobal {
+        node: NodeId,
+    },
+
+    /// Every node reads from within the global domain.
+    InputOutsideDomain {
+        node: NodeId,
+        input: AABB<GRID_DIMENSION>,
+        domain: AABB<GRID_DIMENSION>,
+    },
+
+    /// Every node writes within what it reads.
+    OutputOutsideInput {
+        node: NodeId,
+        input: AABB<GRID_DIMENSION>,
+        output: AABB<GRID_DIMENSION>,
+    },
+
+    /// Boundary nodes read from their periodic parent's input.
+    InputOutsideParent {
+        node: NodeId,
+        parent: NodeId,
+        input: AABB<GRID_DIMENSION>,
+        parent_input: AABB<GRID_DIMENSION>,
+    },
+
+    /// Boundary nodes write to mutually exclusive regions.
+    BoundaryOverlap {
+        parent: NodeId,
+        a: NodeId,
+        b: NodeId,
+    },
+
+    /// Central solves, those of the root repeat node,
+    /// must be tiled by their output and boundary outputs.
+    BoundaryOverlapsOutput {
+        parent: NodeId,
+        node: NodeId,
+    },
+    BoundaryGap {
+        parent: NodeId,
+        covered: usize,
+        required: usize,
+    },
+
+    /// Time cuts read from the output of the solve they follow.
+    TimeCutInput {
+        node: NodeId,
+        parent: NodeId,
+        input: AABB<GRID_DIMENSION>,
+        parent_output: AABB<GRID_DIMENSION>,
+    },
+}
+
+impl<const GRID_DIMENSION: usize> std::fmt::Display
+    for PlanError<GRID_DIMENSION>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::InvalidNodeId { node, id } => {
+                write!(f, "n_{} refers to invalid node id {}", node, id)
+            }
+            PlanError::RootNotRepeat { root } => {
+                write!(f, "root n_{} is not a repeat node", root)
+            }
+            PlanError::UnexpectedRepeat { node } => {
+                write!(f, "n_{} is a repeat node but not the root", node)
+            }
+            PlanError::InvalidRepeat { node, n } => {
+                write!(f, "repeat n_{} has invalid count {}", node, n)
+            }
+            PlanError::RepeatNotGlobal { node } => write!(
+                f,
+                "repeat n_{} must refer to periodic solves over the domain",
+                node
+            ),
+            PlanError::InputOutsideDomain {
+                node,
+                input,
+                domain,
+            } => write!(
+                f,
+                "n_{} input {} is outside domain {}",
+                node, input, domain
+            ),
+            PlanError::OutputOutsideInput {
+                node,
+                input,
+                output,
+            } => write!(
+                f,
+                "n_{} output {} is outside input {}",
+                node, output, input
+            ),
+            PlanError::InputOutsideParent {
+                node,
+                parent,
+                input,
+                parent_input,
+            } => write!(
+                f,
+                "boundary n_{} input {} is outside parent n_{} input {}",
+                node, input, parent, parent_input
+            ),
+            PlanError::BoundaryOverlap { parent, a, b } => write!(
+                f,
+                "boundary nodes n_{} and n_{} of n_{} overlap",
+                a, b, parent
+            ),
+            PlanError::BoundaryOverlapsOutput { parent, node } => write!(
+                f,
+                "boundary n_{} overlaps the output of n_{}",
+                node, parent
+            ),
+            PlanError::BoundaryGap {
+                parent,
+                covered,
+                required,
+            } => write!(
+                f,
+                "n_{} output and boundary nodes cover {} of {} cells",
+                parent, covered, required
+            ),
+            PlanError::TimeCutInput {
+                node,
+                parent,
+                input,
+                parent_output,
+            } => write!(
+                f,
+                "time cut n_{} input {} is outside n_{} output {}",
+                node, input, parent, parent_output
+            ),
+        }
+    }
+}
+
+impl<const GRID_DIMENSION: usize> std::error::Error
+    for PlanError<GRID_DIMENSION>
+{
+}
+
+fn overlaps<const GRID_DIMENSION: usize>(
+    a: &AABB<GRID_DIMENSION>,
+    b: &AABB<GRID_DIMENSION>,
+) -> bool {
+    a.intersect(b).is_some()
+}
+
+impl<const GRID_DIMENSION: usize> APPlan<GRID_DIMENSION> {
+    /// Check the invariants the solver relies on,
+    /// starting from the root and reporting the first problem found.
+    /// Relationships between a node and its children are checked
+    /// before recursing into the children.
+    pub fn verify(
+        &self,
+        global_aabb: &AABB<GRID_DIMENSION>,
+    ) -> Result<(), PlanError<GRID_DIMENSION>> {
+        let root = self.root;
+        let repeat_node = match self.nodes.get(root) {
+            Some(PlanNode::Repeat(repeat_node)) => repeat_node,
+            Some(_) => return Err(PlanError::RootNotRepeat { root }),
+            None => {
+                return Err(PlanError::InvalidNodeId {
+                    node: root,
+                    id: root,
+                })
+            }
+        };
+        if repeat_node.n == 0 {
+            return Err(PlanError::InvalidRepeat {
+                node: root,
+                n: repeat_node.n,
+            });
+        }
+        for id in std::iter::once(repeat_node.node).chain(repeat_node.next) {
+            match self.nodes.get(id) {
+                Some(PlanNode::PeriodicSolve(periodic_solve))
+                    if periodic_solve.input_aabb == *global_aabb => {}
+                Some(_) => {
+                    return Err(PlanError::RepeatNotGlobal { node: root })
+                }
+                None => {
+                    return Err(PlanError::InvalidNodeId { node: root, id })
+                }
+            }
+            self.verify_node(id, global_aabb)?;
+            self.verify_coverage(id)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the output and boundary outputs of a central solve
+    /// tile its input.
+    /// With containment and pairwise disjointness already checked,
+    /// the volumes must add up.
+    /// Frustrum periodic solves aren't checked,
+    /// since the plan doesn't record the region their convolution covers.
+    fn verify_coverage(
+        &self,
+        node_id: NodeId,
+    ) -> Result<(), PlanError<GRID_DIMENSION>> {
+        let periodic_solve = self.unwrap_periodic_node(node_id);
+        let mut covered = periodic_solve.output_aabb.buffer_size();
+        for b in periodic_solve.boundary_nodes.clone() {
+            let b_output = self.node_output_aabb(b);
+            if overlaps(b_output, &periodic_solve.output_aabb) {
+                return Err(PlanError::BoundaryOverlapsOutput {
+                    parent: node_id,
+                    node: b,
+                });
+            }
+            covered += b_output.buffer_size();
+        }
+        let required = periodic_solve.input_aabb.buffer_size();
+        if covered != required {
+            return Err(PlanError::BoundaryGap {
+                parent: node_id,
+                covered,
+                required,
+            });
+        }
+        Ok(())
+    }
+
+    fn check_id(
+        &self,
+        node: NodeId,
+        id: NodeId,
+    ) -> Result<&PlanNode<GRID_DIMENSION>, PlanError<GRID_DIMENSION>> {
+        match self.nodes.get(id) {
+            Some(PlanNode::Repeat(_)) => {
+                Err(PlanError::UnexpectedRepeat { node: id })
+            }
+            Some(n) => Ok(n),
+            None => Err(PlanError::InvalidNodeId { node, id }),
+        }
+    }
+
+    fn verify_node(
+        &self,
+        node_id: NodeId,
+        global_aabb: &AABB<GRID_DIMENSION>,
+    ) -> Result<(), PlanError<GRID_DIMENSION>> {
+        let (input, output) = match self.check_id(node_id, node_id)? {
+            PlanNode::PeriodicSolve(p) => (p.input_aabb, p.output_aabb),
+            PlanNode::DirectSolve(d) => (d.input_aabb, d.output_aabb),
+            PlanNode::Repeat(_) => unreachable!(),
+        };
+        if !global_aabb.contains_aabb(&input) {
+            return Err(PlanError::InputOutsideDomain {
+                node: node_id,
+                input,
+                domain: *global_aabb,
+            });
+        }
+        if !input.contains_aabb(&output) {
+            return Err(PlanError::OutputOutsideInput {
+                node: node_id,
+                input,
+                output,
+            });
+        }
+
+        let periodic_solve = match self.get_node(node_id) {
+            PlanNode::PeriodicSolve(periodic_solve) => periodic_solve,
+            _ => return Ok(()),
+        };
+
+        // Check boundary nodes against parent and each other
+        let boundary_nodes = periodic_solve.boundary_nodes.clone();
+        for a in boundary_nodes.clone() {
+            self.check_id(node_id, a)?;
+            let a_input = self.node_input_aabb(a);
+            if !input.contains_aabb(&a_input) {
+                return Err(PlanError::InputOutsideParent {
+                    node: a,
+                    parent: node_id,
+                    input: a_input,
+                    parent_input: input,
+                });
+            }
+            for b in (a + 1)..boundary_nodes.end {
+                self.check_id(node_id, b)?;
+                if overlaps(self.node_output_aabb(a), self.node_output_aabb(b))
+                {
+                    return Err(PlanError::BoundaryOverlap {
+                        parent: node_id,
+                        a,
+                        b,
+                    });
+                }
+            }
+        }
+
+        // Check time cut against our output
+        if let Some(next) = periodic_solve.time_cut {
+            self.check_id(node_id, next)?;
+            let next_input = self.node_input_aabb(next);
+            if !output.contains_aabb(&next_input) {
+                return Err(PlanError::TimeCutInput {
+                    node: next,
+                    parent: node_id,
+                    input: next_input,
+                    parent_output: output,
+                });
+            }
+        }
+
+        for child in boundary_nodes.chain(periodic_solve.time_cut) {
+            self.verify_node(child, global_aabb)?;
+        }
+        Ok(())
+    }
+
+    fn node_input_aabb(&self, node_id: NodeId) -> AABB<GRID_DIMENSION> {
+        match self.get_node(node_id) {
+            PlanNode::PeriodicSolve(p) => p.input_aabb,
+            PlanNode::DirectSolve(d) => d.input_aabb,
+            PlanNode::Repeat(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn direct(input: Bounds<1>, output: Bounds<1>) -> PlanNode<1> {
+        PlanNode::DirectSolve(DirectSolveNode {
+            input_aabb: AABB::new(input),
+            output_aabb: AABB::new(output),
+            sloped_sides: matrix![1, 1],
+            steps: 10,
+        })
+    }
+
+    /// Small hand built plan over [0, 99].
+    /// n_2 is the central solve with boundary nodes n_0, n_1,
+    /// and a time cut n_3.
+    fn test_plan() -> APPlan<1> {
+        let nodes = vec![
+            direct(matrix![0, 20], matrix![0, 9]),
+            direct(matrix![79, 99], matrix![90, 99]),
+            PlanNode::PeriodicSolve(PeriodicSolveNode {
+                input_aabb: AABB::new(matrix![0, 99]),
+                output_aabb: AABB::new(matrix![10, 89]),
+                convolution_id: 0,
+                steps: 10,
+                boundary_nodes: 0..2,
+                time_cut: Some(3),
+            }),
+            direct(matrix![10, 89], matrix![20, 79]),
+            PlanNode::Repeat(RepeatNode {
+                n: 3,
+                node: 2,
+                next: None,
+            }),
+        ];
+        APPlan { nodes, root: 4 }
+    }
+
+    fn set_input(plan: &mut APPlan<1>, node: NodeId, input: Bounds<1>) {
+        match &mut plan.nodes[node] {
+            PlanNode::DirectSolve(d) => d.input_aabb = AABB::new(input),
+            PlanNode::PeriodicSolve(p) => p.input_aabb = AABB::new(input),
+            PlanNode::Repeat(_) => panic!(),
+        }
+    }
+
+    #[test]
+    fn verify_valid_test() {
+        let domain = AABB::new(matrix![0, 99]);
+        assert_eq!(test_plan().verify(&domain), Ok(()));
+    }
+
+    #[test]
+    fn verify_corrupt_test() {
+        let domain = AABB::new(matrix![0, 99]);
+
+        // Root not a repeat
+        {
+            let mut plan = test_plan();
+            plan.root = 2;
+            assert_eq!(
+                plan.verify(&domain),
+                Err(PlanError::RootNotRepeat { root: 2 })
+            );
+        }
+
+        // Zero repeats
+        {
+            let mut plan = test_plan();
+            plan.nodes[4] = PlanNode::Repeat(RepeatNode {
+                n: 0,
+                node: 2,
+                next: None,
+            });
+            assert!(matches!(
+                plan.verify(&domain),
+                Err(PlanError::InvalidRepeat { node: 4, n: 0 })
+            ));
+        }
+
+        // Boundary reads outside parent, and domain
+        {
+            let mut plan = test_plan();
+            set_input(&mut plan, 1, matrix![79, 100]);
+            assert!(matches!(
+                plan.verify(&domain),
+                Err(PlanError::InputOutsideParent {
+                    node: 1,
+                    parent: 2,
+                    ..
+                })
+            ));
+        }
+
+        // Boundary outputs overlap
+        {
+            let mut plan = test_plan();
+            plan.nodes[1] = direct(matrix![0, 20], matrix![5, 12]);
+            assert_eq!(
+                plan.verify(&domain),
+                Err(PlanError::BoundaryOverlap {
+                    parent: 2,
+                    a: 0,
+                    b: 1
+                })
+            );
+        }
+
+        // Missing boundary node leaves a gap
+        {
+            let mut plan = test_plan();
+            if let PlanNode::PeriodicSolve(p) = &mut plan.nodes[2] {
+                p.boundary_nodes = 0..1;
+            }
+            assert_eq!(
+                plan.verify(&domain),
+                Err(PlanError::BoundaryGap {
+                    parent: 2,
+                    covered: 90,
+                    required: 100
+                })
+            );
+        }
+
+        // Boundary writes over the central output
+        {
+            let mut plan = test_plan();
+            plan.nodes[1] = direct(matrix![79, 99], matrix![85, 99]);
+            assert_eq!(
+                plan.verify(&domain),
+                Err(PlanError::BoundaryOverlapsOutput { parent: 2, node: 1 })
+            );
+        }
+
+        // Time cut reads outside our output
+        {
+            let mut plan = test_plan();
+            set_input(&mut plan, 3, matrix![5, 89]);
+            assert!(matches!(
+                plan.verify(&domain),
+                Err(PlanError::TimeCutInput {
+                    node: 3,
+                    parent: 2,
+                    ..
+                })
+            ));
+        }
+
+        // Output outside input
+        {
+            let mut plan = test_plan();
+            plan.nodes[3] = direct(matrix![10, 89], matrix![20, 95]);
+            assert!(matches!(
+                plan.verify(&domain),
+                Err(PlanError::OutputOutsideInput { node: 3, .. })
+            ));
+        }
+
+        // Invalid ids
+        {
+            let mut plan = test_plan();
+            if let PlanNode::PeriodicSolve(p) = &mut plan.nodes[2] {
+                p.time_cut = Some(17);
+            }
+            assert_eq!(
+                plan.verify(&domain),
+                Err(PlanError::InvalidNodeId { node: 2, id: 17 })
+            );
+        }
+    }
+}
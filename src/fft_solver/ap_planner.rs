@@ -4,25 +4,55 @@ use crate::util::*;
 
 /// Planner recurses by finding periodic solves.
 /// These solves are configured with these parameters.
+#[derive(Copy, Clone, Debug)]
 pub struct PlannerParameters {
     pub plan_type: PlanType,
+
+    /// Retry with `PlanType::Estimate` if FFTW planning fails.
+    pub plan_fallback: bool,
     pub cutoff: i32,
     pub ratio: f64,
     pub chunk_size: usize,
+
+    /// Cap on the steps of any one periodic solve,
+    /// forcing shallower plans with more direct solving.
+    pub max_steps: Option<usize>,
+}
+
+/// Measure plans with a cutoff of 40 and ratio of 0.5.
+/// Cutoff, ratio, and chunk size match the example executables,
+/// but those default to `PlanType::Estimate`.
+impl Default for PlannerParameters {
+    fn default() -> Self {
+        PlannerParameters {
+            plan_type: PlanType::Measure,
+            plan_fallback: false,
+            cutoff: 40,
+            ratio: 0.5,
+            chunk_size: 1000,
+            max_steps: None,
+        }
+    }
 }
 
 /// Creating a plan results in both a plan and convolution store.
 /// Someday we may separate the creation, if for example we
 /// we add support for saving APPlans to file.
+#[derive(Clone)]
 pub struct PlannerResult<const GRID_DIMENSION: usize> {
     pub plan: APPlan<GRID_DIMENSION>,
     pub convolution_store: ConvolutionStore,
     pub stencil_slopes: Bounds<GRID_DIMENSION>,
+
+    /// Cutoff for each axis, see `create_ap_plan_with_cutoffs`.
+    pub cutoffs: Coord<GRID_DIMENSION>,
 }
 
 /// Given a stencil and AABB domain
 /// create an `PlannerResult`.
 /// We assume all faces of the AABB are boundary conditions.
+/// `params.cutoff` applies to every axis,
+/// see `create_ap_plan_with_cutoffs`.
 pub fn create_ap_plan<
     Operation,
     const GRID_DIMENSION: usize,
@@ -33,6 +63,32 @@ pub fn create_ap_plan<
     steps: usize,
     params: &PlannerParameters,
 ) -> PlannerResult<GRID_DIMENSION>
+where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    create_ap_plan_with_cutoffs(
+        stencil,
+        aabb,
+        steps,
+        params,
+        Coord::repeat(params.cutoff),
+    )
+}
+
+/// Like `create_ap_plan`, but with a cutoff for each axis,
+/// `params.cutoff` is ignored.
+/// Useful for domains much thinner along some axes than others.
+pub fn create_ap_plan_with_cutoffs<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    aabb: AABB<GRID_DIMENSION>,
+    steps: usize,
+    params: &PlannerParameters,
+    cutoffs: Coord<GRID_DIMENSION>,
+) -> PlannerResult<GRID_DIMENSION>
 where
     Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
 {
@@ -41,11 +97,15 @@ where
         aabb,
         steps,
         params.plan_type,
-        params.cutoff,
+        params.plan_fallback,
+        cutoffs,
         params.ratio,
         params.chunk_size,
+        params.max_steps,
     );
-    planner.finish()
+    let result = planner.finish();
+    warn_slow_transforms(&result.plan.required_transform_sizes());
+    result
 }
 
 /// Used to create an `APPlan`. See `create_ap_plan`
@@ -60,8 +120,9 @@ struct APPlanner<
     stencil_slopes: Bounds<GRID_DIMENSION>,
     aabb: AABB<GRID_DIMENSION>,
     steps: usize,
-    cutoff: i32,
+    cutoff: Coord<GRID_DIMENSION>,
     ratio: f64,
+    max_steps: Option<usize>,
     convolution_gen:
         ConvolutionGenerator<'a, Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
     nodes: Vec<PlanNode<GRID_DIMENSION>>,
@@ -81,13 +142,21 @@ where
         aabb: AABB<GRID_DIMENSION>,
         steps: usize,
         plan_type: PlanType,
-        cutoff: i32,
+        plan_fallback: bool,
+        cutoff: Coord<GRID_DIMENSION>,
         ratio: f64,
         chunk_size: usize,
+        max_steps: Option<usize>,
     ) -> Self {
+        assert_ne!(max_steps, Some(0), "ERROR: max_steps must be positive");
         let stencil_slopes = stencil.slopes();
-        let convolution_gen =
-            ConvolutionGenerator::new(&aabb, stencil, plan_type, chunk_size);
+        let convolution_gen = ConvolutionGenerator::new(
+            &aabb,
+            stencil,
+            plan_type,
+            plan_fallback,
+            chunk_size,
+        );
         let nodes = Vec::new();
         APPlanner {
             stencil_slopes,
@@ -95,11 +164,17 @@ where
             steps,
             cutoff,
             ratio,
+            max_steps,
             convolution_gen,
             nodes,
         }
     }
 
+    /// Limit steps to `max_steps`, if set.
+    fn capped_steps(&self, steps: usize) -> usize {
+        self.max_steps.map_or(steps, |max| steps.min(max))
+    }
+
     /// Pushes a node into the plan store and returns the id.
     fn add_node(&mut self, node: PlanNode<GRID_DIMENSION>) -> NodeId {
         let result = self.nodes.len();
@@ -180,7 +255,7 @@ where
             stencil_slopes: self.stencil_slopes,
             cutoff: self.cutoff,
             ratio: self.ratio,
-            max_steps: Some(frustrum.steps),
+            max_steps: Some(self.capped_steps(frustrum.steps)),
         };
         let input_aabb = frustrum.input_aabb(&self.stencil_slopes);
         debug_assert!(self.aabb.contains_aabb(&input_aabb));
@@ -207,11 +282,17 @@ where
             stencil_slopes: self.stencil_slopes,
             cutoff: self.cutoff,
             ratio: self.ratio,
-            max_steps: Some(max_steps),
+            max_steps: Some(self.capped_steps(max_steps)),
         };
 
-        let periodic_solve =
-            find_periodic_solve(&self.aabb, &solve_params).unwrap();
+        let periodic_solve = find_periodic_solve(&self.aabb, &solve_params)
+            .unwrap_or_else(|| {
+                panic!(
+                    "ERROR: No periodic solve for {}, check the cutoff, \
+                     and that degenerate axes have zero stencil slopes",
+                    self.aabb
+                )
+            });
 
         let convolution_id = self
             .convolution_gen
@@ -222,8 +303,13 @@ where
         let mut sub_nodes = Vec::with_capacity(2 * GRID_DIMENSION);
         for d in 0..GRID_DIMENSION {
             for side in [Side::Min, Side::Max] {
+                // Empty on degenerate axes
+                let boundary_aabb = decomposition[d][side];
+                if !boundary_aabb.check_validity() {
+                    continue;
+                }
                 sub_nodes.push(self.generate_frustrum(APFrustrum::new(
-                    decomposition[d][side.outer_index()],
+                    boundary_aabb,
                     d,
                     side,
                     periodic_solve.steps,
@@ -276,8 +362,22 @@ where
     }
 
     /// Package up the results
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "ap_planner_finish",
+            skip_all,
+            fields(aabb_size = self.aabb.buffer_size(), steps = self.steps)
+        )
+    )]
     fn finish(mut self) -> PlannerResult<GRID_DIMENSION> {
         let root = self.generate();
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            nodes = self.nodes.len(),
+            convolutions = self.convolution_gen.op_count(),
+            "plan created"
+        );
         let convolution_store = self.convolution_gen.finish();
         let stencil_slopes = self.stencil_slopes;
         let plan = APPlan {
@@ -289,6 +389,7 @@ where
             plan,
             convolution_store,
             stencil_slopes,
+            cutoffs: self.cutoff,
         }
     }
 }
@@ -304,28 +405,33 @@ mod unit_tests {
             cutoff: 20,
             ratio: 0.5,
             plan_type: PlanType::Estimate,
+            plan_fallback: false,
             chunk_size: 1000,
+            max_steps: None,
         };
 
         {
             let stencil = heat_1d(1.0, 1.0, 0.5);
             let aabb = AABB::new(matrix![54, 5234]);
             let steps = 10000;
-            create_ap_plan(&stencil, aabb, steps, &planner_params);
+            let result = create_ap_plan(&stencil, aabb, steps, &planner_params);
+            assert_eq!(result.plan.verify(&aabb), Ok(()));
         }
 
         {
             let stencil = heat_2d(1.0, 1.0, 1.0, 1.0, 0.5);
             let aabb = AABB::new(matrix![0, 100; 0, 100]);
             let steps = 100;
-            create_ap_plan(&stencil, aabb, steps, &planner_params);
+            let result = create_ap_plan(&stencil, aabb, steps, &planner_params);
+            assert_eq!(result.plan.verify(&aabb), Ok(()));
         }
 
         {
             let stencil = heat_2d(1.0, 1.0, 1.0, 1.0, 0.5);
             let aabb = AABB::new(matrix![555, 1234; -1234, -343]);
             let steps = 1000;
-            create_ap_plan(&stencil, aabb, steps, &planner_params);
+            let result = create_ap_plan(&stencil, aabb, steps, &planner_params);
+            assert_eq!(result.plan.verify(&aabb), Ok(()));
         }
 
         {
@@ -333,7 +439,222 @@ mod unit_tests {
                 Stencil::new([[-1], [0], [4]], |args: &[f64; 3]| args[0]);
             let aabb = AABB::new(matrix![54, 5234]);
             let steps = 10000;
-            create_ap_plan(&stencil, aabb, steps, &planner_params);
+            let result = create_ap_plan(&stencil, aabb, steps, &planner_params);
+            assert_eq!(result.plan.verify(&aabb), Ok(()));
+        }
+    }
+
+    #[test]
+    fn deterministic_convolution_ids_test() {
+        let planner_params = PlannerParameters {
+            cutoff: 20,
+            ratio: 0.5,
+            plan_type: PlanType::Estimate,
+            plan_fallback: false,
+            chunk_size: 1000,
+            max_steps: None,
+        };
+        let stencil = heat_2d(1.0, 1.0, 1.0, 1.0, 0.5);
+        let aabb = AABB::new(matrix![0, 100; 0, 100]);
+        let steps = 100;
+
+        let convolution_ids = |result: &PlannerResult<2>| -> Vec<OpId> {
+            result
+                .plan
+                .nodes
+                .iter()
+                .filter_map(|node| match node {
+                    PlanNode::PeriodicSolve(p) => Some(p.convolution_id),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let a = create_ap_plan(&stencil, aabb, steps, &planner_params);
+        let b = create_ap_plan(&stencil, aabb, steps, &planner_params);
+        assert_eq!(a.plan.nodes.len(), b.plan.nodes.len());
+        let a_ids = convolution_ids(&a);
+        assert!(!a_ids.is_empty());
+        assert_eq!(a_ids, convolution_ids(&b));
+
+        // IDs are dense
+        let max_id = *a_ids.iter().max().unwrap();
+        for id in 0..=max_id {
+            assert!(a_ids.contains(&id));
+        }
+    }
+
+    #[test]
+    fn boundary_nodes_by_size_test() {
+        let planner_params = PlannerParameters {
+            cutoff: 20,
+            ratio: 0.5,
+            plan_type: PlanType::Estimate,
+            plan_fallback: false,
+            chunk_size: 1000,
+            max_steps: None,
+        };
+        let stencil = heat_3d(1.0, 1.0, 1.0, 1.0, 0.1, 0.1, 0.1);
+        let aabb = AABB::new(matrix![0, 79; 0, 39; 0, 59]);
+        let result = create_ap_plan(&stencil, aabb, 40, &planner_params);
+        let plan = &result.plan;
+        for node in plan.nodes.iter() {
+            if let PlanNode::PeriodicSolve(periodic_solve) = node {
+                let range = periodic_solve.boundary_nodes.clone();
+                let sorted = plan.boundary_nodes_by_size(range.clone());
+
+                // Same nodes
+                let mut ids = sorted.clone();
+                ids.sort();
+                assert_eq!(ids, range.collect::<Vec<_>>());
+
+                // Largest first
+                for w in sorted.windows(2) {
+                    assert!(
+                        plan.node_output_aabb(w[0]).buffer_size()
+                            >= plan.node_output_aabb(w[1]).buffer_size()
+                    );
+                }
+            }
+        }
+    }
+
+    /// Direct solves run by one solve with the plan,
+    /// counting repeated nodes each time they run.
+    fn direct_solve_runs<const GRID_DIMENSION: usize>(
+        plan: &APPlan<GRID_DIMENSION>,
+        node_id: NodeId,
+    ) -> usize {
+        match plan.get_node(node_id) {
+            PlanNode::DirectSolve(_) => 1,
+            PlanNode::PeriodicSolve(p) => {
+                p.boundary_nodes
+                    .clone()
+                    .map(|n| direct_solve_runs(plan, n))
+                    .sum::<usize>()
+                    + p.time_cut.map_or(0, |n| direct_solve_runs(plan, n))
+            }
+            PlanNode::Repeat(r) => {
+                r.n * direct_solve_runs(plan, r.node)
+                    + r.next.map_or(0, |n| direct_solve_runs(plan, n))
+            }
+        }
+    }
+
+    #[test]
+    fn max_steps_test() {
+        let mut planner_params = PlannerParameters {
+            cutoff: 20,
+            ratio: 0.5,
+            plan_type: PlanType::Estimate,
+            plan_fallback: false,
+            chunk_size: 1000,
+            max_steps: None,
+        };
+        let stencil = heat_1d(1.0, 1.0, 0.5);
+        let aabb = AABB::new(matrix![0, 999]);
+        let steps = 400;
+        let uncapped = create_ap_plan(&stencil, aabb, steps, &planner_params);
+
+        planner_params.max_steps = Some(2);
+        let capped = create_ap_plan(&stencil, aabb, steps, &planner_params);
+        assert_eq!(capped.plan.verify(&aabb), Ok(()));
+        for node in capped.plan.nodes.iter() {
+            if let PlanNode::PeriodicSolve(p) = node {
+                assert!(p.steps <= 2);
+            }
+        }
+        let repeat = capped.plan.unwrap_repeat_node(capped.plan.root);
+        assert_eq!(repeat.n, 200);
+        assert!(
+            direct_solve_runs(&capped.plan, capped.plan.root)
+                > direct_solve_runs(&uncapped.plan, uncapped.plan.root)
+        );
+    }
+
+    #[test]
+    fn anisotropic_cutoff_test() {
+        let planner_params = PlannerParameters {
+            plan_type: PlanType::Estimate,
+            ..PlannerParameters::default()
+        };
+        let stencil = heat_3d(1.0, 1.0, 1.0, 1.0, 0.1, 0.1, 0.1);
+        let aabb = AABB::new(matrix![0, 9; 0, 9; 0, 399]);
+        let cutoffs = vector![4, 4, 40];
+
+        // Each axis is checked against its own cutoff
+        let solve_params = |cutoff| PeriodicSolveParams {
+            stencil_slopes: stencil.slopes(),
+            cutoff,
+            ratio: 0.5,
+            max_steps: None,
+        };
+        assert!(find_periodic_solve(&aabb, &solve_params(cutoffs)).is_some());
+        assert!(
+            find_periodic_solve(&aabb, &solve_params(vector![10, 4, 40]))
+                .is_none()
+        );
+        assert!(
+            find_periodic_solve(&aabb, &solve_params(vector![4, 4, 400]))
+                .is_none()
+        );
+
+        let result = create_ap_plan_with_cutoffs(
+            &stencil,
+            aabb,
+            20,
+            &planner_params,
+            cutoffs,
+        );
+        assert_eq!(result.cutoffs, cutoffs);
+        let plan = &result.plan;
+        assert_eq!(plan.verify(&aabb), Ok(()));
+        let mut stopped_by_short_axis = false;
+        for node in plan.nodes.iter() {
+            match node {
+                PlanNode::PeriodicSolve(p) => {
+                    let sides = p.input_aabb.exclusive_bounds();
+                    assert!(sides
+                        .iter()
+                        .zip(cutoffs.iter())
+                        .all(|(s, c)| s > c));
+                }
+                PlanNode::DirectSolve(direct) => {
+                    // Long axis alone would keep recursing
+                    let sides = direct.input_aabb.exclusive_bounds();
+                    if sides[0] <= cutoffs[0] && sides[2] > cutoffs[2] {
+                        stopped_by_short_axis = true;
+                    }
+                }
+                PlanNode::Repeat(_) => {}
+            }
+        }
+        assert!(stopped_by_short_axis);
+    }
+
+    #[test]
+    fn warm_wisdom_test() {
+        let planner_params = PlannerParameters {
+            cutoff: 10,
+            ratio: 0.5,
+            plan_type: PlanType::Estimate,
+            plan_fallback: false,
+            chunk_size: 1000,
+            max_steps: None,
+        };
+        let stencil = heat_2d(1.0, 1.0, 1.0, 1.0, 0.5);
+        let aabb = AABB::new(matrix![0, 66; 0, 58]);
+        let result = create_ap_plan(&stencil, aabb, 30, &planner_params);
+        let sizes = result.plan.required_transform_sizes();
+        assert!(!sizes.is_empty());
+        assert!(sizes.contains(&aabb.exclusive_bounds()));
+        for (i, size) in sizes.iter().enumerate() {
+            assert!(!sizes[i + 1..].contains(size));
+        }
+
+        warm_wisdom(&sizes, PlanType::Measure);
+        for size in sizes.iter() {
+            FFTPlan::new(size, PlanType::WisdomOnly, false);
         }
     }
 }
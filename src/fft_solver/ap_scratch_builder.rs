@@ -34,6 +34,12 @@ impl<'a, const GRID_DIMENSION: usize> APScratchBuilder<'a, GRID_DIMENSION> {
         (scratch_descriptors, scratch_space)
     }
 
+    /// Bytes of scratch space `build` would allocate for the plan.
+    pub fn scratch_bytes(plan: &'a APPlan<GRID_DIMENSION>) -> usize {
+        let node_block_requirements = APAccountBuilder::node_requirements(plan);
+        node_block_requirements[plan.root] * MIN_ALIGNMENT
+    }
+
     fn blocks_to_bytes(&self, blocks: usize) -> usize {
         blocks * MIN_ALIGNMENT
     }
@@ -1,9 +1,13 @@
+use crate::clock::SolveClock;
 use crate::domain::*;
 use crate::fft_solver::*;
 use crate::mem_fmt::*;
 use crate::stencil::*;
 use crate::util::*;
+use rayon::prelude::*;
 use std::io::prelude::*;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 pub struct APSolver<
     'a,
@@ -27,6 +31,145 @@ pub struct APSolver<
     pub node_scratch_descriptors: Vec<ScratchDescriptor>,
     pub scratch_space: APScratch,
     pub chunk_size: usize,
+    pub node_dump: Option<NodeDump>,
+    pub params: PlannerParameters,
+
+    /// Cutoff for each axis the plan was made with,
+    /// `params.cutoff` on every axis unless set per axis.
+    pub cutoffs: Coord<GRID_DIMENSION>,
+    pub instrumentation: Option<Instrumentation>,
+
+    /// Maps `global_time` to simulated time for callbacks,
+    /// see `with_dt`.
+    pub clock: SolveClock,
+}
+
+/// Scratch space that `APSolver::try_new` refused to allocate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AllocError {
+    ExceedsBudget { required: usize, budget: usize },
+    ExceedsAvailable { required: usize, available: usize },
+}
+
+impl std::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AllocError::ExceedsBudget { required, budget } => write!(
+                f,
+                "scratch space of {} exceeds budget of {}",
+                human_readable_bytes(*required),
+                human_readable_bytes(*budget)
+            ),
+            AllocError::ExceedsAvailable {
+                required,
+                available,
+            } => write!(
+                f,
+                "scratch space of {} exceeds available memory of {}",
+                human_readable_bytes(*required),
+                human_readable_bytes(*available)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// A solve checked by `APSolver::apply_checked` went wrong.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SolveError<const GRID_DIMENSION: usize> {
+    /// The output of periodic solve `node_id` has a NaN or infinity,
+    /// `coord` is the first in linear order.
+    NonFinite {
+        node_id: NodeId,
+        coord: Coord<GRID_DIMENSION>,
+    },
+}
+
+impl<const GRID_DIMENSION: usize> std::fmt::Display
+    for SolveError<GRID_DIMENSION>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::NonFinite { node_id, coord } => write!(
+                f,
+                "non-finite value at {:?} after node {}",
+                coord.as_slice(),
+                node_id
+            ),
+        }
+    }
+}
+
+impl<const GRID_DIMENSION: usize> std::error::Error
+    for SolveError<GRID_DIMENSION>
+{
+}
+
+/// Best effort query of available memory,
+/// `MemAvailable` from `/proc/meminfo` on Linux, otherwise `None`.
+pub fn available_memory_bytes() -> Option<usize> {
+    let meminfo = std::fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kb: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Ready `input_domain` to hold `aabb`, which it must contain,
+/// and size `output_domain` to match.
+/// If the aabbs already match nothing is copied,
+/// otherwise the domains are swapped and the values copied over.
+/// Returns whether the copy happened.
+fn fit_input_aabb<'b, const GRID_DIMENSION: usize>(
+    input_domain: &mut SliceDomain<'b, GRID_DIMENSION>,
+    output_domain: &mut SliceDomain<'b, GRID_DIMENSION>,
+    aabb: &AABB<GRID_DIMENSION>,
+    chunk_size: usize,
+) -> bool {
+    let copied = input_domain.aabb() != aabb;
+    if copied {
+        std::mem::swap(input_domain, output_domain);
+        input_domain.set_aabb(*aabb);
+        input_domain.par_from_superset(output_domain, chunk_size);
+    }
+    output_domain.set_aabb(*aabb);
+    copied
+}
+
+/// Scan the output of periodic solve `node_id` for NaNs and infinities.
+fn check_finite_output<const GRID_DIMENSION: usize>(
+    node_id: NodeId,
+    output_domain: &SliceDomain<'_, GRID_DIMENSION>,
+) -> Result<(), SolveError<GRID_DIMENSION>> {
+    match output_domain
+        .buffer()
+        .par_iter()
+        .position_first(|v| !v.is_finite())
+    {
+        Some(i) => Err(SolveError::NonFinite {
+            node_id,
+            coord: output_domain.aabb().linear_to_coord(i),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Where to dump node outputs, see `APSolver::with_node_dump`.
+pub struct NodeDump {
+    pub dir: PathBuf,
+
+    /// Counts periodic solves of the root repeat node.
+    solve_counter: AtomicUsize,
+}
+
+impl NodeDump {
+    /// Dump file for a node during the given root solve.
+    pub fn node_path(&self, solve: usize, node_id: NodeId) -> PathBuf {
+        self.dir
+            .join(format!("solve_{}_node_{}.raw", solve, node_id))
+    }
 }
 
 impl<
@@ -47,11 +190,67 @@ where
         steps: usize,
         params: &PlannerParameters,
     ) -> Self {
-        // Create our plan and convolution_store
+        APSolverBuilder::new(aabb, steps)
+            .params(*params)
+            .build(bc, stencil)
+    }
+
+    /// Like `new`, but checks the scratch space size before allocating it.
+    /// Fails if it exceeds `max_bytes`, when given,
+    /// or the available memory, when that can be queried.
+    /// The convolution kernels are still allocated during planning.
+    pub fn try_new(
+        bc: &'a BC,
+        stencil: &'a StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+        aabb: AABB<GRID_DIMENSION>,
+        steps: usize,
+        params: &PlannerParameters,
+        max_bytes: Option<usize>,
+    ) -> Result<Self, AllocError> {
         let planner_result = create_ap_plan(stencil, aabb, steps, params);
+        let required = APScratchBuilder::scratch_bytes(&planner_result.plan);
+        if let Some(budget) = max_bytes {
+            if required > budget {
+                return Err(AllocError::ExceedsBudget { required, budget });
+            }
+        }
+        if let Some(available) = available_memory_bytes() {
+            if required > available {
+                return Err(AllocError::ExceedsAvailable {
+                    required,
+                    available,
+                });
+            }
+        }
+        Ok(Self::from_planner_result(
+            bc,
+            stencil,
+            planner_result,
+            params,
+        ))
+    }
+
+    /// Create a solver from an existing plan,
+    /// i.e. a clone of another solver's `PlannerResult`
+    /// with modified nodes.
+    pub fn from_planner_result(
+        bc: &'a BC,
+        stencil: &'a StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+        planner_result: PlannerResult<GRID_DIMENSION>,
+        params: &PlannerParameters,
+    ) -> Self {
+        let chunk_size = params.chunk_size;
         let plan = planner_result.plan;
         let convolution_store = planner_result.convolution_store;
         let stencil_slopes = planner_result.stencil_slopes;
+        let cutoffs = planner_result.cutoffs;
+        let repeat_solve = plan.unwrap_repeat_node(plan.root);
+        let aabb = plan.unwrap_periodic_node(repeat_solve.node).input_aabb;
+        if cfg!(debug_assertions) {
+            if let Err(error) = plan.verify(&aabb) {
+                panic!("ERROR: Invalid plan, {}", error);
+            }
+        }
 
         let (node_scratch_descriptors, scratch_space) =
             APScratchBuilder::build(&plan);
@@ -60,7 +259,7 @@ where
             bc,
             stencil,
             stencil_slopes,
-            chunk_size: params.chunk_size,
+            chunk_size,
         };
 
         APSolver {
@@ -69,7 +268,93 @@ where
             plan,
             node_scratch_descriptors,
             scratch_space,
-            chunk_size: params.chunk_size,
+            chunk_size,
+            node_dump: None,
+            params: *params,
+            cutoffs,
+            instrumentation: None,
+            clock: SolveClock::default(),
+        }
+    }
+
+    /// Time transforms, direct solves, and copies during solves,
+    /// in total and per node, see `timing_report` and `node_timing_reports`.
+    /// Without this, solves skip the timing entirely.
+    pub fn with_instrumentation(mut self) -> Self {
+        self.instrumentation =
+            Some(Instrumentation::new(self.plan.nodes.len()));
+        self
+    }
+
+    /// Simulated time per step, `1.0` unless set.
+    pub fn with_dt(mut self, dt: f64) -> Self {
+        self.clock = SolveClock::new(dt);
+        self
+    }
+
+    /// Simulated time at `global_time`.
+    pub fn time_at(&self, global_time: usize) -> f64 {
+        self.clock.time_at(global_time)
+    }
+
+    /// Totals since creation, or the last `reset_timing`,
+    /// `None` without `with_instrumentation`.
+    pub fn timing_report(&self) -> Option<TimingReport> {
+        self.instrumentation.as_ref().map(|i| i.report())
+    }
+
+    /// Per node totals indexed by `NodeId`,
+    /// `None` without `with_instrumentation`.
+    pub fn node_timing_reports(&self) -> Option<Vec<TimingReport>> {
+        self.instrumentation.as_ref().map(|i| i.node_reports())
+    }
+
+    pub fn reset_timing(&self) {
+        if let Some(instrumentation) = &self.instrumentation {
+            instrumentation.reset();
+        }
+    }
+
+    /// Run `f`, timing it under `category` for `node_id` if instrumented.
+    fn timed<R, F: FnOnce() -> R>(
+        &self,
+        category: TimingCategory,
+        node_id: NodeId,
+        f: F,
+    ) -> R {
+        match &self.instrumentation {
+            Some(instrumentation) => instrumentation.time(category, node_id, f),
+            None => f(),
+        }
+    }
+
+    /// Debugging aid, only active in debug builds.
+    /// Each node's output domain is written to `dir` with `write_raw`,
+    /// see `NodeDump::node_path` for the naming.
+    /// Every periodic solve of the root repeat node
+    /// increments the solve counter, across calls to `apply`.
+    pub fn with_node_dump<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.node_dump = Some(NodeDump {
+            dir: dir.into(),
+            solve_counter: AtomicUsize::new(0),
+        });
+        self
+    }
+
+    fn dump_node(&self, node_id: NodeId, output: &SliceDomain<GRID_DIMENSION>) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if let Some(node_dump) = &self.node_dump {
+            let solve = node_dump.solve_counter.load(Ordering::SeqCst);
+            let path = node_dump.node_path(solve, node_id);
+            crate::raw::write_raw(output, &path, crate::raw::Layout::RowMajor);
+        }
+    }
+
+    fn finish_root_dump(&self) {
+        if let Some(node_dump) = &self.node_dump {
+            node_dump.solve_counter.fetch_add(1, Ordering::SeqCst);
         }
     }
 
@@ -82,13 +367,248 @@ where
         );
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "ap_solve",
+            skip_all,
+            fields(
+                aabb_size = input_domain.aabb().buffer_size(),
+                global_time = global_time
+            )
+        )
+    )]
     pub fn apply(
         &self,
         input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
         output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
         global_time: usize,
     ) {
-        self.solve_root(input_domain, output_domain, global_time);
+        self.apply_with_callback(
+            input_domain,
+            output_domain,
+            global_time,
+            |_, _| {},
+        );
+    }
+
+    /// Like `apply`, calling `on_progress(completed_steps, total_steps)`
+    /// after each periodic solve of the root.
+    /// The callback runs on the calling thread between solves,
+    /// never inside the rayon scope of the boundary solves.
+    pub fn apply_with_callback<F>(
+        &self,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        global_time: usize,
+        mut on_progress: F,
+    ) where
+        F: FnMut(usize, usize),
+    {
+        self.solve_root_impl(
+            input_domain,
+            output_domain,
+            global_time,
+            None,
+            false,
+            &mut |steps, total_steps, _| on_progress(steps, total_steps),
+        )
+        .unwrap();
+    }
+
+    /// Like `apply`, but after each periodic solve of the root
+    /// the output is scanned for NaNs and infinities.
+    /// On the first one found we stop early,
+    /// `output_domain` holds the state with the non-finite value.
+    /// `apply` skips the scan entirely.
+    pub fn apply_checked(
+        &self,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        global_time: usize,
+    ) -> Result<(), SolveError<GRID_DIMENSION>> {
+        self.solve_root_impl(
+            input_domain,
+            output_domain,
+            global_time,
+            None,
+            true,
+            &mut |_, _, _| {},
+        )?;
+        Ok(())
+    }
+
+    /// Like `apply`, but returns early once `cancel` is set.
+    /// The token is checked before each periodic solve of the root,
+    /// boundary solves running under rayon are not interrupted.
+    /// Returns the number of steps taken,
+    /// either way `output_domain` holds the state after those steps.
+    pub fn apply_cancellable(
+        &self,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        global_time: usize,
+        cancel: &AtomicBool,
+    ) -> usize {
+        self.solve_root_impl(
+            input_domain,
+            output_domain,
+            global_time,
+            Some(cancel),
+            false,
+            &mut |_, _, _| {},
+        )
+        .unwrap()
+    }
+
+    /// Solve once, calling `on_horizon(steps, time, output)`
+    /// with the state after every multiple of `steps_per_image` steps,
+    /// `time` is the simulated time at that horizon.
+    /// The callback runs from inside the root solve,
+    /// between periodic solves of the root.
+    /// Plan for `horizons * steps_per_image` steps
+    /// with `max_steps = Some(steps_per_image)`,
+    /// so each periodic solve of the root ends on a horizon.
+    /// Panics if some horizon falls inside a periodic solve of the root.
+    /// As with `apply`, `output_domain` holds the final state.
+    pub fn apply_with_horizons<F>(
+        &self,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        global_time: usize,
+        steps_per_image: usize,
+        mut on_horizon: F,
+    ) where
+        F: FnMut(usize, f64, &SliceDomain<'a, GRID_DIMENSION>),
+    {
+        let repeat_solve = self.plan.unwrap_repeat_node(self.plan.root);
+        let repeat_steps =
+            self.plan.unwrap_periodic_node(repeat_solve.node).steps;
+        let next_steps = repeat_solve
+            .next
+            .map_or(0, |next| self.plan.unwrap_periodic_node(next).steps);
+        let total_steps = repeat_solve.n * repeat_steps + next_steps;
+        assert!(
+            steps_per_image > 0 && total_steps % steps_per_image == 0,
+            "ERROR: {} planned steps aren't a multiple of {} steps per image",
+            total_steps,
+            steps_per_image
+        );
+        // Root solves end at each multiple of repeat_steps, then total_steps
+        let ends_root_solve = |steps: usize| {
+            steps == total_steps
+                || (steps % repeat_steps == 0
+                    && steps / repeat_steps <= repeat_solve.n)
+        };
+        for horizon in 1..=total_steps / steps_per_image {
+            assert!(
+                ends_root_solve(horizon * steps_per_image),
+                "ERROR: horizon at step {} falls inside a root solve, \
+                 root repeats {} steps {} times then {} steps, \
+                 plan with max_steps = Some({})",
+                horizon * steps_per_image,
+                repeat_steps,
+                repeat_solve.n,
+                next_steps,
+                steps_per_image
+            );
+        }
+
+        self.solve_root_impl(
+            input_domain,
+            output_domain,
+            global_time,
+            None,
+            false,
+            &mut |steps, _, output| {
+                if steps % steps_per_image == 0 {
+                    on_horizon(
+                        steps,
+                        self.time_at(global_time + steps),
+                        output,
+                    );
+                }
+            },
+        )
+        .unwrap();
+    }
+
+    /// The domain this solver was planned for.
+    pub fn aabb(&self) -> &AABB<GRID_DIMENSION> {
+        let repeat_solve = self.plan.unwrap_repeat_node(self.plan.root);
+        &self.plan.unwrap_periodic_node(repeat_solve.node).input_aabb
+    }
+
+    /// The region written by `apply`, an alias for `aabb`.
+    /// Each periodic solve of the root writes its output region,
+    /// and its boundary solves fill in the rest of its input region,
+    /// so `apply` always writes the whole planned domain.
+    pub fn output_bound(&self) -> AABB<GRID_DIMENSION> {
+        *self.aabb()
+    }
+
+    /// Evolve a window of a larger field in place.
+    /// The solver should be planned over the window,
+    /// with a boundary condition that pins the surrounding values,
+    /// see `exterior_check`.
+    /// The window is copied into `input_domain`, solved,
+    /// and the result is written back into `full_domain`.
+    pub fn apply_subregion<DomainType: DomainView<GRID_DIMENSION>>(
+        &self,
+        full_domain: &mut DomainType,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        global_time: usize,
+    ) {
+        let window = *self.aabb();
+        assert!(
+            full_domain.aabb().contains_aabb(&window),
+            "ERROR: {:?} does not contain solve window {:?}",
+            full_domain.aabb(),
+            window
+        );
+        assert_eq!(*input_domain.aabb(), window);
+        input_domain.par_from_superset(full_domain, self.chunk_size);
+        self.apply(input_domain, output_domain, global_time);
+        full_domain.par_set_subdomain(output_domain, self.chunk_size);
+    }
+
+    /// Run a single plan node in isolation, mostly for testing.
+    /// `input` must contain the node's input AABB,
+    /// the node's output AABB is written into `output`.
+    /// Nodes run in their scratch space like during a full solve,
+    /// except for the root's periodic solves,
+    /// which are given temporary buffers here.
+    /// Panics for repeat nodes.
+    pub fn run_node<'b>(
+        &self,
+        node_id: NodeId,
+        input: &SliceDomain<'b, GRID_DIMENSION>,
+        output: &mut SliceDomain<'b, GRID_DIMENSION>,
+        global_time: usize,
+    ) {
+        let repeat_solve = self.plan.unwrap_repeat_node(self.plan.root);
+        if node_id == repeat_solve.node || Some(node_id) == repeat_solve.next {
+            let periodic_solve = self.plan.unwrap_periodic_node(node_id);
+            let mut input_buffer = OwnedDomain::new(periodic_solve.input_aabb);
+            let mut output_buffer = OwnedDomain::new(periodic_solve.input_aabb);
+            let mut node_input = input_buffer.as_slice_domain();
+            let mut node_output = output_buffer.as_slice_domain();
+            node_input.par_from_superset(input, self.chunk_size);
+            self.periodic_solve_preallocated_io(
+                node_id,
+                false,
+                &mut node_input,
+                &mut node_output,
+                global_time,
+            );
+            output.par_set_subdomain(&node_output, self.chunk_size);
+        } else {
+            if let PlanNode::Repeat(_) = self.plan.get_node(node_id) {
+                panic!("ERROR: Can't run repeat node {} in isolation", node_id);
+            }
+            self.unknown_solve_allocate_io(node_id, input, output, global_time);
+        }
     }
 
     pub fn to_dot_file<P: AsRef<std::path::Path>>(&self, path: &P) {
@@ -103,6 +623,56 @@ where
         }
     }
 
+    /// Everything planned, as one JSON object.
+    /// Includes the grid, planner parameters with the per axis
+    /// `cutoffs`, plan, scratch descriptors, and convolution store stats,
+    /// along with top level `node_count` and `scratch_bytes`.
+    pub fn report_json(&self) -> String {
+        let descriptors: Vec<String> = self
+            .node_scratch_descriptors
+            .iter()
+            .map(|d| {
+                format!(
+                    "{{\"input_offset\": {}, \"output_offset\": {}, \"real_buffer_size\": {}, \"complex_offset\": {}, \"complex_buffer_size\": {}}}",
+                    d.input_offset,
+                    d.output_offset,
+                    d.real_buffer_size,
+                    d.complex_offset,
+                    d.complex_buffer_size
+                )
+            })
+            .collect();
+        format!(
+            "{{\n\"grid\": {},\n\"parameters\": {{\"plan_type\": \"{:?}\", \"plan_fallback\": {}, \"cutoff\": {}, \"cutoffs\": [{}], \"ratio\": {}, \"chunk_size\": {}}},\n\"node_count\": {},\n\"scratch_bytes\": {},\n\"convolutions\": {{\"count\": {}, \"kernel_bytes\": {}}},\n\"scratch_descriptors\": [\n{}\n],\n\"plan\": {}\n}}\n",
+            bounds_json(&self.aabb().bounds),
+            self.params.plan_type,
+            self.params.plan_fallback,
+            self.params.cutoff,
+            self.cutoffs
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            f64_json(self.params.ratio),
+            self.params.chunk_size,
+            self.plan.len(),
+            self.scratch_space.size,
+            self.convolution_store.len(),
+            self.convolution_store.kernel_bytes(),
+            descriptors.join(",\n"),
+            self.plan.to_json()
+        )
+    }
+
+    /// Write `report_json` to path,
+    /// a single artifact for post-mortem analysis.
+    pub fn write_report<P: AsRef<std::path::Path>>(&self, path: &P) {
+        println!("Writing solver report: {:?}", path.as_ref());
+        let mut writer =
+            std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+        writer.write_all(self.report_json().as_bytes()).unwrap();
+    }
+
     fn get_input_output(
         &self,
         node_id: usize,
@@ -137,14 +707,50 @@ where
         &self,
         input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
         output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
-        mut global_time: usize,
+        global_time: usize,
     ) {
+        self.solve_root_impl(
+            input_domain,
+            output_domain,
+            global_time,
+            None,
+            false,
+            &mut |_, _, _| {},
+        )
+        .unwrap();
+    }
+
+    fn solve_root_impl(
+        &self,
+        input_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        output_domain: &mut SliceDomain<'a, GRID_DIMENSION>,
+        mut global_time: usize,
+        cancel: Option<&AtomicBool>,
+        check_finite: bool,
+        on_progress: &mut dyn FnMut(
+            usize,
+            usize,
+            &SliceDomain<'a, GRID_DIMENSION>,
+        ),
+    ) -> Result<usize, SolveError<GRID_DIMENSION>> {
         let repeat_solve = self.plan.unwrap_repeat_node(self.plan.root);
         let repeat_periodic_solve =
             self.plan.unwrap_periodic_node(repeat_solve.node);
         let repeat_steps = repeat_periodic_solve.steps;
+        let next_steps = repeat_solve
+            .next
+            .map_or(0, |next| self.plan.unwrap_periodic_node(next).steps);
+        let total_steps = repeat_solve.n * repeat_steps + next_steps;
+        let cancelled =
+            || cancel.is_some_and(|token| token.load(Ordering::SeqCst));
 
+        let mut steps_taken = 0;
         for _ in 0..repeat_solve.n {
+            if cancelled() {
+                // Latest state is in input_domain after the swap
+                std::mem::swap(input_domain, output_domain);
+                return Ok(steps_taken);
+            }
             self.periodic_solve_preallocated_io(
                 repeat_solve.node,
                 false,
@@ -152,20 +758,37 @@ where
                 output_domain,
                 global_time,
             );
+            self.finish_root_dump();
+            if check_finite {
+                check_finite_output(repeat_solve.node, output_domain)?;
+            }
             global_time += repeat_steps;
+            steps_taken += repeat_steps;
+            on_progress(steps_taken, total_steps, output_domain);
             std::mem::swap(input_domain, output_domain);
         }
         if let Some(next) = repeat_solve.next {
+            if cancelled() {
+                std::mem::swap(input_domain, output_domain);
+                return Ok(steps_taken);
+            }
             self.periodic_solve_preallocated_io(
                 next,
                 false,
                 input_domain,
                 output_domain,
                 global_time,
-            )
+            );
+            self.finish_root_dump();
+            if check_finite {
+                check_finite_output(next, output_domain)?;
+            }
+            steps_taken += next_steps;
+            on_progress(steps_taken, total_steps, output_domain);
         } else {
             std::mem::swap(input_domain, output_domain);
         }
+        Ok(steps_taken)
     }
 
     pub fn unknown_solve_allocate_io<'b>(
@@ -229,6 +852,21 @@ where
         }
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "periodic_solve",
+            skip_all,
+            fields(
+                node_id = node_id,
+                aabb_size = self
+                    .plan
+                    .unwrap_periodic_node(node_id)
+                    .input_aabb
+                    .buffer_size()
+            )
+        )
+    )]
     pub fn periodic_solve_preallocated_io<'b>(
         &self,
         node_id: NodeId,
@@ -238,31 +876,74 @@ where
         mut global_time: usize,
     ) {
         let periodic_solve = self.plan.unwrap_periodic_node(node_id);
-        std::mem::swap(input_domain, output_domain);
-        input_domain.set_aabb(periodic_solve.input_aabb);
-        input_domain.par_from_superset(output_domain, self.chunk_size);
-        output_domain.set_aabb(periodic_solve.input_aabb);
+        self.timed(TimingCategory::Copy, node_id, || {
+            fit_input_aabb(
+                input_domain,
+                output_domain,
+                &periodic_solve.input_aabb,
+                self.chunk_size,
+            )
+        });
 
         // Apply convolution
         {
             let convolution_op =
                 self.convolution_store.get(periodic_solve.convolution_id);
-            convolution_op.apply(
-                input_domain,
-                output_domain,
-                self.get_complex(node_id),
-                self.chunk_size,
-            );
+            // Same span as `ConvolutionOperation::apply`,
+            // which we skip to time each step
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!(
+                "fft_convolution",
+                aabb_size = input_domain.aabb().buffer_size()
+            )
+            .entered();
+            let complex_buffer = self.get_complex(node_id);
+            self.timed(TimingCategory::ForwardFft, node_id, || {
+                convolution_op.forward(input_domain, complex_buffer)
+            });
+            self.timed(TimingCategory::Multiply, node_id, || {
+                convolution_op.multiply(
+                    input_domain.aabb(),
+                    complex_buffer,
+                    self.chunk_size,
+                )
+            });
+            self.timed(TimingCategory::BackwardFft, node_id, || {
+                convolution_op.backward(
+                    complex_buffer,
+                    output_domain,
+                    self.chunk_size,
+                )
+            });
         }
 
         // Boundary
         // In a rayon scope, we fork for each of the boundary solves,
         // each of which will fill in their part of of output_domain
+        //
+        // Soundness of the `unsafe_mut_access` aliasing relies on:
+        //   - Boundary node output AABBs are disjoint, so no two tasks
+        //     write the same element of output_domain.
+        //   - Tasks only read input_domain, which nobody writes while
+        //     the scope is alive.
+        //   - Each node has its own scratch region, see `APScratchBuilder`,
+        //     so sibling tasks never share scratch buffers.
+        //   - Nothing outside of the tasks touches output_domain until
+        //     `rayon::scope` returns. The scope blocks until every spawned
+        //     task has completed, and that join synchronizes with the
+        //     completion of each task (rayon latches use acquire / release
+        //     ordering). So every write made by a task happens-before
+        //     the swaps and reads that follow the scope.
+        // No extra barrier is needed as long as the aliased views
+        // do not escape the scope.
         {
             let input_domain_const: &SliceDomain<'b, GRID_DIMENSION> =
                 input_domain;
+            let boundary_nodes = self
+                .plan
+                .boundary_nodes_by_size(periodic_solve.boundary_nodes.clone());
             rayon::scope(|s| {
-                for node_id in periodic_solve.boundary_nodes.clone() {
+                for node_id in boundary_nodes {
                     // Our plan should provide the guarantee that
                     // that boundary nodes have mutually exclusive
                     // access to the output_domain
@@ -285,9 +966,12 @@ where
         if resize {
             std::mem::swap(input_domain, output_domain);
             output_domain.set_aabb(periodic_solve.output_aabb);
-            output_domain.par_from_superset(input_domain, self.chunk_size);
+            self.timed(TimingCategory::Copy, node_id, || {
+                output_domain.par_from_superset(input_domain, self.chunk_size)
+            });
             input_domain.set_aabb(periodic_solve.output_aabb);
         }
+        self.dump_node(node_id, output_domain);
 
         // call time cut if needed
         if let Some(next_id) = periodic_solve.time_cut {
@@ -315,7 +999,9 @@ where
             self.get_input_output(node_id, &periodic_solve.input_aabb);
 
         // copy input
-        input_domain.par_from_superset(input, self.chunk_size);
+        self.timed(TimingCategory::Copy, node_id, || {
+            input_domain.par_from_superset(input, self.chunk_size)
+        });
 
         self.periodic_solve_preallocated_io(
             node_id,
@@ -326,7 +1012,9 @@ where
         );
 
         // copy output to output
-        output.par_set_subdomain(&output_domain, self.chunk_size);
+        self.timed(TimingCategory::Copy, node_id, || {
+            output.par_set_subdomain(&output_domain, self.chunk_size)
+        });
     }
 
     pub fn direct_solve_allocate_io<'b>(
@@ -342,7 +1030,9 @@ where
             self.get_input_output(node_id, &direct_solve.input_aabb);
 
         // copy input
-        input_domain.par_from_superset(input, self.chunk_size);
+        self.timed(TimingCategory::Copy, node_id, || {
+            input_domain.par_from_superset(input, self.chunk_size)
+        });
 
         self.direct_solve_preallocated_io(
             node_id,
@@ -353,9 +1043,26 @@ where
         debug_assert_eq!(*output_domain.aabb(), direct_solve.output_aabb);
 
         // copy output to output
-        output.par_set_subdomain(&output_domain, self.chunk_size);
+        self.timed(TimingCategory::Copy, node_id, || {
+            output.par_set_subdomain(&output_domain, self.chunk_size)
+        });
     }
 
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "direct_solve",
+            skip_all,
+            fields(
+                node_id = node_id,
+                aabb_size = self
+                    .plan
+                    .unwrap_direct_node(node_id)
+                    .input_aabb
+                    .buffer_size()
+            )
+        )
+    )]
     pub fn direct_solve_preallocated_io<'b>(
         &self,
         node_id: NodeId,
@@ -375,18 +1082,22 @@ where
         // the expected input domain
         std::mem::swap(input_domain, output_domain);
         input_domain.set_aabb(direct_solve.input_aabb);
-        input_domain.par_from_superset(output_domain, self.chunk_size);
+        self.timed(TimingCategory::Copy, node_id, || {
+            input_domain.par_from_superset(output_domain, self.chunk_size)
+        });
         output_domain.set_aabb(direct_solve.input_aabb);
         debug_assert_eq!(*input_domain.aabb(), direct_solve.input_aabb);
 
         // invoke direct solver
-        self.direct_frustrum_solver.apply(
-            input_domain,
-            output_domain,
-            &direct_solve.sloped_sides,
-            direct_solve.steps,
-            global_time,
-        );
+        self.timed(TimingCategory::Direct, node_id, || {
+            self.direct_frustrum_solver.apply(
+                input_domain,
+                output_domain,
+                &direct_solve.sloped_sides,
+                direct_solve.steps,
+                global_time,
+            )
+        });
 
         debug_assert_eq!(
             direct_solve.output_aabb,
@@ -394,5 +1105,56 @@ where
             "ERROR: n_id: {}, Unexpected solve output",
             node_id
         );
+        self.dump_node(node_id, output_domain);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use nalgebra::matrix;
+
+    #[test]
+    fn fit_input_aabb_test() {
+        let chunk_size = 10;
+        let outer = AABB::new(matrix![0, 9; 0, 9]);
+        let inner = AABB::new(matrix![2, 7; 1, 8]);
+        let value = |c: Coord<2>| (c[0] * 10 + c[1]) as f64;
+        let mut buffer = OwnedDomain::new_paired(outer);
+
+        // Matching, the input is used in place
+        {
+            let (mut input, mut output) = buffer.split_halves();
+            input.par_set_values(value, chunk_size);
+            let input_ptr = input.buffer().as_ptr();
+            assert!(!fit_input_aabb(
+                &mut input,
+                &mut output,
+                &outer,
+                chunk_size
+            ));
+            assert_eq!(input.buffer().as_ptr(), input_ptr);
+            assert_eq!(*output.aabb(), outer);
+            for c in outer.coord_iter() {
+                assert_eq!(input.view(&c), value(c));
+            }
+        }
+
+        // Shrinking copies
+        {
+            let (mut input, mut output) = buffer.split_halves();
+            input.par_set_values(value, chunk_size);
+            assert!(fit_input_aabb(
+                &mut input,
+                &mut output,
+                &inner,
+                chunk_size
+            ));
+            assert_eq!(*input.aabb(), inner);
+            assert_eq!(*output.aabb(), inner);
+            for c in inner.coord_iter() {
+                assert_eq!(input.view(&c), value(c));
+            }
+        }
     }
 }
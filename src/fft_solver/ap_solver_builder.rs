@@ -0,0 +1,162 @@
+use crate::domain::*;
+use crate::fft_solver::*;
+use crate::stencil::*;
+use crate::util::*;
+
+/// Named setters for the `PlannerParameters` of an `APSolver`,
+/// starting from `PlannerParameters::default()`.
+/// ```ignore
+/// let solver = APSolverBuilder::new(aabb, steps)
+///     .cutoff(20)
+///     .chunk_size(100)
+///     .build(&bc, &stencil);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct APSolverBuilder<const GRID_DIMENSION: usize> {
+    aabb: AABB<GRID_DIMENSION>,
+    steps: usize,
+    params: PlannerParameters,
+    cutoffs: Option<Coord<GRID_DIMENSION>>,
+    instrumented: bool,
+    dt: f64,
+}
+
+impl<const GRID_DIMENSION: usize> APSolverBuilder<GRID_DIMENSION> {
+    pub fn new(aabb: AABB<GRID_DIMENSION>, steps: usize) -> Self {
+        APSolverBuilder {
+            aabb,
+            steps,
+            params: PlannerParameters::default(),
+            cutoffs: None,
+            instrumented: false,
+            dt: 1.0,
+        }
+    }
+
+    /// Replace all the planner parameters at once.
+    pub fn params(mut self, params: PlannerParameters) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn plan_type(mut self, plan_type: PlanType) -> Self {
+        self.params.plan_type = plan_type;
+        self
+    }
+
+    pub fn plan_fallback(mut self, plan_fallback: bool) -> Self {
+        self.params.plan_fallback = plan_fallback;
+        self
+    }
+
+    /// Use the same cutoff on every axis.
+    pub fn cutoff(mut self, cutoff: i32) -> Self {
+        self.params.cutoff = cutoff;
+        self.cutoffs = None;
+        self
+    }
+
+    /// Use a different cutoff on each axis,
+    /// see `create_ap_plan_with_cutoffs`.
+    pub fn cutoffs(mut self, cutoffs: Coord<GRID_DIMENSION>) -> Self {
+        self.cutoffs = Some(cutoffs);
+        self
+    }
+
+    pub fn ratio(mut self, ratio: f64) -> Self {
+        self.params.ratio = ratio;
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.params.chunk_size = chunk_size;
+        self
+    }
+
+    /// Cap the steps of any one periodic solve.
+    pub fn max_steps(mut self, max_steps: Option<usize>) -> Self {
+        self.params.max_steps = max_steps;
+        self
+    }
+
+    /// See `APSolver::with_instrumentation`.
+    pub fn instrumented(mut self, instrumented: bool) -> Self {
+        self.instrumented = instrumented;
+        self
+    }
+
+    /// See `APSolver::with_dt`.
+    pub fn dt(mut self, dt: f64) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    pub fn planner_params(&self) -> &PlannerParameters {
+        &self.params
+    }
+
+    /// Plan and create the solver.
+    pub fn build<'a, BC, Operation, const NEIGHBORHOOD_SIZE: usize>(
+        &self,
+        bc: &'a BC,
+        stencil: &'a StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    ) -> APSolver<'a, BC, Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>
+    where
+        Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+        BC: BCCheck<GRID_DIMENSION>,
+    {
+        let cutoffs = self
+            .cutoffs
+            .unwrap_or_else(|| Coord::repeat(self.params.cutoff));
+        let planner_result = create_ap_plan_with_cutoffs(
+            stencil,
+            self.aabb,
+            self.steps,
+            &self.params,
+            cutoffs,
+        );
+        let solver = APSolver::from_planner_result(
+            bc,
+            stencil,
+            planner_result,
+            &self.params,
+        )
+        .with_dt(self.dt);
+        if self.instrumented {
+            solver.with_instrumentation()
+        } else {
+            solver
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn builder_params_test() {
+        let aabb = AABB::new(matrix![0, 99]);
+        let builder = APSolverBuilder::new(aabb, 10);
+        let params = builder.planner_params();
+        assert_eq!(params.plan_type, PlanType::Measure);
+        assert!(!params.plan_fallback);
+        assert_eq!(params.cutoff, 40);
+        assert_eq!(params.ratio, 0.5);
+
+        let builder = builder
+            .plan_type(PlanType::Estimate)
+            .plan_fallback(true)
+            .cutoff(20)
+            .ratio(0.25)
+            .chunk_size(100)
+            .max_steps(Some(8));
+        let params = builder.planner_params();
+        assert_eq!(params.plan_type, PlanType::Estimate);
+        assert!(params.plan_fallback);
+        assert_eq!(params.cutoff, 20);
+        assert_eq!(params.ratio, 0.25);
+        assert_eq!(params.chunk_size, 100);
+        assert_eq!(params.max_steps, Some(8));
+    }
+}
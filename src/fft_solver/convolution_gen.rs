@@ -12,6 +12,11 @@ struct ConvolutionDescriptor<const GRID_DIMENSION: usize> {
 
 /// Used by APPlaner to create convolution operations,
 /// and assign them IDs.
+///
+/// IDs are assigned densely in the order operations are first requested,
+/// which is the planner's traversal order.
+/// The key map is only used for lookups and never iterated,
+/// so planning the same problem always yields the same IDs.
 pub struct ConvolutionGenerator<
     'a,
     Operation,
@@ -26,6 +31,7 @@ pub struct ConvolutionGenerator<
     real_buffer: AlignedVec<f64>,
     convolution_buffer: AlignedVec<c64>,
     plan_type: PlanType,
+    plan_fallback: bool,
     key_map: HashMap<ConvolutionDescriptor<GRID_DIMENSION>, OpId>,
     chunk_size: usize,
 }
@@ -43,8 +49,10 @@ where
         max_aabb: &AABB<GRID_DIMENSION>,
         stencil: &'a StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
         plan_type: PlanType,
+        plan_fallback: bool,
         chunk_size: usize,
     ) -> Self {
+        stencil.warn_missing_origin_term();
         let max_real_size = max_aabb.buffer_size();
         let real_buffer = fftw::array::AlignedVec::new(max_real_size);
         let max_complex_size = max_aabb.complex_buffer_size();
@@ -58,11 +66,14 @@ where
             real_buffer,
             convolution_buffer,
             plan_type,
+            plan_fallback,
             key_map: HashMap::new(),
             chunk_size,
         }
     }
 
+    /// Get the ID for a convolution, creating it if this is the first
+    /// request for these bounds and steps.
     pub fn get_op(
         &mut self,
         bounds: &AABB<GRID_DIMENSION>,
@@ -82,6 +93,7 @@ where
                 bounds,
                 steps,
                 self.plan_type,
+                self.plan_fallback,
                 self.chunk_size,
             ));
             result
@@ -5,16 +5,51 @@ use crate::stencil::*;
 use crate::util::*;
 use fftw::array::*;
 use fftw::plan::*;
+use fftw::types::c32;
 use float_cmp::assert_approx_eq;
 
+/// Precision used for the pointwise multiply
+/// between the input spectrum and the convolution.
+/// Transforms always run in double precision.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ComplexMulPrecision {
+    #[default]
+    F64,
+
+    /// Halves the bandwidth needed for the convolution,
+    /// at the cost of single precision rounding in the multiply.
+    F32,
+}
+
 /// The backbone of our periodic solves.
 /// This has the FFTW plans we need, as well
 /// as the stencil operation in the frequency domain to some
 /// power.
 pub struct ConvolutionOperation {
-    pub forward_plan: fftw::plan::Plan<f64, c64, fftw::plan::Plan64>,
-    pub backward_plan: fftw::plan::Plan<c64, f64, fftw::plan::Plan64>,
+    pub fft_plan: FFTPlan,
     pub convolution: AlignedVec<c64>,
+
+    /// Single precision copy of convolution,
+    /// present when using `ComplexMulPrecision::F32`.
+    pub convolution_c32: Option<Vec<c32>>,
+
+    /// Applied after the multiply, before the backward transform.
+    pub spectral_filter: Option<SpectralFilter>,
+}
+
+/// Deep copy, duplicating the frequency domain kernels
+/// and re-creating the FFTW plans, see `FFTPlan::clone`.
+impl Clone for ConvolutionOperation {
+    fn clone(&self) -> Self {
+        let mut convolution = AlignedVec::new(self.convolution.len());
+        convolution.copy_from_slice(&self.convolution);
+        ConvolutionOperation {
+            fft_plan: self.fft_plan.clone(),
+            convolution,
+            convolution_c32: self.convolution_c32.clone(),
+            spectral_filter: self.spectral_filter,
+        }
+    }
 }
 
 impl ConvolutionOperation {
@@ -32,6 +67,7 @@ impl ConvolutionOperation {
         aabb: &AABB<GRID_DIMENSION>,
         steps: usize,
         plan_type: PlanType,
+        plan_fallback: bool,
         chunk_size: usize,
     ) -> Self
     where
@@ -43,18 +79,8 @@ impl ConvolutionOperation {
                 assert_approx_eq!(f64, *v, 0.0, epsilon = 0.0000000000001);
             }
         }
-        let size = aabb.exclusive_bounds();
-        let plan_size = size.try_cast::<usize>().unwrap();
-        let forward_plan = fftw::plan::R2CPlan64::aligned(
-            plan_size.as_slice(),
-            plan_type.to_fftw3_flag(),
-        )
-        .unwrap();
-        let backward_plan = fftw::plan::C2RPlan64::aligned(
-            plan_size.as_slice(),
-            plan_type.to_fftw3_flag(),
-        )
-        .unwrap();
+        let fft_plan =
+            FFTPlan::new(&aabb.exclusive_bounds(), plan_type, plan_fallback);
 
         // TODO: Create domain for real buffer
         let mut stencil_domain = SliceDomain::new(*aabb, real_buffer);
@@ -62,9 +88,9 @@ impl ConvolutionOperation {
         // Place offsets in real buffer
         let offsets = stencil.offsets();
         for n_i in 0..NEIGHBORHOOD_SIZE {
-            // I don't understand why, but we found that this mirroring operation
-            // was necessary. I think it was in the paper.
-            // TODO: Why is this the case?
+            // Convolution computes out[x] = sum_y in[y] * g[x - y],
+            // while the stencil computes out[x] = sum_i w_i * in[x + o_i].
+            // Matching terms gives g[-o_i] = w_i, hence the mirroring.
             let rn_i: Coord<GRID_DIMENSION> = aabb.min() + offsets[n_i] * -1;
             let periodic_coord = aabb.periodic_coord(&rn_i);
             stencil_domain.set_coord(&periodic_coord, stencil_weights[n_i]);
@@ -72,9 +98,10 @@ impl ConvolutionOperation {
 
         // Calculate convolution of stencil
         let n_c = aabb.complex_buffer_size();
-        forward_plan
-            .r2c(stencil_domain.buffer_mut(), &mut convolution_buffer[0..n_c])
-            .unwrap();
+        fft_plan.forward(
+            stencil_domain.buffer_mut(),
+            &mut convolution_buffer[0..n_c],
+        );
 
         // Clean up real buffer
         //stencil_domain.par_set_values(|_| 0.0, chunk_size);
@@ -101,12 +128,43 @@ impl ConvolutionOperation {
         );
 
         ConvolutionOperation {
-            forward_plan,
-            backward_plan,
+            fft_plan,
             convolution: result_buffer,
+            convolution_c32: None,
+            spectral_filter: None,
         }
     }
 
+    pub fn precision(&self) -> ComplexMulPrecision {
+        if self.convolution_c32.is_some() {
+            ComplexMulPrecision::F32
+        } else {
+            ComplexMulPrecision::F64
+        }
+    }
+
+    pub fn set_precision(&mut self, precision: ComplexMulPrecision) {
+        self.convolution_c32 = match precision {
+            ComplexMulPrecision::F64 => None,
+            ComplexMulPrecision::F32 => {
+                let convolution_c32 = self
+                    .convolution
+                    .iter()
+                    .map(|c| c32::new(c.re as f32, c.im as f32))
+                    .collect();
+                Some(convolution_c32)
+            }
+        };
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            name = "fft_convolution",
+            skip_all,
+            fields(aabb_size = input.aabb().buffer_size())
+        )
+    )]
     #[inline]
     pub fn apply<
         const GRID_DIMENSION: usize,
@@ -118,19 +176,96 @@ impl ConvolutionOperation {
         complex_buffer: &mut [c64],
         chunk_size: usize,
     ) {
-        let n_r = input.aabb().buffer_size();
+        self.forward(input, complex_buffer);
+        self.multiply(input.aabb(), complex_buffer, chunk_size);
+        self.backward(complex_buffer, output, chunk_size);
+    }
+
+    /// First step of `apply`, transform `input` into `complex_buffer`.
+    pub fn forward<
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    >(
+        &self,
+        input: &mut DomainType,
+        complex_buffer: &mut [c64],
+    ) {
+        self.check_size(input.aabb(), "input");
         let n_c = input.aabb().complex_buffer_size();
-        self.forward_plan
-            .r2c(input.buffer_mut(), &mut complex_buffer[0..n_c])
-            .unwrap();
-        par_slice::multiply_by(
-            &mut complex_buffer[0..n_c],
-            self.convolution.as_slice(),
-            chunk_size,
-        );
-        self.backward_plan
-            .c2r(&mut complex_buffer[0..n_c], output.buffer_mut())
-            .unwrap();
+        self.fft_plan
+            .forward(input.buffer_mut(), &mut complex_buffer[0..n_c]);
+    }
+
+    /// Second step of `apply`, multiply the spectrum of `aabb`
+    /// by the convolution and apply the spectral filter.
+    pub fn multiply<const GRID_DIMENSION: usize>(
+        &self,
+        aabb: &AABB<GRID_DIMENSION>,
+        complex_buffer: &mut [c64],
+        chunk_size: usize,
+    ) {
+        let n_c = aabb.complex_buffer_size();
+        match &self.convolution_c32 {
+            None => par_slice::multiply_by(
+                &mut complex_buffer[0..n_c],
+                self.convolution.as_slice(),
+                chunk_size,
+            ),
+            Some(convolution_c32) => par_slice::multiply_by_c32(
+                &mut complex_buffer[0..n_c],
+                convolution_c32.as_slice(),
+                chunk_size,
+            ),
+        }
+        if let Some(filter) = &self.spectral_filter {
+            filter.apply(
+                &mut complex_buffer[0..n_c],
+                &aabb.exclusive_bounds(),
+                chunk_size,
+            );
+        }
+    }
+
+    /// Last step of `apply`, transform `complex_buffer`
+    /// back into `output` and normalize.
+    pub fn backward<
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    >(
+        &self,
+        complex_buffer: &mut [c64],
+        output: &mut DomainType,
+        chunk_size: usize,
+    ) {
+        self.check_size(output.aabb(), "output");
+        let n_r = output.aabb().buffer_size();
+        let n_c = output.aabb().complex_buffer_size();
+        self.fft_plan
+            .backward(&mut complex_buffer[0..n_c], output.buffer_mut());
         par_slice::div(output.buffer_mut(), n_r as f64, chunk_size);
     }
+
+    /// Panic naming both sizes if `aabb` isn't what the plans were made for.
+    /// Equal buffer sizes in a different shape
+    /// would otherwise transform silently wrong.
+    fn check_size<const GRID_DIMENSION: usize>(
+        &self,
+        aabb: &AABB<GRID_DIMENSION>,
+        name: &str,
+    ) {
+        let size = aabb.exclusive_bounds();
+        let plan_size = self.fft_plan.plan_size();
+        let matches = plan_size.len() == GRID_DIMENSION
+            && (0..GRID_DIMENSION).all(|d| plan_size[d] == size[d] as usize);
+        if !matches {
+            panic!(
+                "ERROR: Convolution planned for size {:?}, \
+                 but {} domain {:?} has size {:?}",
+                plan_size,
+                name,
+                aabb,
+                size.as_slice()
+            );
+        }
+    }
 }
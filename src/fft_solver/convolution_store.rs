@@ -1,7 +1,12 @@
 use crate::fft_solver::*;
+use crate::util::*;
+use fftw::types::c32;
 
 /// This stores the convolution operations in
 /// an APSolver instance.
+/// Cloning is a deep copy of every operation,
+/// which costs a kernel buffer copy and a re-plan per operation.
+#[derive(Clone)]
 pub struct ConvolutionStore {
     operations: Vec<ConvolutionOperation>,
 }
@@ -14,4 +19,33 @@ impl ConvolutionStore {
     pub fn get(&self, op: OpId) -> &ConvolutionOperation {
         &self.operations[op]
     }
+
+    /// Number of convolution operations.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Bytes used by the frequency domain kernels.
+    pub fn kernel_bytes(&self) -> usize {
+        self.operations
+            .iter()
+            .map(|op| {
+                op.convolution.len() * std::mem::size_of::<c64>()
+                    + op.convolution_c32
+                        .as_ref()
+                        .map_or(0, |c| c.len() * std::mem::size_of::<c32>())
+            })
+            .sum()
+    }
+
+    /// Set the complex multiply precision for every operation.
+    pub fn set_precision(&mut self, precision: ComplexMulPrecision) {
+        for op in self.operations.iter_mut() {
+            op.set_precision(precision);
+        }
+    }
 }
@@ -54,14 +54,30 @@ where
                 input_domain.aabb().buffer_size() >= output_box.buffer_size()
             );
             output_domain.set_aabb(output_box);
-            par_stencil::apply(
-                self.bc,
-                self.stencil,
-                input_domain,
-                output_domain,
-                global_time,
-                self.chunk_size,
-            );
+            // Frustrums sloped on every side never reach the boundary
+            if self.bc.interior_is_unconstrained()
+                && par_stencil::is_interior(
+                    self.stencil,
+                    input_domain.aabb(),
+                    &output_box,
+                )
+            {
+                par_stencil::apply_interior(
+                    self.stencil,
+                    input_domain,
+                    output_domain,
+                    self.chunk_size,
+                );
+            } else {
+                par_stencil::apply_rows(
+                    self.bc,
+                    self.stencil,
+                    input_domain,
+                    output_domain,
+                    global_time,
+                    self.chunk_size,
+                );
+            }
             std::mem::swap(input_domain, output_domain);
         }
         std::mem::swap(input_domain, output_domain);
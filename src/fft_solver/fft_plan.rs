@@ -0,0 +1,424 @@
+use crate::fft_solver::*;
+use crate::util::*;
+use fftw::plan::*;
+use fftw::types::{c32, Flag};
+
+/// The forward and backward FFTW plans for
+/// real domains of a given size.
+///
+/// Like FFTW, transforms are un-normalized,
+/// `backward(forward(x))` yields `real_buffer_size * x`.
+pub struct FFTPlan {
+    pub forward_plan: Plan<f64, c64, Plan64>,
+    pub backward_plan: Plan<c64, f64, Plan64>,
+    pub real_buffer_size: usize,
+    pub complex_buffer_size: usize,
+    plan_size: Vec<usize>,
+    plan_type: PlanType,
+    plan_fallback: bool,
+}
+
+impl FFTPlan {
+    /// Create plans for real domains with the given exclusive bounds.
+    /// If planning fails with `plan_type` and `fallback` is set,
+    /// we warn and retry with `PlanType::Estimate`.
+    /// Slow sizes aren't flagged here, see `warn_slow_transforms`.
+    pub fn new<const GRID_DIMENSION: usize>(
+        size: &Coord<GRID_DIMENSION>,
+        plan_type: PlanType,
+        fallback: bool,
+    ) -> Self {
+        let plan_size = size.try_cast::<usize>().unwrap();
+        Self::from_plan_size(
+            plan_size.as_slice().to_vec(),
+            indexing::real_buffer_size(size),
+            indexing::complex_buffer_size(size),
+            plan_type,
+            fallback,
+        )
+    }
+
+    fn from_plan_size(
+        plan_size: Vec<usize>,
+        real_buffer_size: usize,
+        complex_buffer_size: usize,
+        plan_type: PlanType,
+        plan_fallback: bool,
+    ) -> Self {
+        let forward_plan = plan_with_fallback(
+            |flag| R2CPlan64::aligned(plan_size.as_slice(), flag),
+            plan_type,
+            plan_fallback,
+        );
+        let backward_plan = plan_with_fallback(
+            |flag| C2RPlan64::aligned(plan_size.as_slice(), flag),
+            plan_type,
+            plan_fallback,
+        );
+
+        FFTPlan {
+            forward_plan,
+            backward_plan,
+            real_buffer_size,
+            complex_buffer_size,
+            plan_size,
+            plan_type,
+            plan_fallback,
+        }
+    }
+
+    /// The real domain size these plans were made for.
+    pub fn plan_size(&self) -> &[usize] {
+        &self.plan_size
+    }
+
+    /// Real to complex transform.
+    /// Buffers must match the planned sizes exactly.
+    #[track_caller]
+    pub fn forward(&self, real: &mut [f64], complex: &mut [c64]) {
+        assert_eq!(
+            real.len(),
+            self.real_buffer_size,
+            "ERROR: forward real buffer size mismatch"
+        );
+        assert_eq!(
+            complex.len(),
+            self.complex_buffer_size,
+            "ERROR: forward complex buffer size mismatch"
+        );
+        self.forward_plan.r2c(real, complex).unwrap();
+    }
+
+    /// Complex to real transform, un-normalized.
+    /// Buffers must match the planned sizes exactly.
+    /// Note that FFTW may overwrite the complex input.
+    #[track_caller]
+    pub fn backward(&self, complex: &mut [c64], real: &mut [f64]) {
+        assert_eq!(
+            complex.len(),
+            self.complex_buffer_size,
+            "ERROR: backward complex buffer size mismatch"
+        );
+        assert_eq!(
+            real.len(),
+            self.real_buffer_size,
+            "ERROR: backward real buffer size mismatch"
+        );
+        self.backward_plan.c2r(complex, real).unwrap();
+    }
+}
+
+/// FFTW plans can't be copied, so cloning plans the same size again
+/// with the original plan type.
+/// This is cheap once the first plan has left its wisdom,
+/// but with an empty wisdom store `PlanType::Measure` may take a while.
+impl Clone for FFTPlan {
+    fn clone(&self) -> Self {
+        Self::from_plan_size(
+            self.plan_size.clone(),
+            self.real_buffer_size,
+            self.complex_buffer_size,
+            self.plan_type,
+            self.plan_fallback,
+        )
+    }
+}
+
+/// Single precision analog of `FFTPlan`, used by `PeriodicSolverF32`.
+pub struct FFTPlan32 {
+    pub forward_plan: Plan<f32, c32, Plan32>,
+    pub backward_plan: Plan<c32, f32, Plan32>,
+    pub real_buffer_size: usize,
+    pub complex_buffer_size: usize,
+}
+
+impl FFTPlan32 {
+    /// Create plans for real domains with the given exclusive bounds,
+    /// see `FFTPlan::new`.
+    pub fn new<const GRID_DIMENSION: usize>(
+        size: &Coord<GRID_DIMENSION>,
+        plan_type: PlanType,
+        fallback: bool,
+    ) -> Self {
+        let plan_size = size.try_cast::<usize>().unwrap();
+        let plan_size = plan_size.as_slice();
+        let forward_plan = plan_with_fallback(
+            |flag| R2CPlan32::aligned(plan_size, flag),
+            plan_type,
+            fallback,
+        );
+        let backward_plan = plan_with_fallback(
+            |flag| C2RPlan32::aligned(plan_size, flag),
+            plan_type,
+            fallback,
+        );
+        FFTPlan32 {
+            forward_plan,
+            backward_plan,
+            real_buffer_size: indexing::real_buffer_size(size),
+            complex_buffer_size: indexing::complex_buffer_size(size),
+        }
+    }
+
+    /// Real to complex transform.
+    /// Buffers must match the planned sizes exactly.
+    #[track_caller]
+    pub fn forward(&self, real: &mut [f32], complex: &mut [c32]) {
+        assert_eq!(
+            real.len(),
+            self.real_buffer_size,
+            "ERROR: forward real buffer size mismatch"
+        );
+        assert_eq!(
+            complex.len(),
+            self.complex_buffer_size,
+            "ERROR: forward complex buffer size mismatch"
+        );
+        self.forward_plan.r2c(real, complex).unwrap();
+    }
+
+    /// Complex to real transform, un-normalized.
+    /// Buffers must match the planned sizes exactly.
+    #[track_caller]
+    pub fn backward(&self, complex: &mut [c32], real: &mut [f32]) {
+        assert_eq!(
+            complex.len(),
+            self.complex_buffer_size,
+            "ERROR: backward complex buffer size mismatch"
+        );
+        assert_eq!(
+            real.len(),
+            self.real_buffer_size,
+            "ERROR: backward real buffer size mismatch"
+        );
+        self.backward_plan.c2r(complex, real).unwrap();
+    }
+}
+
+/// Create and discard plans for each size,
+/// leaving the accumulated wisdom in FFTW's global state.
+/// After this, planning these sizes with `PlanType::WisdomOnly` succeeds.
+/// See `APPlan::required_transform_sizes`.
+pub fn warm_wisdom<const GRID_DIMENSION: usize>(
+    sizes: &[Coord<GRID_DIMENSION>],
+    plan_type: PlanType,
+) {
+    for size in sizes {
+        FFTPlan::new(size, plan_type, false);
+    }
+}
+
+/// Transform sizes with prime factors above this are much slower in FFTW.
+pub const SLOW_PRIME_FACTOR_THRESHOLD: usize = 100;
+
+/// Largest prime factor of n, with 1 for n <= 1.
+pub fn largest_prime_factor(mut n: usize) -> usize {
+    let mut result = 1;
+    let mut factor = 2;
+    while factor * factor <= n {
+        while n % factor == 0 {
+            result = factor;
+            n /= factor;
+        }
+        factor += 1;
+    }
+    if n > 1 {
+        result = n;
+    }
+    result
+}
+
+/// `slow_transform_warning` for each distinct size in `sizes`.
+pub fn slow_transform_warnings<const GRID_DIMENSION: usize>(
+    sizes: &[Coord<GRID_DIMENSION>],
+) -> Vec<String> {
+    let mut seen = Vec::new();
+    let mut result = Vec::new();
+    for size in sizes {
+        if seen.contains(size) {
+            continue;
+        }
+        seen.push(*size);
+        if let Some(warning) = slow_transform_warning(size) {
+            result.push(warning);
+        }
+    }
+    result
+}
+
+/// Print `slow_transform_warnings` to stderr,
+/// once per distinct size no matter how many plans use it.
+pub fn warn_slow_transforms<const GRID_DIMENSION: usize>(
+    sizes: &[Coord<GRID_DIMENSION>],
+) {
+    for warning in slow_transform_warnings(sizes) {
+        eprintln!("{}", warning);
+    }
+}
+
+/// Returns a warning message if any dimension of `size` has a prime factor
+/// above `SLOW_PRIME_FACTOR_THRESHOLD`.
+pub fn slow_transform_warning<const GRID_DIMENSION: usize>(
+    size: &Coord<GRID_DIMENSION>,
+) -> Option<String> {
+    for d in 0..GRID_DIMENSION {
+        let factor = largest_prime_factor(size[d] as usize);
+        if factor > SLOW_PRIME_FACTOR_THRESHOLD {
+            return Some(format!(
+                "WARNING: FFT size {:?} has prime factor {} in dimension {}, \
+                 transforms may be much slower. \
+                 Consider padding the domain to a smoother size.",
+                size.as_slice(),
+                factor,
+                d
+            ));
+        }
+    }
+    None
+}
+
+/// Run `create` with the flag for `plan_type`.
+/// On failure, retry with `PlanType::Estimate` if `fallback` is set,
+/// otherwise panic.
+pub fn plan_with_fallback<PlanT, ErrorT, F>(
+    create: F,
+    plan_type: PlanType,
+    fallback: bool,
+) -> PlanT
+where
+    ErrorT: std::fmt::Debug,
+    F: Fn(Flag) -> Result<PlanT, ErrorT>,
+{
+    match create(plan_type.to_fftw3_flag()) {
+        Ok(plan) => plan,
+        Err(error) => {
+            if !fallback || matches!(plan_type, PlanType::Estimate) {
+                panic!(
+                    "ERROR: FFTW planning with {:?} failed: {:?}",
+                    plan_type, error
+                );
+            }
+            eprintln!(
+                "WARNING: FFTW planning with {:?} failed: {:?}, using {:?}",
+                plan_type,
+                error,
+                PlanType::Estimate
+            );
+            create(PlanType::Estimate.to_fftw3_flag()).unwrap_or_else(|error| {
+                panic!(
+                    "ERROR: FFTW planning with {:?} failed: {:?}",
+                    PlanType::Estimate,
+                    error
+                )
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use fftw::array::*;
+    use float_cmp::assert_approx_eq;
+    use std::cell::Cell;
+
+    #[test]
+    fn fallback_test() {
+        // Pretend everything but estimate fails to plan
+        let attempts = Cell::new(0);
+        let create = |flag: Flag| {
+            attempts.set(attempts.get() + 1);
+            if flag == Flag::ESTIMATE {
+                R2CPlan64::aligned(&[16], flag).map_err(|e| format!("{:?}", e))
+            } else {
+                Err("forced planning failure".to_string())
+            }
+        };
+        let plan = plan_with_fallback(create, PlanType::Patient, true);
+        assert_eq!(attempts.get(), 2);
+
+        // The fallback plan is usable
+        let mut input = AlignedVec::new(16);
+        let mut output = AlignedVec::new(9);
+        for i in 0..16 {
+            input[i] = 1.0;
+        }
+        plan.r2c(&mut input, &mut output).unwrap();
+        assert_approx_eq!(f64, output[0].re, 16.0);
+        assert_approx_eq!(f64, output[1].norm(), 0.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn no_fallback_test() {
+        let create = |_flag: Flag| -> Result<usize, String> {
+            Err("forced planning failure".to_string())
+        };
+        plan_with_fallback(create, PlanType::Patient, false);
+    }
+
+    #[test]
+    fn slow_transform_warning_test() {
+        assert_eq!(largest_prime_factor(1), 1);
+        assert_eq!(largest_prime_factor(2), 2);
+        assert_eq!(largest_prime_factor(1000), 5);
+        assert_eq!(largest_prime_factor(1009), 1009);
+        assert_eq!(largest_prime_factor(2 * 3 * 101), 101);
+
+        assert!(slow_transform_warning(&vector![1024, 1000]).is_none());
+        assert!(slow_transform_warning(&vector![97]).is_none());
+        let warning = slow_transform_warning(&vector![1000, 1009]).unwrap();
+        assert!(warning.contains("1009"));
+        assert!(warning.contains("dimension 1"));
+
+        // One warning per distinct slow size
+        let sizes = [
+            vector![1009, 64],
+            vector![1024, 64],
+            vector![1009, 64],
+            vector![64, 1013],
+            vector![1009, 64],
+        ];
+        let warnings = slow_transform_warnings(&sizes);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("1009"));
+        assert!(warnings[1].contains("1013"));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let size = vector![4, 6];
+        let plan = FFTPlan::new(&size, PlanType::Estimate, false);
+        assert_eq!(plan.real_buffer_size, 24);
+        assert_eq!(plan.complex_buffer_size, 16);
+
+        let mut real = AlignedVec::new(24);
+        let mut complex = AlignedVec::new(16);
+        for i in 0..24 {
+            real[i] = (i * i) as f64 - 3.0;
+        }
+        let original = real.as_slice().to_vec();
+
+        plan.forward(&mut real, &mut complex);
+        plan.backward(&mut complex, &mut real);
+
+        // Un-normalized, so divide by the real buffer size
+        for i in 0..24 {
+            assert_approx_eq!(
+                f64,
+                real[i] / 24.0,
+                original[i],
+                epsilon = 1e-10
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn forward_size_mismatch() {
+        let plan = FFTPlan::new(&vector![8], PlanType::Estimate, false);
+        let mut real = AlignedVec::new(9);
+        let mut complex = AlignedVec::new(5);
+        plan.forward(&mut real, &mut complex);
+    }
+}
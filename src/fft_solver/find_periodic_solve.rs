@@ -3,7 +3,9 @@ use crate::util::*;
 #[derive(Debug)]
 pub struct PeriodicSolveParams<const DIMENSION: usize> {
     pub stencil_slopes: Bounds<DIMENSION>,
-    pub cutoff: i32,
+
+    /// Each sloped axis must be longer than its cutoff.
+    pub cutoff: Coord<DIMENSION>,
     pub ratio: f64,
     pub max_steps: Option<usize>,
 }
@@ -22,8 +24,23 @@ pub fn find_periodic_solve<const DIMENSION: usize>(
     input_aabb: &AABB<DIMENSION>,
     params: &PeriodicSolveParams<DIMENSION>,
 ) -> Option<PeriodicSolve<DIMENSION>> {
-    if input_aabb.min_size_len() <= params.cutoff {
-        return None;
+    // Stencils without slopes, i.e. the identity, never shrink the box,
+    // so the whole box can be solved for up to `max_steps`.
+    if params.stencil_slopes == Bounds::zeros() {
+        return params.max_steps.map(|steps| PeriodicSolve {
+            output_aabb: *input_aabb,
+            steps,
+        });
+    }
+
+    let exclusive_bounds = input_aabb.exclusive_bounds();
+    for d in 0..DIMENSION {
+        let slopes = &params.stencil_slopes;
+        if slopes[(d, 0)] + slopes[(d, 1)] > 0
+            && exclusive_bounds[d] <= params.cutoff[d]
+        {
+            return None;
+        }
     }
 
     let (steps, output_aabb) = input_aabb.shrink(
@@ -10,7 +10,7 @@ pub fn frustrum_input_aabb<const GRID_DIMENSION: usize>(
 ) -> AABB<GRID_DIMENSION> {
     let trapezoid_slopes =
         slopes_to_outward_diff(&stencil_slopes.component_mul(sloped_sides));
-    output_box.add_bounds_diff(steps as i32 * trapezoid_slopes)
+    output_box.add_bounds_diff(scale_bounds(steps, &trapezoid_slopes))
 }
 
 /// This needs to match logic from AABB::decomposition
@@ -67,4 +67,19 @@ mod unit_tests {
             assert_eq!(d3, expected);
         }
     }
+
+    #[test]
+    #[should_panic]
+    fn frustrum_input_aabb_overflow() {
+        // 100k steps with a very wide stencil must not wrap
+        let output_box = AABB::new(matrix![0, 99]);
+        let sloped_sides = matrix![1, 1];
+        let stencil_slopes = matrix![30000, 30000];
+        frustrum_input_aabb(
+            100000,
+            &output_box,
+            &sloped_sides,
+            &stencil_slopes,
+        );
+    }
 }
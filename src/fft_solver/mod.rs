@@ -5,34 +5,46 @@ pub const MIN_ALIGNMENT: usize = 128;
 
 mod ap_accountant;
 mod ap_frustrum;
+mod ap_instrumentation;
 mod ap_plan;
+mod ap_plan_verify;
 mod ap_planner;
 mod ap_scratch;
 mod ap_scratch_builder;
 mod ap_solver;
+mod ap_solver_builder;
 mod direct_frustrum_solver;
 
 pub use ap_accountant::*;
 pub use ap_frustrum::*;
+pub use ap_instrumentation::*;
 pub use ap_plan::*;
+pub use ap_plan_verify::*;
 pub use ap_planner::*;
 pub use ap_scratch::*;
 pub use ap_scratch_builder::*;
 pub use ap_solver::*;
+pub use ap_solver_builder::*;
 pub use direct_frustrum_solver::*;
 
 mod convolution_gen;
 mod convolution_op;
 mod convolution_store;
+mod fft_plan;
 mod find_periodic_solve;
 mod frustrum_util;
 mod periodic_solver;
+mod periodic_solver_f32;
 mod plan_type;
+mod spectral_filter;
 
 pub use convolution_gen::*;
 pub use convolution_op::*;
 pub use convolution_store::*;
+pub use fft_plan::*;
 pub use find_periodic_solve::*;
 pub use frustrum_util::*;
 pub use periodic_solver::*;
+pub use periodic_solver_f32::*;
 pub use plan_type::*;
+pub use spectral_filter::*;
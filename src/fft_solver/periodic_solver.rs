@@ -1,16 +1,57 @@
 use crate::domain::*;
 use crate::fft_solver::*;
+use crate::par_slice;
 use crate::stencil::*;
 use crate::util::*;
 use fftw::array::*;
 
+/// Chunk size used by `PeriodicSolver::new`, matches the executables.
+pub const DEFAULT_CHUNK_SIZE: usize = 1000;
+
 pub struct PeriodicSolver {
     operation: ConvolutionOperation,
     complex_buffer: AlignedVec<c64>,
     chunk_size: usize,
+
+    /// Single step convolution, present for solvers from `new`.
+    step_convolution: Option<AlignedVec<c64>>,
+
+    /// Steps the current `operation` convolution is for.
+    steps: usize,
 }
 
 impl PeriodicSolver {
+    /// Create a solver for any number of steps, see `apply_steps`.
+    /// Owns its scratch buffers and uses `DEFAULT_CHUNK_SIZE`.
+    pub fn new<
+        Operation,
+        const GRID_DIMENSION: usize,
+        const NEIGHBORHOOD_SIZE: usize,
+    >(
+        stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+        aabb: AABB<GRID_DIMENSION>,
+        plan_type: PlanType,
+    ) -> Self
+    where
+        Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    {
+        let mut real_buffer = AlignedVec::new(aabb.buffer_size());
+        let mut solver = Self::create(
+            stencil,
+            &mut real_buffer,
+            &aabb,
+            1,
+            plan_type,
+            true,
+            DEFAULT_CHUNK_SIZE,
+        );
+        let mut step_convolution =
+            AlignedVec::new(solver.operation.convolution.len());
+        step_convolution.copy_from_slice(&solver.operation.convolution);
+        solver.step_convolution = Some(step_convolution);
+        solver
+    }
+
     pub fn create<
         Operation,
         const GRID_DIMENSION: usize,
@@ -21,11 +62,14 @@ impl PeriodicSolver {
         aabb: &AABB<GRID_DIMENSION>,
         steps: usize,
         plan_type: PlanType,
+        plan_fallback: bool,
         chunk_size: usize,
     ) -> Self
     where
         Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
     {
+        stencil.warn_missing_origin_term();
+        warn_slow_transforms(&[aabb.exclusive_bounds()]);
         let stencil_weights = stencil.extract_weights();
         let mut complex_buffer = AlignedVec::new(aabb.complex_buffer_size());
         let operation = ConvolutionOperation::create(
@@ -36,6 +80,7 @@ impl PeriodicSolver {
             aabb,
             steps,
             plan_type,
+            plan_fallback,
             chunk_size,
         );
 
@@ -43,7 +88,53 @@ impl PeriodicSolver {
             operation,
             complex_buffer,
             chunk_size,
+            step_convolution: None,
+            steps,
+        }
+    }
+
+    /// See `ComplexMulPrecision`, defaults to `F64`.
+    pub fn set_complex_mul_precision(
+        &mut self,
+        precision: ComplexMulPrecision,
+    ) {
+        self.operation.set_precision(precision);
+    }
+
+    /// See `SpectralFilter`, defaults to none.
+    pub fn set_spectral_filter(&mut self, filter: Option<SpectralFilter>) {
+        self.operation.spectral_filter = filter;
+    }
+
+    /// Apply `steps` steps, the convolution is only recomputed
+    /// when `steps` differs from the previous call.
+    /// Requires a solver from `new`.
+    pub fn apply_steps<
+        const GRID_DIMENSION: usize,
+        DomainType: DomainView<GRID_DIMENSION>,
+    >(
+        &mut self,
+        input: &mut DomainType,
+        output: &mut DomainType,
+        steps: usize,
+    ) {
+        if steps != self.steps {
+            let step_convolution = self.step_convolution.as_ref().expect(
+                "ERROR: apply_steps requires a solver from PeriodicSolver::new",
+            );
+            let mut x_buffer = AlignedVec::new(step_convolution.len());
+            x_buffer.copy_from_slice(step_convolution);
+            par_slice::power(
+                steps,
+                &mut x_buffer,
+                &mut self.operation.convolution,
+                self.chunk_size,
+            );
+            let precision = self.operation.precision();
+            self.operation.set_precision(precision);
+            self.steps = steps;
         }
+        self.apply(input, output);
     }
 
     pub fn apply<
@@ -95,6 +186,7 @@ mod unit_tests {
             &aabb,
             steps,
             plan_type,
+            false,
             chunk_size,
         );
         solver.apply(&mut input_domain, &mut output_domain);
@@ -196,6 +288,7 @@ mod unit_tests {
             &aabb,
             n,
             plan_type,
+            false,
             chunk_size,
         );
         solver.apply(&mut input_domain, &mut output_domain);
@@ -207,4 +300,90 @@ mod unit_tests {
             );
         }
     }
+
+    #[test]
+    #[should_panic(expected = "planned for size [8, 4]")]
+    fn size_mismatch_test() {
+        // Same buffer size, transposed shape
+        let stencil = Stencil::new([[0, 0]], |args: &[f64; 1]| args[0]);
+        let aabb = AABB::new(matrix![0, 7; 0, 3]);
+        let mut solver =
+            PeriodicSolver::new(&stencil, aabb, PlanType::Estimate);
+        let other_aabb = AABB::new(matrix![0, 3; 0, 7]);
+        let mut input_domain = OwnedDomain::new(other_aabb);
+        let mut output_domain = OwnedDomain::new(other_aabb);
+        solver.apply(&mut input_domain, &mut output_domain);
+    }
+
+    #[test]
+    fn apply_steps_test() {
+        let chunk_size = 10;
+        let aabb = AABB::new(matrix![0, 63]);
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.3);
+        let mut solver =
+            PeriodicSolver::new(&stencil, aabb, PlanType::Estimate);
+
+        let mut ic = OwnedDomain::new(aabb);
+        ic.par_set_values(|c| if c[0] < 16 { 1.0 } else { 0.0 }, chunk_size);
+
+        // Changing step counts, and repeating one
+        for steps in [5, 17, 17, 1] {
+            let mut input = OwnedDomain::new(aabb);
+            let mut output = OwnedDomain::new(aabb);
+            input.buffer_mut().copy_from_slice(ic.buffer());
+            solver.apply_steps(&mut input, &mut output, steps);
+
+            let mut direct_input = OwnedDomain::new(aabb);
+            let mut direct_output = OwnedDomain::new(aabb);
+            direct_input.buffer_mut().copy_from_slice(ic.buffer());
+            crate::solver::direct_periodic_apply(
+                &stencil,
+                &mut direct_input,
+                &mut direct_output,
+                steps,
+                chunk_size,
+            );
+            for (a, b) in output.buffer().iter().zip(direct_output.buffer()) {
+                assert_approx_eq!(f64, *a, *b, epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn complex_mul_precision_test() {
+        let chunk_size = 100;
+        let steps = 200;
+        let aabb = AABB::new(matrix![0, 63; 0, 63]);
+        let stencil =
+            crate::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+
+        let mut results = Vec::new();
+        for precision in [ComplexMulPrecision::F64, ComplexMulPrecision::F32] {
+            let mut input_domain = OwnedDomain::new(aabb);
+            let mut output_domain = OwnedDomain::new(aabb);
+            let mut solver = PeriodicSolver::create(
+                &stencil,
+                output_domain.buffer_mut(),
+                &aabb,
+                steps,
+                PlanType::Estimate,
+                false,
+                chunk_size,
+            );
+            solver.set_complex_mul_precision(precision);
+            crate::init::normal_ic_2d(&mut input_domain, chunk_size);
+            solver.apply(&mut input_domain, &mut output_domain);
+            results.push(output_domain);
+        }
+
+        // Values are in [0, 1], single precision rounding of each
+        // spectral coefficient gives errors near f32 epsilon, ~1.2e-7.
+        // Summed over the inverse transform we stay below 1e-5.
+        let mut max_diff = 0.0f64;
+        for (a, b) in results[0].buffer().iter().zip(results[1].buffer()) {
+            max_diff = max_diff.max((a - b).abs());
+        }
+        assert!(max_diff < 1e-5, "f32 multiply error {}", max_diff);
+        assert!(max_diff > 0.0);
+    }
 }
@@ -0,0 +1,131 @@
+use crate::fft_solver::*;
+use crate::par_slice;
+use crate::stencil::*;
+use crate::util::*;
+use fftw::array::*;
+use fftw::types::c32;
+
+/// Single precision analog of `PeriodicSolver`,
+/// halving the memory and bandwidth of the buffers and convolution.
+/// Works on bare buffers laid out over `aabb`,
+/// which must be aligned for FFTW, i.e. `AlignedVec<f32>`.
+/// Expect errors around `1e-6` relative instead of `1e-15`.
+pub struct PeriodicSolverF32<const GRID_DIMENSION: usize> {
+    fft_plan: FFTPlan32,
+    convolution: AlignedVec<c32>,
+    complex_buffer: AlignedVec<c32>,
+    aabb: AABB<GRID_DIMENSION>,
+    chunk_size: usize,
+}
+
+impl<const GRID_DIMENSION: usize> PeriodicSolverF32<GRID_DIMENSION> {
+    /// Create a solver for `steps` steps of a linear `stencil`.
+    pub fn new<Operation, const NEIGHBORHOOD_SIZE: usize>(
+        stencil: &StencilF32<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+        aabb: AABB<GRID_DIMENSION>,
+        steps: usize,
+        plan_type: PlanType,
+        chunk_size: usize,
+    ) -> Self
+    where
+        Operation: StencilOperation<f32, NEIGHBORHOOD_SIZE>,
+    {
+        warn_slow_transforms(&[aabb.exclusive_bounds()]);
+        let fft_plan =
+            FFTPlan32::new(&aabb.exclusive_bounds(), plan_type, true);
+
+        // Mirrored offsets, see `ConvolutionOperation::create`
+        let mut real_buffer = AlignedVec::new(aabb.buffer_size());
+        let weights = stencil.extract_weights();
+        for (offset, weight) in stencil.offsets().iter().zip(weights) {
+            let rn_i: Coord<GRID_DIMENSION> = aabb.min() + offset * -1;
+            let periodic_coord = aabb.periodic_coord(&rn_i);
+            real_buffer[aabb.coord_to_linear(&periodic_coord)] += weight;
+        }
+
+        let n_c = aabb.complex_buffer_size();
+        let mut step_convolution = AlignedVec::new(n_c);
+        fft_plan.forward(&mut real_buffer, &mut step_convolution);
+        let mut convolution = AlignedVec::new(n_c);
+        par_slice::power(
+            steps,
+            &mut step_convolution,
+            &mut convolution,
+            chunk_size,
+        );
+
+        PeriodicSolverF32 {
+            fft_plan,
+            convolution,
+            complex_buffer: AlignedVec::new(n_c),
+            aabb,
+            chunk_size,
+        }
+    }
+
+    pub fn aabb(&self) -> &AABB<GRID_DIMENSION> {
+        &self.aabb
+    }
+
+    /// Solve from `input` into `output`, `input` is clobbered.
+    pub fn apply(&mut self, input: &mut [f32], output: &mut [f32]) {
+        self.fft_plan.forward(input, &mut self.complex_buffer);
+        par_slice::multiply_by(
+            &mut self.complex_buffer,
+            &self.convolution,
+            self.chunk_size,
+        );
+        self.fft_plan.backward(&mut self.complex_buffer, output);
+        let n_r = self.aabb.buffer_size() as f32;
+        par_slice::div(output, n_r, self.chunk_size);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::domain::*;
+    use crate::solver::*;
+    use nalgebra::matrix;
+
+    #[test]
+    fn periodic_f32_test() {
+        let chunk_size = 10;
+        let aabb = AABB::new(matrix![0, 63]);
+        let ic = |c: Coord<1>| ((c[0] * 7) % 13) as f64;
+        let steps = 40;
+
+        let stencil_f32 = Stencil::new([[-1], [0], [1]], |args: &[f32; 3]| {
+            args[1] + 0.25 * (args[0] - 2.0 * args[1] + args[2])
+        });
+        let mut solver = PeriodicSolverF32::new(
+            &stencil_f32,
+            aabb,
+            steps,
+            PlanType::Estimate,
+            chunk_size,
+        );
+        let mut input_f32 = AlignedVec::new(aabb.buffer_size());
+        let mut output_f32 = AlignedVec::new(aabb.buffer_size());
+        for (i, value) in input_f32.iter_mut().enumerate() {
+            *value = ic(aabb.linear_to_coord(i)) as f32;
+        }
+        solver.apply(&mut input_f32, &mut output_f32);
+
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let mut input = OwnedDomain::new(aabb);
+        let mut output = OwnedDomain::new(aabb);
+        input.par_set_values(ic, chunk_size);
+        direct_periodic_apply(
+            &stencil,
+            &mut input,
+            &mut output,
+            steps,
+            chunk_size,
+        );
+
+        for (single, double) in output_f32.iter().zip(output.buffer()) {
+            assert!((*single as f64 - double).abs() < 1e-4);
+        }
+    }
+}
@@ -3,7 +3,12 @@ use fftw::types::Flag;
 
 /// FFTW3 Provides several strategies for plan creation,
 /// we expose three of them.
-#[derive(Copy, Clone, Debug, ValueEnum, Default)]
+#[derive(Copy, Clone, Debug, ValueEnum, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
 pub enum PlanType {
     /// Create optimziated plan
     #[default]
@@ -0,0 +1,140 @@
+use crate::util::*;
+use rayon::prelude::*;
+
+/// Zeros spectral coefficients above a fraction of the Nyquist frequency,
+/// applied between the forward and backward transforms of a periodic solve.
+/// A `cutoff_fraction` of 2/3 gives the usual 2/3 rule.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpectralFilter {
+    pub cutoff_fraction: f64,
+}
+
+impl SpectralFilter {
+    pub fn new(cutoff_fraction: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&cutoff_fraction),
+            "ERROR: cutoff fraction must be in [0, 1], got {}",
+            cutoff_fraction
+        );
+        SpectralFilter { cutoff_fraction }
+    }
+
+    /// The 2/3 rule anti-aliasing filter.
+    pub fn two_thirds() -> Self {
+        SpectralFilter::new(2.0 / 3.0)
+    }
+
+    /// Does the coefficient at `index`, in the r2c complex layout
+    /// for a real domain of `size`, survive the filter.
+    /// Every dimension must be at or below the cutoff.
+    pub fn keep<const GRID_DIMENSION: usize>(
+        &self,
+        index: &Coord<GRID_DIMENSION>,
+        size: &Coord<GRID_DIMENSION>,
+    ) -> bool {
+        for d in 0..GRID_DIMENSION {
+            let n = size[d];
+            let frequency = indexing::signed_frequency(index[d], n).abs();
+            let nyquist = (n / 2).max(1) as f64;
+            if frequency as f64 > self.cutoff_fraction * nyquist {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Zero the filtered coefficients of a complex buffer
+    /// for a real domain of `size`.
+    pub fn apply<const GRID_DIMENSION: usize>(
+        &self,
+        complex_buffer: &mut [c64],
+        size: &Coord<GRID_DIMENSION>,
+        chunk_size: usize,
+    ) {
+        let mut complex_size = *size;
+        complex_size[GRID_DIMENSION - 1] = size[GRID_DIMENSION - 1] / 2 + 1;
+        debug_assert_eq!(
+            complex_buffer.len(),
+            indexing::complex_buffer_size(size)
+        );
+        complex_buffer
+            .par_chunks_mut(chunk_size)
+            .enumerate()
+            .for_each(|(i, chunk): (usize, &mut [c64])| {
+                let offset = i * chunk_size;
+                for (j, value) in chunk.iter_mut().enumerate() {
+                    let index =
+                        indexing::linear_to_coord(offset + j, &complex_size);
+                    if !self.keep(&index, size) {
+                        *value = c64::zero();
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::domain::*;
+    use crate::fft_solver::*;
+    use crate::stencil::*;
+    use float_cmp::assert_approx_eq;
+
+    #[test]
+    fn spectral_filter_test() {
+        let chunk_size = 7;
+        let n = 64;
+        let aabb = AABB::new(matrix![0, n - 1]);
+        let stencil = Stencil::new([[0]], |args: &[f64; 1]| args[0]);
+        let wave = |k: i32| {
+            move |c: Coord<1>| {
+                (2.0 * std::f64::consts::PI * (k * c[0]) as f64 / n as f64)
+                    .cos()
+            }
+        };
+
+        let mut input_domain = OwnedDomain::new(aabb);
+        let mut output_domain = OwnedDomain::new(aabb);
+        let mut solver = PeriodicSolver::create(
+            &stencil,
+            output_domain.buffer_mut(),
+            &aabb,
+            1,
+            PlanType::Estimate,
+            false,
+            chunk_size,
+        );
+        solver.set_spectral_filter(Some(SpectralFilter::two_thirds()));
+
+        // High frequency only is removed
+        input_domain.par_set_values(wave(30), chunk_size);
+        solver.apply(&mut input_domain, &mut output_domain);
+        for v in output_domain.buffer() {
+            assert_approx_eq!(f64, *v, 0.0, epsilon = 1e-12);
+        }
+
+        // Low frequency passes unchanged
+        input_domain.par_set_values(wave(3), chunk_size);
+        solver.apply(&mut input_domain, &mut output_domain);
+        for c in aabb.coord_iter() {
+            assert_approx_eq!(
+                f64,
+                output_domain.view(&c),
+                wave(3)(c),
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn keep_test() {
+        let filter = SpectralFilter::new(0.5);
+        let size = vector![8, 8];
+        assert!(filter.keep(&vector![0, 0], &size));
+        assert!(filter.keep(&vector![2, 2], &size));
+        assert!(filter.keep(&vector![6, 1], &size));
+        assert!(!filter.keep(&vector![3, 0], &size));
+        assert!(!filter.keep(&vector![0, 4], &size));
+    }
+}
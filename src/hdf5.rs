@@ -0,0 +1,102 @@
+//! HDF5 Output
+//!
+//! 3D domains written as chunked HDF5 datasets,
+//! much smaller than VTK for large grids and easy to load with h5py.
+//! The dataset shape is the domain's exclusive bounds,
+//! in our row-major layout, so `data[i, j, k]` is coordinate `min + (i, j, k)`.
+//! The AABB corners are stored as `min` and `max` attributes.
+
+use crate::domain::*;
+use crate::util::*;
+
+/// Largest chunk extent in each dimension.
+pub const HDF5_CHUNK_LEN: usize = 64;
+
+pub fn write_hdf5_3d<P: AsRef<std::path::Path>, DomainType: DomainView<3>>(
+    domain: &DomainType,
+    path: &P,
+    dataset: &str,
+) {
+    println!("Writing hdf5: {:?}", path.as_ref());
+    let aabb = domain.aabb();
+    let exclusive_bounds = aabb.exclusive_bounds();
+    let shape: [usize; 3] =
+        std::array::from_fn(|d| exclusive_bounds[d] as usize);
+    let chunk = shape.map(|n| n.clamp(1, HDF5_CHUNK_LEN));
+
+    let write = || -> ::hdf5::Result<()> {
+        let file = ::hdf5::File::create(path)?;
+        let data = file
+            .new_dataset::<f64>()
+            .shape(shape)
+            .chunk(chunk)
+            .create(dataset)?;
+        data.write_raw(domain.buffer())?;
+        data.new_attr::<i32>()
+            .shape(3)
+            .create("min")?
+            .write_raw(aabb.min().as_slice())?;
+        data.new_attr::<i32>()
+            .shape(3)
+            .create("max")?
+            .write_raw(aabb.max().as_slice())?;
+        Ok(())
+    };
+    write().unwrap_or_else(|error| {
+        panic!("ERROR: Failed to write hdf5 {:?}: {}", path.as_ref(), error)
+    });
+}
+
+/// Read back a dataset written by `write_hdf5_3d`.
+pub fn read_hdf5_3d<P: AsRef<std::path::Path>>(
+    path: &P,
+    dataset: &str,
+) -> OwnedDomain<3> {
+    let read = || -> ::hdf5::Result<OwnedDomain<3>> {
+        let file = ::hdf5::File::open(path)?;
+        let data = file.dataset(dataset)?;
+        let min = data.attr("min")?.read_raw::<i32>()?;
+        let max = data.attr("max")?.read_raw::<i32>()?;
+        let aabb = AABB::from_mm(
+            Coord::from_column_slice(&min),
+            Coord::from_column_slice(&max),
+        );
+        let values = data.read_raw::<f64>()?;
+        assert_eq!(
+            values.len(),
+            aabb.buffer_size(),
+            "ERROR: hdf5 dataset size does not match its min and max"
+        );
+        let mut domain = OwnedDomain::new(aabb);
+        domain.buffer_mut().copy_from_slice(&values);
+        Ok(domain)
+    };
+    read().unwrap_or_else(|error| {
+        panic!("ERROR: Failed to read hdf5 {:?}: {}", path.as_ref(), error)
+    })
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use nalgebra::matrix;
+
+    #[test]
+    fn round_trip_test() {
+        let aabb = AABB::new(matrix![-2, 5; 3, 4; 0, 70]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(
+            |c| (c[0] * 10000 + c[1] * 100 + c[2]) as f64 + 0.25,
+            100,
+        );
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_hdf5_test_{}.h5", std::process::id()));
+        write_hdf5_3d(&domain, &path, "frame");
+        let read = read_hdf5_3d(&path, "frame");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(*read.aabb(), aabb);
+        assert_eq!(read.buffer(), domain.buffer());
+    }
+}
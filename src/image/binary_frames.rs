@@ -0,0 +1,252 @@
+//! Binary Frame Streams
+//!
+//! Many frames of a fixed AABB appended to a single file,
+//! instead of one file per frame.
+//!
+//! The header is all little-endian:
+//! * `FRAME_MAGIC`, 8 bytes
+//! * frame count, u64, `UNKNOWN_FRAME_COUNT` until the writer is dropped
+//! * dimension, u32
+//! * dtype, u32, only `DTYPE_F64` for now
+//! * min and max corner for each dimension, i32
+//!
+//! followed by each frame's buffer as raw row-major f64 values.
+
+use crate::domain::*;
+use crate::raw::*;
+use crate::util::*;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+pub const FRAME_MAGIC: &[u8; 8] = b"NHLSFRMS";
+
+/// Frame count of a stream that was never finished,
+/// readers take frames until the end of the file.
+pub const UNKNOWN_FRAME_COUNT: u64 = u64::MAX;
+
+/// Little-endian f64 values.
+pub const DTYPE_F64: u32 = 0;
+
+/// Offset of the frame count in the header, just after the magic.
+const FRAME_COUNT_OFFSET: u64 = 8;
+
+/// Appends domain buffers to a single file,
+/// the frame count in the header is patched on drop.
+pub struct BinaryFrameWriter<const GRID_DIMENSION: usize> {
+    writer: BufWriter<std::fs::File>,
+    aabb: AABB<GRID_DIMENSION>,
+    frames: u64,
+}
+
+impl<const GRID_DIMENSION: usize> BinaryFrameWriter<GRID_DIMENSION> {
+    /// Create the file at `path` and write the header for `aabb`.
+    pub fn new<P: AsRef<Path>>(path: &P, aabb: AABB<GRID_DIMENSION>) -> Self {
+        println!("Writing frames: {:?}", path.as_ref());
+        let file = std::fs::File::create(path).unwrap_or_else(|error| {
+            panic!("ERROR: Can't create {:?}: {}", path.as_ref(), error)
+        });
+        let mut writer = BufWriter::new(file);
+        writer.write_all(FRAME_MAGIC).unwrap();
+        writer
+            .write_all(&UNKNOWN_FRAME_COUNT.to_le_bytes())
+            .unwrap();
+        writer
+            .write_all(&(GRID_DIMENSION as u32).to_le_bytes())
+            .unwrap();
+        writer.write_all(&DTYPE_F64.to_le_bytes()).unwrap();
+        for d in 0..GRID_DIMENSION {
+            writer
+                .write_all(&aabb.bounds[(d, 0)].to_le_bytes())
+                .unwrap();
+            writer
+                .write_all(&aabb.bounds[(d, 1)].to_le_bytes())
+                .unwrap();
+        }
+        BinaryFrameWriter {
+            writer,
+            aabb,
+            frames: 0,
+        }
+    }
+
+    /// Append the buffer of `domain`, which must match the stream's AABB.
+    #[track_caller]
+    pub fn add_frame<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        domain: &DomainType,
+    ) {
+        assert_eq!(
+            *domain.aabb(),
+            self.aabb,
+            "ERROR: frame {} does not match stream {}",
+            domain.aabb(),
+            self.aabb
+        );
+        write_values(domain, Layout::RowMajor, &mut self.writer).unwrap();
+        self.frames += 1;
+    }
+
+    /// Frames written so far.
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+impl<const GRID_DIMENSION: usize> Drop for BinaryFrameWriter<GRID_DIMENSION> {
+    fn drop(&mut self) {
+        self.writer
+            .seek(SeekFrom::Start(FRAME_COUNT_OFFSET))
+            .unwrap();
+        self.writer.write_all(&self.frames.to_le_bytes()).unwrap();
+        self.writer.flush().unwrap();
+    }
+}
+
+/// Reads the frames of a file written by `BinaryFrameWriter`.
+pub struct BinaryFrameReader<const GRID_DIMENSION: usize> {
+    reader: BufReader<std::fs::File>,
+    aabb: AABB<GRID_DIMENSION>,
+    frame_count: Option<u64>,
+    frames_read: u64,
+    frame_bytes: Vec<u8>,
+}
+
+impl<const GRID_DIMENSION: usize> BinaryFrameReader<GRID_DIMENSION> {
+    /// Open `path` and read the header,
+    /// panics if the file is not a frame stream of `GRID_DIMENSION`.
+    pub fn open<P: AsRef<Path>>(path: &P) -> Self {
+        let file = std::fs::File::open(path).unwrap_or_else(|error| {
+            panic!("ERROR: Can't open {:?}: {}", path.as_ref(), error)
+        });
+        let mut reader = BufReader::new(file);
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).unwrap();
+        assert_eq!(
+            &magic,
+            FRAME_MAGIC,
+            "ERROR: {:?} is not a frame stream",
+            path.as_ref()
+        );
+        let frame_count = read_u64(&mut reader);
+        let dimension = read_u32(&mut reader);
+        assert_eq!(
+            dimension as usize,
+            GRID_DIMENSION,
+            "ERROR: {:?} has dimension {}, expected {}",
+            path.as_ref(),
+            dimension,
+            GRID_DIMENSION
+        );
+        let dtype = read_u32(&mut reader);
+        assert_eq!(
+            dtype,
+            DTYPE_F64,
+            "ERROR: {:?} has unknown dtype {}",
+            path.as_ref(),
+            dtype
+        );
+        let mut bounds = Bounds::zero();
+        for d in 0..GRID_DIMENSION {
+            bounds[(d, 0)] = read_u32(&mut reader) as i32;
+            bounds[(d, 1)] = read_u32(&mut reader) as i32;
+        }
+        let aabb = AABB::new(bounds);
+        BinaryFrameReader {
+            reader,
+            aabb,
+            frame_count: (frame_count != UNKNOWN_FRAME_COUNT)
+                .then_some(frame_count),
+            frames_read: 0,
+            frame_bytes: vec![0; aabb.buffer_size() * 8],
+        }
+    }
+
+    pub fn aabb(&self) -> &AABB<GRID_DIMENSION> {
+        &self.aabb
+    }
+
+    /// Frame count from the header,
+    /// `None` if the writer was never dropped.
+    pub fn frame_count(&self) -> Option<u64> {
+        self.frame_count
+    }
+}
+
+impl<const GRID_DIMENSION: usize> Iterator
+    for BinaryFrameReader<GRID_DIMENSION>
+{
+    type Item = OwnedDomain<GRID_DIMENSION>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.frame_count == Some(self.frames_read) {
+            return None;
+        }
+        match self.reader.read_exact(&mut self.frame_bytes) {
+            Ok(()) => {}
+            Err(error)
+                if self.frame_count.is_none()
+                    && error.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                return None;
+            }
+            Err(error) => panic!(
+                "ERROR: Can't read frame {}: {}",
+                self.frames_read, error
+            ),
+        }
+        self.frames_read += 1;
+        let mut result = OwnedDomain::new(self.aabb);
+        read_values(&self.frame_bytes, Layout::RowMajor, &mut result);
+        Some(result)
+    }
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> u64 {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes).unwrap();
+    u64::from_le_bytes(bytes)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> u32 {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes).unwrap();
+    u32::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip_test() {
+        let aabb = AABB::new(matrix![-2, 5; 3, 7]);
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_frames_test_{}.bin", std::process::id()));
+
+        let mut frames = Vec::new();
+        {
+            let mut writer = BinaryFrameWriter::new(&path, aabb);
+            for f in 0..3 {
+                let mut domain = OwnedDomain::new(aabb);
+                domain.par_set_values(
+                    |c| (f * 100 + c[0] * 10 + c[1]) as f64 / 3.0,
+                    4,
+                );
+                writer.add_frame(&domain.as_slice_domain());
+                frames.push(domain);
+            }
+            assert_eq!(writer.frames(), 3);
+        }
+
+        let reader = BinaryFrameReader::<2>::open(&path);
+        assert_eq!(*reader.aabb(), aabb);
+        assert_eq!(reader.frame_count(), Some(3));
+        let read: Vec<_> = reader.collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read.len(), 3);
+        for (r, f) in read.iter().zip(frames.iter()) {
+            assert_eq!(r.aabb(), f.aabb());
+            assert_eq!(r.buffer(), f.buffer());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! CSV Export
+//!
+//! Plain text dumps of 1D fields for quick plotting in spreadsheets.
+//! The first column is the world coordinate,
+//! values are written with enough digits to parse back exactly.
+
+use crate::domain::*;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Write `x,value` rows, one per coordinate of `domain`.
+pub fn write_csv_1d<P: AsRef<Path>, DomainType: DomainView<1>>(
+    domain: &DomainType,
+    path: &P,
+) {
+    write_csv_1d_frames(std::slice::from_ref(domain), path);
+}
+
+/// Write one row per coordinate with a column for each frame,
+/// i.e. `x,frame_0,frame_1,...`.
+/// All frames must have the same AABB.
+#[track_caller]
+pub fn write_csv_1d_frames<P: AsRef<Path>, DomainType: DomainView<1>>(
+    frames: &[DomainType],
+    path: &P,
+) {
+    assert!(!frames.is_empty(), "ERROR: no frames to write");
+    let aabb = *frames[0].aabb();
+    for (f, frame) in frames.iter().enumerate() {
+        assert_eq!(
+            *frame.aabb(),
+            aabb,
+            "ERROR: frame {} has AABB {}, expected {}",
+            f,
+            frame.aabb(),
+            aabb
+        );
+    }
+
+    println!("Writing csv: {:?}", path.as_ref());
+    let file = std::fs::File::create(path).unwrap_or_else(|error| {
+        panic!("ERROR: Can't create {:?}: {}", path.as_ref(), error)
+    });
+    let mut writer = BufWriter::new(file);
+    if frames.len() == 1 {
+        write!(writer, "x,value").unwrap();
+    } else {
+        write!(writer, "x").unwrap();
+        for f in 0..frames.len() {
+            write!(writer, ",frame_{}", f).unwrap();
+        }
+    }
+    writeln!(writer).unwrap();
+    for i in 0..aabb.buffer_size() {
+        write!(writer, "{}", aabb.linear_to_coord(i)[0]).unwrap();
+        for frame in frames.iter() {
+            write!(writer, ",{}", frame.buffer()[i]).unwrap();
+        }
+        writeln!(writer).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::util::*;
+
+    fn parse_csv(path: &std::path::Path) -> (String, Vec<(i32, Vec<f64>)>) {
+        let csv = std::fs::read_to_string(path).unwrap();
+        let mut lines = csv.lines();
+        let header = lines.next().unwrap().to_string();
+        let rows = lines
+            .map(|line| {
+                let mut columns = line.split(',');
+                let x = columns.next().unwrap().parse().unwrap();
+                (x, columns.map(|v| v.parse().unwrap()).collect())
+            })
+            .collect();
+        (header, rows)
+    }
+
+    #[test]
+    fn csv_1d_round_trip_test() {
+        let aabb = AABB::new(matrix![-4, 12]);
+        let mut frames = Vec::new();
+        for f in 0..3 {
+            let mut domain = OwnedDomain::new(aabb);
+            domain.par_set_values(|c| (c[0] * (f + 1)) as f64 / 7.0, 4);
+            frames.push(domain);
+        }
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_csv_1d_test_{}.csv", std::process::id()));
+        write_csv_1d(&frames[0].as_slice_domain(), &path);
+        let (header, rows) = parse_csv(&path);
+        assert_eq!(header, "x,value");
+        assert_eq!(rows.len(), aabb.buffer_size());
+        for (row, x) in rows.iter().zip(-4..=12) {
+            assert_eq!(row.0, x);
+            assert_eq!(row.1, vec![frames[0].view(&vector![x])]);
+        }
+
+        write_csv_1d_frames(&frames, &path);
+        let (header, rows) = parse_csv(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(header, "x,frame_0,frame_1,frame_2");
+        assert_eq!(rows.len(), aabb.buffer_size());
+        for (row, x) in rows.iter().zip(-4..=12) {
+            assert_eq!(row.0, x);
+            let expected: Vec<f64> =
+                frames.iter().map(|f| f.view(&vector![x])).collect();
+            assert_eq!(row.1, expected);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::domain::*;
+use crate::sidecar::*;
+use crate::util::*;
+
+mod binary_frames;
+mod csv;
+
+pub use binary_frames::*;
+pub use csv::*;
+
+pub struct Image1D {
+    img_buffer: image::RgbImage,
+    averaging: Option<RowAverages>,
+}
+
+/// Running sums for each image row, used when
+/// several solve outputs map to the same row.
+struct RowAverages {
+    frames: u32,
+    sums: Vec<f64>,
+    counts: Vec<u32>,
+}
+
+impl Image1D {
+    pub fn new(bound: AABB<1>, lines: u32) -> Self {
+        debug_assert_eq!(bound.bounds[(0, 0)], 0);
+        let exclusive_bound = bound.exclusive_bounds();
+        Image1D {
+            img_buffer: image::RgbImage::new(exclusive_bound[0] as u32, lines),
+            averaging: None,
+        }
+    }
+
+    /// Create an image with `lines` rows for `frames` solve outputs.
+    /// In this mode `add_line` takes a frame index,
+    /// and frames mapping to the same row are averaged.
+    /// For example 10000 frames into 1000 lines averages
+    /// every 10 consecutive frames.
+    pub fn new_averaged(bound: AABB<1>, lines: u32, frames: u32) -> Self {
+        debug_assert!(frames > 0);
+        let mut result = Self::new(bound, lines);
+        let row_size = (result.img_buffer.width() * lines) as usize;
+        result.averaging = Some(RowAverages {
+            frames,
+            sums: vec![0.0; row_size],
+            counts: vec![0; lines as usize],
+        });
+        result
+    }
+
+    /// Write values to line `l`.
+    /// If the image was created with `new_averaged`, `l` is a frame index,
+    /// and the row is set to the running average of its frames.
+    pub fn add_line(&mut self, l: u32, v: &[f64]) {
+        debug_assert_eq!(v.len(), self.img_buffer.width() as usize);
+        let width = self.img_buffer.width();
+        let Some(averaging) = &mut self.averaging else {
+            self.put_line(l, v);
+            return;
+        };
+
+        debug_assert!(l < averaging.frames);
+        let lines = self.img_buffer.height() as u64;
+        let row = (l as u64 * lines / averaging.frames as u64) as u32;
+        averaging.counts[row as usize] += 1;
+        let count = averaging.counts[row as usize] as f64;
+        let start = (row * width) as usize;
+        let sums = &mut averaging.sums[start..start + v.len()];
+        for (s, x) in sums.iter_mut().zip(v) {
+            *s += x;
+        }
+        let average: Vec<f64> = sums.iter().map(|s| s / count).collect();
+        self.put_line(row, &average);
+    }
+
+    fn put_line(&mut self, l: u32, v: &[f64]) {
+        debug_assert!(l < self.img_buffer.height());
+        let gradient = colorous::TURBO;
+        for x in 0..self.img_buffer.width() {
+            let r = v[x as usize];
+            let c = gradient.eval_continuous(r);
+            self.img_buffer.put_pixel(x, l, image::Rgb(c.as_array()));
+        }
+    }
+
+    pub fn write<F: AsRef<std::path::Path>>(self, s: &F) {
+        self.img_buffer.save(s).expect("Couldn't save image");
+    }
+}
+
+/// A 2D domain as a heatmap,
+/// values are mapped through the same colormap as `Image1D`.
+/// Pixel `(x, y)` is the coordinate `min + (x, y)`.
+pub struct Image2D {
+    img_buffer: image::RgbImage,
+    bound: AABB<2>,
+}
+
+impl Image2D {
+    pub fn new(bound: AABB<2>) -> Self {
+        let exclusive_bounds = bound.exclusive_bounds();
+        Image2D {
+            img_buffer: image::RgbImage::new(
+                exclusive_bounds[0] as u32,
+                exclusive_bounds[1] as u32,
+            ),
+            bound,
+        }
+    }
+
+    pub fn from_domain<DomainType: DomainView<2>>(domain: &DomainType) -> Self {
+        let mut result = Self::new(*domain.aabb());
+        result.set_domain(domain);
+        result
+    }
+
+    /// Set every pixel from `domain`, which must match the image bound.
+    #[track_caller]
+    pub fn set_domain<DomainType: DomainView<2>>(
+        &mut self,
+        domain: &DomainType,
+    ) {
+        assert_eq!(
+            *domain.aabb(),
+            self.bound,
+            "ERROR: domain {} does not match image {}",
+            domain.aabb(),
+            self.bound
+        );
+        let gradient = colorous::TURBO;
+        let min = self.bound.min();
+        for (l, r) in domain.buffer().iter().enumerate() {
+            let coord = self.bound.linear_to_coord(l) - min;
+            let c = gradient.eval_continuous(*r);
+            self.img_buffer.put_pixel(
+                coord[0] as u32,
+                coord[1] as u32,
+                image::Rgb(c.as_array()),
+            );
+        }
+    }
+
+    /// Pixel width and height.
+    pub fn dimensions(&self) -> (u32, u32) {
+        self.img_buffer.dimensions()
+    }
+
+    pub fn write<F: AsRef<std::path::Path>>(&self, s: &F) {
+        println!("Writing png: {:?}", s.as_ref());
+        self.img_buffer.save(s).expect("Couldn't save image");
+    }
+
+    /// `write`, and the `sidecar` metadata next to it if given,
+    /// see `sidecar_path`.
+    pub fn write_with_sidecar<F: AsRef<std::path::Path>>(
+        &self,
+        s: &F,
+        sidecar: Option<&FrameMetadata<2>>,
+    ) {
+        self.write(s);
+        write_sidecar(sidecar, s, &self.bound);
+    }
+}
+
+pub fn image2d<P: AsRef<std::path::Path>, DomainType: DomainView<2>>(
+    domain: &DomainType,
+    s: &P,
+) {
+    Image2D::from_domain(domain).write(s);
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn averaged_lines_test() {
+        let bound = AABB::new(matrix![0, 3]);
+
+        // Two constant frames into one row
+        let mut img = Image1D::new_averaged(bound, 1, 2);
+        img.add_line(0, &[0.5; 4]);
+        img.add_line(1, &[0.5; 4]);
+        let averaging = img.averaging.as_ref().unwrap();
+        assert_eq!(averaging.counts, vec![2]);
+        for s in &averaging.sums {
+            assert_eq!(s / 2.0, 0.5);
+        }
+        let expected = colorous::TURBO.eval_continuous(0.5).as_array();
+        for x in 0..4 {
+            assert_eq!(img.img_buffer.get_pixel(x, 0).0, expected);
+        }
+
+        // Consecutive frames share rows
+        let mut img = Image1D::new_averaged(bound, 2, 4);
+        img.add_line(0, &[0.2; 4]);
+        img.add_line(1, &[0.6; 4]);
+        img.add_line(2, &[1.0; 4]);
+        let expected = colorous::TURBO.eval_continuous(0.4).as_array();
+        assert_eq!(img.img_buffer.get_pixel(0, 0).0, expected);
+        let expected = colorous::TURBO.eval_continuous(1.0).as_array();
+        assert_eq!(img.img_buffer.get_pixel(0, 1).0, expected);
+    }
+
+    #[test]
+    fn image_2d_test() {
+        let bound = AABB::new(matrix![-2, 5; 3, 5]);
+        let mut domain = OwnedDomain::new(bound);
+        domain.par_set_values(|c| if c[0] == 5 { 1.0 } else { 0.0 }, 4);
+        let img = Image2D::from_domain(&domain.as_slice_domain());
+        assert_eq!(img.dimensions(), (8, 3));
+        let expected = colorous::TURBO.eval_continuous(1.0).as_array();
+        assert_eq!(img.img_buffer.get_pixel(7, 2).0, expected);
+        let expected = colorous::TURBO.eval_continuous(0.0).as_array();
+        assert_eq!(img.img_buffer.get_pixel(0, 0).0, expected);
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_image_2d_test_{}.png", std::process::id()));
+        img.write(&path);
+        let read = image::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((read.width(), read.height()), (8, 3));
+    }
+}
@@ -4,6 +4,9 @@ use clap::Parser;
 use std::path::PathBuf;
 
 /// nhls 1D stencil executable
+///
+/// Unlike the 2D `Args` there is no `--config`,
+/// `SolverConfig` only describes 2D runs.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
@@ -41,6 +44,10 @@ pub struct Args {
     #[arg(short, long, default_value = "estimate")]
     pub plan_type: PlanType,
 
+    /// Retry failed FFTW3 planning with estimate instead of aborting.
+    #[arg(long)]
+    pub plan_fallback: bool,
+
     /// File to load and save FFTW3 wisdom.
     #[arg(long)]
     pub wisdom_file: Option<PathBuf>,
@@ -49,10 +56,18 @@ pub struct Args {
     #[arg(short, long)]
     pub rand_init: bool,
 
+    /// Seed for `rand_init`, see `init::rand_seeded`.
+    #[arg(long, default_value_t = crate::init::DEFAULT_SEED)]
+    pub seed: u64,
+
     /// Write out a dot file for the ap plan
     #[arg(long)]
     pub write_dot: bool,
 
+    /// Print node counts, transform sizes, and depth of the ap plan
+    #[arg(long)]
+    pub plan_stats: bool,
+
     /// Target ratio for fft solves
     #[arg(long, default_value = "0.5")]
     pub ratio: f64,
@@ -72,6 +87,9 @@ impl Args {
         println!("GIT DESCRIBE: {}", env!("GIT_DESCRIBE"));
         println!("GIT HASH: {}", env!("GIT_HASH"));
         let args = Args::parse();
+        if args.rand_init {
+            println!("SEED: {}", args.seed);
+        }
 
         let output_dir = args.output_dir.to_str().unwrap();
         let _ = std::fs::remove_dir_all(output_dir);
@@ -1,6 +1,11 @@
+use crate::bench::*;
+use crate::clock::SolveClock;
+#[cfg(feature = "serde")]
+use crate::config::*;
 use crate::fft_solver::PlanType;
+use crate::output_schedule::*;
 use crate::util::*;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use std::path::PathBuf;
 
 /// nhls 2D stencil executable
@@ -25,6 +30,10 @@ pub struct Args {
     #[arg(short, long, default_value = "16")]
     pub steps_per_image: usize,
 
+    /// Simulated time per step, used for output metadata.
+    #[arg(long, default_value = "1.0")]
+    pub dt: f64,
+
     /// Domain size, assume square
     #[arg(short, long, default_value = "1000")]
     pub domain_size: usize,
@@ -33,6 +42,10 @@ pub struct Args {
     #[arg(short, long)]
     pub write_images: bool,
 
+    /// Only write this many frames, log spaced in time.
+    #[arg(long)]
+    pub log_frames: Option<usize>,
+
     /// The number of threads to use.
     #[arg(short, long, default_value = "8")]
     pub threads: usize,
@@ -41,6 +54,10 @@ pub struct Args {
     #[arg(short, long, default_value = "estimate")]
     pub plan_type: PlanType,
 
+    /// Retry failed FFTW3 planning with estimate instead of aborting.
+    #[arg(long)]
+    pub plan_fallback: bool,
+
     /// File to load and save FFTW3 wisdom.
     #[arg(long)]
     pub wisdom_file: Option<PathBuf>,
@@ -49,10 +66,18 @@ pub struct Args {
     #[arg(short, long)]
     pub rand_init: bool,
 
+    /// Seed for `rand_init`, see `init::rand_seeded`.
+    #[arg(long, default_value_t = crate::init::DEFAULT_SEED)]
+    pub seed: u64,
+
     /// Write out a dot file for the ap plan
     #[arg(long)]
     pub write_dot: bool,
 
+    /// Print node counts, transform sizes, and depth of the ap plan
+    #[arg(long)]
+    pub plan_stats: bool,
+
     /// Target ratio for fft solves
     #[arg(long, default_value = "0.5")]
     pub ratio: f64,
@@ -64,6 +89,30 @@ pub struct Args {
     /// Generate solver only, do not solve
     #[arg(long)]
     pub gen_only: bool,
+
+    /// Write a json metadata sidecar next to each frame.
+    #[arg(long)]
+    pub sidecar: bool,
+
+    /// Load settings from a `SolverConfig` TOML file,
+    /// flags given on the command line take precedence.
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// The file loaded by `--config`, see `check_config`.
+    #[cfg(feature = "serde")]
+    #[arg(skip)]
+    pub loaded_config: Option<SolverConfig>,
+
+    /// Time the solve loop and print a `BenchReport`,
+    /// use without `--write-images` for clean timings.
+    #[arg(long)]
+    pub bench: bool,
+
+    /// Leave the first frames out of the `--bench` timing.
+    #[arg(long, default_value_t = 0)]
+    pub warmup: usize,
 }
 
 impl Args {
@@ -71,7 +120,10 @@ impl Args {
         println!("EXAMPLE: {}", name);
         println!("GIT DESCRIBE: {}", env!("GIT_DESCRIBE"));
         println!("GIT HASH: {}", env!("GIT_HASH"));
-        let args = Args::parse();
+        let args = Args::from_cli(std::env::args_os());
+        if args.rand_init {
+            println!("SEED: {}", args.seed);
+        }
 
         let output_dir = args.output_dir.to_str().unwrap();
         let _ = std::fs::remove_dir_all(output_dir);
@@ -94,6 +146,116 @@ impl Args {
         args
     }
 
+    /// Parse `itr`, then fill in settings from `--config`
+    /// unless they were given on the command line.
+    pub fn from_cli<I, T>(itr: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let matches = Args::command().get_matches_from(itr);
+        #[allow(unused_mut)]
+        let mut args =
+            Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        #[cfg(feature = "serde")]
+        if let Some(path) = args.config.clone() {
+            let config = SolverConfig::read(&path);
+            args.apply_config(&config, &matches);
+            args.loaded_config = Some(config);
+        }
+        args
+    }
+
+    /// Take each setting from `config` unless given on the command line.
+    #[cfg(feature = "serde")]
+    pub fn apply_config(
+        &mut self,
+        config: &SolverConfig,
+        matches: &clap::ArgMatches,
+    ) {
+        let from_config = |id: &str| {
+            matches.value_source(id)
+                != Some(clap::parser::ValueSource::CommandLine)
+        };
+        macro_rules! take {
+            ($field:ident) => {
+                if from_config(stringify!($field)) {
+                    self.$field = config.$field;
+                }
+            };
+        }
+        if from_config("domain_size") {
+            self.domain_size = config.domain_size();
+        }
+        take!(images);
+        take!(steps_per_image);
+        take!(dt);
+        take!(plan_type);
+        take!(plan_fallback);
+        take!(cutoff);
+        take!(ratio);
+        take!(chunk_size);
+        take!(threads);
+        take!(rand_init);
+        take!(seed);
+        take!(write_images);
+        take!(log_frames);
+        take!(sidecar);
+    }
+
+    /// Panic if `--config` asked for a different stencil or boundary
+    /// condition than the `stencil` and `bc` this executable runs.
+    /// Pass `None` for a boundary condition `BCSpec` can't describe,
+    /// such executables reject any config.
+    #[cfg(feature = "serde")]
+    pub fn check_config(&self, stencil: &str, bc: Option<BCSpec>) {
+        let Some(ref config) = self.loaded_config else {
+            return;
+        };
+        if config.stencil != stencil {
+            panic!(
+                "ERROR: config stencil {:?}, but this executable runs {:?}",
+                config.stencil, stencil
+            );
+        }
+        match bc {
+            Some(bc) if bc == config.bc => {}
+            Some(bc) => panic!(
+                "ERROR: config bc {:?}, but this executable runs {:?}",
+                config.bc, bc
+            ),
+            None => panic!(
+                "ERROR: config bc {:?}, but this executable's bc can't be configured",
+                config.bc
+            ),
+        }
+    }
+
+    /// The settings of this run, see `SolverConfig`.
+    #[cfg(feature = "serde")]
+    pub fn to_config(&self, stencil: &str, bc: BCSpec) -> SolverConfig {
+        let max = self.domain_size as i32 - 1;
+        SolverConfig {
+            grid_bounds: vec![[0, max], [0, max]],
+            images: self.images,
+            steps_per_image: self.steps_per_image,
+            dt: self.dt,
+            plan_type: self.plan_type,
+            plan_fallback: self.plan_fallback,
+            cutoff: self.cutoff,
+            ratio: self.ratio,
+            chunk_size: self.chunk_size,
+            threads: self.threads,
+            stencil: stencil.to_string(),
+            bc,
+            rand_init: self.rand_init,
+            seed: self.seed,
+            write_images: self.write_images,
+            log_frames: self.log_frames,
+            sidecar: self.sidecar,
+        }
+    }
+
     pub fn grid_bounds(&self) -> AABB<2> {
         let inclusive = self.domain_size as i32 - 1;
         AABB::new(matrix![0, inclusive; 0, inclusive])
@@ -105,9 +267,169 @@ impl Args {
         result
     }
 
+    pub fn output_schedule(&self) -> OutputSchedule {
+        match self.log_frames {
+            Some(n_frames) => OutputSchedule::LogSpaced { n_frames },
+            None => OutputSchedule::Linear,
+        }
+    }
+
+    /// Should the driver write frame `t`, the state after
+    /// `t * steps_per_image` steps.
+    pub fn write_frame(&self, t: usize) -> bool {
+        let total_steps = self.images.saturating_sub(1) * self.steps_per_image;
+        self.write_images
+            && self.output_schedule().write_step(
+                t * self.steps_per_image,
+                self.steps_per_image,
+                total_steps,
+            )
+    }
+
+    pub fn clock(&self) -> SolveClock {
+        SolveClock::new(self.dt)
+    }
+
+    /// Timer for the solve loop, skipping `--warmup` frames.
+    pub fn bench_timer(&self) -> BenchTimer {
+        if self.bench && self.warmup + 1 >= self.images {
+            eprintln!(
+                "WARNING: --warmup {} leaves no timed frames of {}",
+                self.warmup,
+                self.images.saturating_sub(1)
+            );
+        }
+        BenchTimer::new(self.warmup)
+    }
+
+    /// Report for the frames timed by `timer`,
+    /// `steps_per_image` steps each.
+    pub fn bench_report(&self, name: &str, timer: &BenchTimer) -> BenchReport {
+        BenchReport::new(
+            name,
+            self.grid_bounds().buffer_size(),
+            timer.timed_frames() * self.steps_per_image,
+            timer.solve_seconds(),
+        )
+    }
+
+    /// With `--bench`, print the report for the timed frames.
+    pub fn report_bench(&self, name: &str, timer: &BenchTimer) {
+        if self.bench {
+            self.bench_report(name, timer).print();
+        }
+    }
+
     pub fn save_wisdom(&self) {
         if let Some(ref wisdom_path) = self.wisdom_file {
             fftw::wisdom::export_wisdom_file_f64(&wisdom_path).unwrap();
         }
     }
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn config_override_test() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_config_test_{}.toml", std::process::id()));
+        let config = SolverConfig {
+            grid_bounds: vec![[0, 255], [0, 255]],
+            cutoff: 20,
+            chunk_size: 500,
+            seed: 77,
+            ..SolverConfig::default()
+        };
+        config.write(&path);
+
+        let path_str = path.to_str().unwrap();
+        let args = Args::from_cli([
+            "nhls", "-o", "out", "--config", path_str, "--cutoff", "30",
+        ]);
+        assert_eq!(args.domain_size, 256);
+        assert_eq!(args.chunk_size, 500);
+        assert_eq!(args.seed, 77);
+        assert_eq!(args.cutoff, 30);
+
+        // Everything but the override round trips
+        let round_trip = args.to_config("heat_2d", config.bc.clone());
+        assert_eq!(
+            SolverConfig {
+                cutoff: 20,
+                ..round_trip
+            },
+            config
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn check_config_test() {
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_check_config_{}.toml", std::process::id()));
+        SolverConfig::default().write(&path);
+        let path_str = path.to_str().unwrap();
+        let args = Args::from_cli(["nhls", "-o", "out", "--config", path_str]);
+        std::fs::remove_file(&path).unwrap();
+
+        let heat_bc = BCSpec::Constant { value: 1.0 };
+        args.check_config("heat_2d", Some(heat_bc.clone()));
+        let check = |stencil: &'static str, bc: Option<BCSpec>| {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                args.check_config(stencil, bc)
+            }))
+            .is_err()
+        };
+        assert!(check("gen_2d", Some(heat_bc)));
+        assert!(check("heat_2d", Some(BCSpec::Periodic)));
+        assert!(check("heat_2d", None));
+
+        // Without --config anything goes
+        let args = Args::from_cli(["nhls", "-o", "out"]);
+        args.check_config("gen_2d", None);
+    }
+
+    #[test]
+    fn warmup_arg_test() {
+        let args = Args::try_parse_from(["nhls", "-o", "out"]).unwrap();
+        assert_eq!(args.warmup, 0);
+
+        let args = Args::try_parse_from([
+            "nhls", "-o", "out", "--bench", "--warmup", "2", "--images", "6",
+        ])
+        .unwrap();
+        assert!(args.bench);
+        assert_eq!(args.warmup, 2);
+
+        // Warmup frames are not part of the reported steps
+        let mut timer = args.bench_timer();
+        for _ in 1..args.images {
+            timer.frame();
+        }
+        let report = args.bench_report("mock", &timer);
+        assert_eq!(report.steps, 3 * args.steps_per_image);
+        assert_eq!(report.cells, args.grid_bounds().buffer_size());
+    }
+
+    #[test]
+    fn seed_arg_test() {
+        let args = Args::try_parse_from(["nhls", "-o", "out"]).unwrap();
+        assert_eq!(args.seed, crate::init::DEFAULT_SEED);
+
+        let args = Args::try_parse_from([
+            "nhls",
+            "-o",
+            "out",
+            "--rand-init",
+            "--seed",
+            "12345",
+        ])
+        .unwrap();
+        assert!(args.rand_init);
+        assert_eq!(args.seed, 12345);
+    }
+}
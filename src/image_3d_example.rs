@@ -1,9 +1,15 @@
+use crate::bench::*;
+use crate::clock::SolveClock;
 use crate::fft_solver::PlanType;
+use crate::output_schedule::*;
 use crate::util::*;
 use clap::Parser;
 use std::path::PathBuf;
 
-/// nhls 2D stencil executable
+/// nhls 3D stencil executable
+///
+/// Unlike the 2D `Args` there is no `--config`,
+/// `SolverConfig` only describes 2D runs.
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
@@ -25,6 +31,10 @@ pub struct Args {
     #[arg(short, long, default_value = "16")]
     pub steps_per_image: usize,
 
+    /// Simulated time per step, used for output metadata.
+    #[arg(long, default_value = "1.0")]
+    pub dt: f64,
+
     /// Domain size, assume cube
     #[arg(short, long, default_value = "100")]
     pub domain_size: usize,
@@ -33,6 +43,10 @@ pub struct Args {
     #[arg(short, long)]
     pub write_images: bool,
 
+    /// Only write this many frames, log spaced in time.
+    #[arg(long)]
+    pub log_frames: Option<usize>,
+
     /// The number of threads to use.
     #[arg(short, long, default_value = "8")]
     pub threads: usize,
@@ -41,6 +55,10 @@ pub struct Args {
     #[arg(short, long, default_value = "estimate")]
     pub plan_type: PlanType,
 
+    /// Retry failed FFTW3 planning with estimate instead of aborting.
+    #[arg(long)]
+    pub plan_fallback: bool,
+
     /// File to load and save FFTW3 wisdom.
     #[arg(long)]
     pub wisdom_file: Option<PathBuf>,
@@ -49,10 +67,18 @@ pub struct Args {
     #[arg(short, long)]
     pub rand_init: bool,
 
+    /// Seed for `rand_init`, see `init::rand_seeded`.
+    #[arg(long, default_value_t = crate::init::DEFAULT_SEED)]
+    pub seed: u64,
+
     /// Write out a dot file for the ap plan
     #[arg(long)]
     pub write_dot: bool,
 
+    /// Print node counts, transform sizes, and depth of the ap plan
+    #[arg(long)]
+    pub plan_stats: bool,
+
     /// Target ratio for fft solves
     #[arg(long, default_value = "0.5")]
     pub ratio: f64,
@@ -64,6 +90,19 @@ pub struct Args {
     /// Generate solver only, do not solve
     #[arg(long)]
     pub gen_only: bool,
+
+    /// Write a json metadata sidecar next to each frame.
+    #[arg(long)]
+    pub sidecar: bool,
+
+    /// Time the solve loop and print a `BenchReport`,
+    /// use without `--write-images` for clean timings.
+    #[arg(long)]
+    pub bench: bool,
+
+    /// Leave the first frames out of the `--bench` timing.
+    #[arg(long, default_value_t = 0)]
+    pub warmup: usize,
 }
 
 impl Args {
@@ -72,6 +111,9 @@ impl Args {
         println!("GIT DESCRIBE: {}", env!("GIT_DESCRIBE"));
         println!("GIT HASH: {}", env!("GIT_HASH"));
         let args = Args::parse();
+        if args.rand_init {
+            println!("SEED: {}", args.seed);
+        }
 
         let output_dir = args.output_dir.to_str().unwrap();
         let _ = std::fs::remove_dir_all(output_dir);
@@ -104,6 +146,59 @@ impl Args {
         result
     }
 
+    pub fn output_schedule(&self) -> OutputSchedule {
+        match self.log_frames {
+            Some(n_frames) => OutputSchedule::LogSpaced { n_frames },
+            None => OutputSchedule::Linear,
+        }
+    }
+
+    /// Should the driver write frame `t`, the state after
+    /// `t * steps_per_image` steps.
+    pub fn write_frame(&self, t: usize) -> bool {
+        let total_steps = self.images.saturating_sub(1) * self.steps_per_image;
+        self.write_images
+            && self.output_schedule().write_step(
+                t * self.steps_per_image,
+                self.steps_per_image,
+                total_steps,
+            )
+    }
+
+    pub fn clock(&self) -> SolveClock {
+        SolveClock::new(self.dt)
+    }
+
+    /// Timer for the solve loop, skipping `--warmup` frames.
+    pub fn bench_timer(&self) -> BenchTimer {
+        if self.bench && self.warmup + 1 >= self.images {
+            eprintln!(
+                "WARNING: --warmup {} leaves no timed frames of {}",
+                self.warmup,
+                self.images.saturating_sub(1)
+            );
+        }
+        BenchTimer::new(self.warmup)
+    }
+
+    /// Report for the frames timed by `timer`,
+    /// `steps_per_image` steps each.
+    pub fn bench_report(&self, name: &str, timer: &BenchTimer) -> BenchReport {
+        BenchReport::new(
+            name,
+            self.grid_bounds().buffer_size(),
+            timer.timed_frames() * self.steps_per_image,
+            timer.solve_seconds(),
+        )
+    }
+
+    /// With `--bench`, print the report for the timed frames.
+    pub fn report_bench(&self, name: &str, timer: &BenchTimer) {
+        if self.bench {
+            self.bench_report(name, timer).print();
+        }
+    }
+
     pub fn save_wisdom(&self) {
         if let Some(ref wisdom_path) = self.wisdom_file {
             fftw::wisdom::export_wisdom_file_f64(&wisdom_path).unwrap();
@@ -5,6 +5,7 @@
 
 use crate::domain::*;
 use crate::util::*;
+use nalgebra::SVector;
 use rand::prelude::*;
 use rayon::prelude::*;
 
@@ -27,6 +28,41 @@ pub fn rand<
     );
 }
 
+/// Default for the examples' `--seed`.
+pub const DEFAULT_SEED: u64 = 0;
+
+/// SplitMix64 finalizer, a cheap well mixed hash of `x`.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Like `rand`, but reproducible.
+/// Each value depends only on `seed` and the coordinate,
+/// not on `chunk_size` or thread scheduling.
+pub fn rand_seeded<
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &mut DomainType,
+    max_val: i32,
+    seed: u64,
+    chunk_size: usize,
+) {
+    let aabb = *domain.aabb();
+    let seed_hash = splitmix64(seed);
+    domain.par_set_values(
+        |coord: Coord<GRID_DIMENSION>| {
+            let index = aabb.coord_to_linear(&coord) as u64;
+            let bits = splitmix64(seed_hash ^ index) as i32;
+            (bits % max_val) as f64
+        },
+        chunk_size,
+    );
+}
+
 /// Generate normal like distribution over bound with spike in the middle,
 /// all values are in [0, 1].
 pub fn normal_ic_1d<DomainType: DomainView<1>>(
@@ -84,3 +120,71 @@ pub fn normal_ic_3d<DomainType: DomainView<3>>(
     };
     domain.par_set_values(ic_gen, chunk_size);
 }
+
+/// Set values from a function of world fraction coordinates.
+/// Each coord is mapped to `(coord - min) / exclusive_bounds`,
+/// which is in [0, 1) for each dimension.
+/// Since refining by an integer factor maps coord `c` to `factor * c`,
+/// the same function gives matching values across resolutions,
+/// see `AABB::refine`.
+pub fn fractional<
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+    F: Fn(SVector<f64, GRID_DIMENSION>) -> f64 + Send + Sync + Copy,
+>(
+    domain: &mut DomainType,
+    f: F,
+    chunk_size: usize,
+) {
+    let min = domain.aabb().min();
+    let exclusive_bounds = domain.aabb().exclusive_bounds();
+    domain.par_set_values(
+        move |coord: Coord<GRID_DIMENSION>| {
+            let fraction = SVector::<f64, GRID_DIMENSION>::from_fn(|d, _| {
+                (coord[d] - min[d]) as f64 / exclusive_bounds[d] as f64
+            });
+            f(fraction)
+        },
+        chunk_size,
+    );
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn fractional_refine_test() {
+        let ic = |x: SVector<f64, 2>| (x[0] * 6.0).sin() + x[1] * x[1];
+        let coarse_aabb = AABB::new(matrix![0, 9; 5, 24]);
+        let fine_aabb = coarse_aabb.refine(2);
+
+        let mut coarse = OwnedDomain::new(coarse_aabb);
+        let mut fine = OwnedDomain::new(fine_aabb);
+        fractional(&mut coarse, ic, 7);
+        fractional(&mut fine, ic, 7);
+
+        for c in coarse_aabb.coord_iter() {
+            let f = coarse_aabb.min() + (c - coarse_aabb.min()) * 2;
+            assert_eq!(coarse.view(&c), fine.view(&f));
+        }
+    }
+
+    #[test]
+    fn rand_seeded_test() {
+        let aabb = AABB::new(matrix![0, 19; -3, 12]);
+        let mut a = OwnedDomain::new(aabb);
+        let mut b = OwnedDomain::new(aabb);
+        let mut c = OwnedDomain::new(aabb);
+        rand_seeded(&mut a, 1024, 5, 7);
+        rand_seeded(&mut b, 1024, 5, 64);
+        rand_seeded(&mut c, 1024, 6, 7);
+
+        // Independent of chunking, dependent on seed
+        assert_eq!(a.buffer(), b.buffer());
+        assert_ne!(a.buffer(), c.buffer());
+        for v in a.buffer() {
+            assert!(v.abs() < 1024.0);
+        }
+    }
+}
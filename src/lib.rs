@@ -4,18 +4,33 @@
 // don't like this warning for this codebase.
 #![allow(clippy::needless_range_loop)]
 
+pub mod bench;
+pub mod checkpoint;
+pub mod clock;
+#[cfg(feature = "serde")]
+pub mod config;
 pub mod domain;
 pub mod fft_solver;
+#[cfg(feature = "hdf5")]
+pub mod hdf5;
 pub mod image;
 pub mod image_1d_example;
 pub mod image_2d_example;
 pub mod image_3d_example;
 pub mod init;
 pub mod mem_fmt;
+pub mod npy;
+pub mod output_schedule;
 pub mod par_slice;
 pub mod par_stencil;
+pub mod probe;
+pub mod raw;
+pub mod selftest;
+pub mod sidecar;
 pub mod solver;
 pub mod standard_stencils;
 pub mod stencil;
 pub mod util;
 pub mod vtk;
+
+pub use selftest::{selftest, SelfTestError};
@@ -0,0 +1,169 @@
+//! NPY Output
+//!
+//! Domains written as NumPy `.npy` files (format version 1.0)
+//! of little-endian f64 values.
+//! `Layout::ColMajor` sets `fortran_order`, so NumPy reads
+//! either layout back with the same `(i, j, ...)` indexing.
+
+use crate::domain::*;
+use crate::raw::*;
+use crate::sidecar::*;
+use crate::util::*;
+use std::io::{Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+/// The header dictionary, padded with spaces and a newline
+/// so the data starts on a 64 byte boundary.
+fn header<const GRID_DIMENSION: usize>(
+    aabb: &AABB<GRID_DIMENSION>,
+    layout: Layout,
+) -> String {
+    let exclusive_bounds = aabb.exclusive_bounds();
+    let shape: Vec<String> =
+        exclusive_bounds.iter().map(|n| n.to_string()).collect();
+    let shape = if GRID_DIMENSION == 1 {
+        format!("({},)", shape[0])
+    } else {
+        format!("({})", shape.join(", "))
+    };
+    let fortran_order = if layout == Layout::ColMajor {
+        "True"
+    } else {
+        "False"
+    };
+    let mut result = format!(
+        "{{'descr': '<f8', 'fortran_order': {}, 'shape': {}, }}",
+        fortran_order, shape
+    );
+    // magic, header length, header, newline
+    let unpadded = MAGIC.len() + 2 + result.len() + 1;
+    let padding = (64 - unpadded % 64) % 64;
+    result.push_str(&" ".repeat(padding));
+    result.push('\n');
+    result
+}
+
+/// Write the values of `domain` to `path` as an NPY file.
+pub fn write_npy<
+    P: AsRef<Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    path: &P,
+    layout: Layout,
+) {
+    let header = header(domain.aabb(), layout);
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+    writer.write_all(MAGIC).unwrap();
+    writer
+        .write_all(&(header.len() as u16).to_le_bytes())
+        .unwrap();
+    writer.write_all(header.as_bytes()).unwrap();
+    write_values(domain, layout, &mut writer).unwrap();
+}
+
+/// `write_npy`, and the `sidecar` metadata next to it if given,
+/// see `sidecar_path`.
+pub fn write_npy_with_sidecar<
+    P: AsRef<Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    path: &P,
+    layout: Layout,
+    sidecar: Option<&FrameMetadata<GRID_DIMENSION>>,
+) {
+    write_npy(domain, path, layout);
+    write_sidecar(sidecar, path, domain.aabb());
+}
+
+/// Read an NPY file written by `write_npy` for a domain with bounds `aabb`.
+/// The layout is taken from the file's `fortran_order`.
+/// Only headers in the form `write_npy` produces are accepted.
+pub fn read_npy<P: AsRef<Path>, const GRID_DIMENSION: usize>(
+    aabb: AABB<GRID_DIMENSION>,
+    path: &P,
+) -> OwnedDomain<GRID_DIMENSION> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    assert!(
+        bytes.starts_with(MAGIC),
+        "ERROR: {:?} is not an NPY 1.0 file",
+        path.as_ref()
+    );
+    let header_len =
+        u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]])
+            as usize;
+    let data_start = MAGIC.len() + 2 + header_len;
+    let file_header = std::str::from_utf8(&bytes[MAGIC.len() + 2..data_start])
+        .unwrap_or_else(|_| {
+            panic!("ERROR: {:?} has an invalid NPY header", path.as_ref())
+        });
+    let layout = [Layout::RowMajor, Layout::ColMajor]
+        .into_iter()
+        .find(|layout| header(&aabb, *layout) == file_header)
+        .unwrap_or_else(|| {
+            panic!(
+                "ERROR: NPY header {:?} in {:?} does not match {}",
+                file_header.trim_end(),
+                path.as_ref(),
+                aabb
+            )
+        });
+    let mut result = OwnedDomain::new(aabb);
+    read_values(&bytes[data_start..], layout, &mut result);
+    result
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn header_test() {
+        let aabb = AABB::new(matrix![0, 2; 0, 3]);
+        let h = header(&aabb, Layout::ColMajor);
+        assert_eq!((MAGIC.len() + 2 + h.len()) % 64, 0);
+        assert!(h.starts_with(
+            "{'descr': '<f8', 'fortran_order': True, 'shape': (3, 4), }"
+        ));
+        assert!(h.ends_with('\n'));
+
+        let h = header(&AABB::new(matrix![0, 9]), Layout::RowMajor);
+        assert!(h.contains("'fortran_order': False"));
+        assert!(h.contains("'shape': (10,)"));
+    }
+
+    #[test]
+    fn npy_round_trip_test() {
+        let aabb = AABB::new(matrix![-1, 1; 2, 5]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| (c[0] * 10 + c[1]) as f64, 4);
+
+        for (layout, name) in
+            [(Layout::RowMajor, "row"), (Layout::ColMajor, "col")]
+        {
+            let mut path = std::env::temp_dir();
+            path.push(format!(
+                "nhls_npy_{}_test_{}.npy",
+                name,
+                std::process::id()
+            ));
+            write_npy(&domain, &path, layout);
+            let read = read_npy(aabb, &path);
+            for linear_index in 0..aabb.buffer_size() {
+                let c = aabb.linear_to_coord(linear_index);
+                assert_eq!(read.view(&c), (c[0] * 10 + c[1]) as f64);
+            }
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Output Cadence
+//!
+//! Example drivers solve `steps_per_image` steps between frames,
+//! an `OutputSchedule` chooses which of those frames are written.
+
+/// Which frames an example driver writes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutputSchedule {
+    /// Write every frame.
+    #[default]
+    Linear,
+
+    /// Write up to `n_frames` frames at geometrically increasing
+    /// step counts, 1, 2, 4, 8, ..., rounded to
+    /// the nearest multiple of `steps_per_image`.
+    /// Useful for relaxation problems where early dynamics are fast.
+    LogSpaced { n_frames: usize },
+}
+
+impl OutputSchedule {
+    /// The step counts at which frames are written,
+    /// strictly increasing and no larger than `total_steps`.
+    pub fn frame_steps(
+        &self,
+        steps_per_image: usize,
+        total_steps: usize,
+    ) -> Vec<usize> {
+        debug_assert!(steps_per_image > 0);
+        match self {
+            OutputSchedule::Linear => (steps_per_image..=total_steps)
+                .step_by(steps_per_image)
+                .collect(),
+            OutputSchedule::LogSpaced { n_frames } => {
+                let mut result: Vec<usize> = Vec::with_capacity(*n_frames);
+                let mut target: usize = 1;
+                while result.len() < *n_frames {
+                    // Nearest multiple of steps_per_image, at least one
+                    let frames =
+                        (target + steps_per_image / 2) / steps_per_image;
+                    let steps = frames.max(1) * steps_per_image;
+                    if steps > total_steps {
+                        break;
+                    }
+                    if result.last().is_none_or(|last| steps > *last) {
+                        result.push(steps);
+                    }
+                    target = match target.checked_mul(2) {
+                        Some(t) => t,
+                        None => break,
+                    };
+                }
+                result
+            }
+        }
+    }
+
+    /// Should we write the frame after `steps` steps?
+    pub fn write_step(
+        &self,
+        steps: usize,
+        steps_per_image: usize,
+        total_steps: usize,
+    ) -> bool {
+        match self {
+            OutputSchedule::Linear => true,
+            OutputSchedule::LogSpaced { .. } => self
+                .frame_steps(steps_per_image, total_steps)
+                .contains(&steps),
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn check_steps(steps: &[usize], steps_per_image: usize, total: usize) {
+        for w in steps.windows(2) {
+            assert!(w[0] < w[1]);
+        }
+        for s in steps {
+            assert!(*s <= total);
+            assert_eq!(s % steps_per_image, 0);
+        }
+    }
+
+    #[test]
+    fn log_spaced_test() {
+        {
+            let schedule = OutputSchedule::LogSpaced { n_frames: 5 };
+            let steps = schedule.frame_steps(1, 1000);
+            assert_eq!(steps, vec![1, 2, 4, 8, 16]);
+            check_steps(&steps, 1, 1000);
+        }
+
+        {
+            // Early targets round to the same frame, only keep one
+            let schedule = OutputSchedule::LogSpaced { n_frames: 6 };
+            let steps = schedule.frame_steps(16, 1600);
+            assert_eq!(steps, vec![16, 32, 64, 128, 256, 512]);
+            check_steps(&steps, 16, 1600);
+        }
+
+        {
+            // Budget runs out before n_frames
+            let schedule = OutputSchedule::LogSpaced { n_frames: 100 };
+            let steps = schedule.frame_steps(10, 990);
+            check_steps(&steps, 10, 990);
+            assert_eq!(*steps.last().unwrap(), 510);
+        }
+
+        {
+            let steps = OutputSchedule::Linear.frame_steps(10, 50);
+            assert_eq!(steps, vec![10, 20, 30, 40, 50]);
+            check_steps(&steps, 10, 50);
+        }
+    }
+}
@@ -45,6 +45,25 @@ pub fn multiply_by<NumType: NumTrait>(
         });
 }
 
+/// Implements a = a * b over slice elements,
+/// with the product computed in single precision.
+pub fn multiply_by_c32(
+    a_slice: &mut [c64],
+    b_slice: &[fftw::types::c32],
+    chunk_size: usize,
+) {
+    a_slice
+        .par_chunks_mut(chunk_size)
+        .zip(b_slice.par_chunks(chunk_size))
+        .for_each(|(a_chunk, b_chunk)| {
+            for (a, b) in a_chunk.iter_mut().zip(b_chunk.iter()) {
+                let a_32 = fftw::types::c32::new(a.re as f32, a.im as f32);
+                let r = a_32 * *b;
+                *a = c64::new(r.re as f64, r.im as f64);
+            }
+        });
+}
+
 /// Implements a = a / c over slice elements.
 pub fn div<NumType: NumTrait>(
     a_slice: &mut [NumType],
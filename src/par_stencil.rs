@@ -1,8 +1,26 @@
 use crate::domain::*;
+use crate::par_slice;
 use crate::stencil::*;
 use crate::util::*;
 use rayon::prelude::*;
 
+/// How `apply_with_mode` spreads chunks across threads.
+/// This is independent of `chunk_size`,
+/// which only controls work granularity.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ParMode {
+    /// Use the current rayon thread pool.
+    #[default]
+    Auto,
+
+    /// Use a dedicated rayon thread pool with this many threads.
+    /// Note that the pool is built for each call.
+    Threads(usize),
+
+    /// Process chunks on the calling thread, no rayon spawns.
+    Serial,
+}
+
 pub fn apply<
     BC,
     Operation,
@@ -19,6 +37,35 @@ pub fn apply<
 ) where
     Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
     BC: BCCheck<GRID_DIMENSION>,
+{
+    apply_with_mode(
+        bc,
+        stencil,
+        input,
+        output,
+        global_time,
+        chunk_size,
+        ParMode::Auto,
+    );
+}
+
+/// Like `apply`, for stencils whose operation depends on position.
+pub fn apply_variable<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    stencil: &VariableStencil<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &DomainType,
+    output: &mut DomainType,
+    global_time: usize,
+    chunk_size: usize,
+) where
+    Operation: VariableStencilOperation<GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
 {
     debug_assert!(input.aabb().contains_aabb(output.aabb()));
     output.par_modify_access(chunk_size).for_each(
@@ -28,27 +75,510 @@ pub fn apply<
                     Coord<GRID_DIMENSION>,
                     &mut f64,
                 )| {
-                    let args = gather_args(
-                        stencil,
+                    let args = gather_offset_args(
+                        stencil.offsets(),
                         bc,
                         input,
                         &world_coord,
                         global_time,
                     );
-                    let result = stencil.apply(&args);
-                    *value_mut = result;
+                    *value_mut = stencil.apply(&args, &world_coord);
+                },
+            )
+        },
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn apply_with_mode<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &DomainType,
+    output: &mut DomainType,
+    global_time: usize,
+    chunk_size: usize,
+    mode: ParMode,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert!(input.aabb().contains_aabb(output.aabb()));
+    // The origin lies inside the input, so only opted in checks can be skipped
+    if stencil.is_identity() && bc.interior_is_unconstrained() {
+        if input.aabb() == output.aabb() {
+            par_slice::copy(output.buffer_mut(), input.buffer(), chunk_size);
+        } else {
+            output.par_from_superset(input, chunk_size);
+        }
+        return;
+    }
+    let apply_chunk = |mut d: DomainChunk<'_, GRID_DIMENSION>| {
+        d.coord_iter_mut().for_each(
+            |(world_coord, value_mut): (Coord<GRID_DIMENSION>, &mut f64)| {
+                let args =
+                    gather_args(stencil, bc, input, &world_coord, global_time);
+                let result = stencil.apply(&args);
+                *value_mut = result;
+            },
+        )
+    };
+    match mode {
+        ParMode::Auto => {
+            output.par_modify_access(chunk_size).for_each(apply_chunk);
+        }
+        ParMode::Threads(n_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                output.par_modify_access(chunk_size).for_each(apply_chunk)
+            });
+        }
+        ParMode::Serial => {
+            output.modify_access(chunk_size).for_each(apply_chunk);
+        }
+    }
+}
+
+/// Apply `stencil` to interior cells and `edge_op` to cells
+/// whose neighborhood leaves the input domain.
+/// Rather than reading a boundary condition, `edge_op` receives the
+/// gathered arguments along with a mask of which neighbors lie inside
+/// the input domain. Arguments for outside neighbors are zero.
+/// This lets edge cells use one-sided stencils, for example to
+/// keep finite volume schemes conservative.
+pub fn apply_with_edge<
+    Operation,
+    EdgeOperation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    edge_op: &EdgeOperation,
+    input: &DomainType,
+    output: &mut DomainType,
+    chunk_size: usize,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    EdgeOperation:
+        Fn(&[f64; NEIGHBORHOOD_SIZE], &[bool; NEIGHBORHOOD_SIZE]) -> f64 + Sync,
+{
+    debug_assert!(input.aabb().contains_aabb(output.aabb()));
+    let input_aabb = *input.aabb();
+    output.par_modify_access(chunk_size).for_each(
+        |mut d: DomainChunk<'_, GRID_DIMENSION>| {
+            d.coord_iter_mut().for_each(
+                |(world_coord, value_mut): (
+                    Coord<GRID_DIMENSION>,
+                    &mut f64,
+                )| {
+                    let mut args = [0.0; NEIGHBORHOOD_SIZE];
+                    let mut inside = [true; NEIGHBORHOOD_SIZE];
+                    for (i, n_i) in stencil.offsets().iter().enumerate() {
+                        let n_world_coord = world_coord + n_i;
+                        if input_aabb.contains(&n_world_coord) {
+                            args[i] = input.view(&n_world_coord);
+                        } else {
+                            inside[i] = false;
+                        }
+                    }
+                    *value_mut = if inside.iter().all(|b| *b) {
+                        stencil.apply(&args)
+                    } else {
+                        edge_op(&args, &inside)
+                    };
                 },
             )
         },
     )
 }
 
+/// Row kernel, evaluate the stencil over a contiguous run of cells.
+/// `input_rows[n]` holds neighbor `n` for each cell of the run,
+/// i.e. `output_row[x] = stencil(input_rows[0][x], input_rows[1][x], ...)`.
+#[inline]
+pub fn apply_run<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input_rows: &[&[f64]; NEIGHBORHOOD_SIZE],
+    output_row: &mut [f64],
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    for row in input_rows {
+        debug_assert!(row.len() >= output_row.len());
+    }
+    for (x, value_mut) in output_row.iter_mut().enumerate() {
+        let args = std::array::from_fn(|n| input_rows[n][x]);
+        *value_mut = stencil.apply(&args);
+    }
+}
+
+/// Same result as `apply`, but works on whole rows of the last dimension.
+/// The part of each row whose neighborhood lies inside the input domain
+/// is handed to `apply_run`, only the ends use `gather_args`.
+/// This relies on `bc` returning `None` inside the input domain,
+/// so checks that don't opt in with `BCCheck::interior_is_unconstrained`
+/// fall back to `apply`.
+pub fn apply_rows<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &DomainType,
+    output: &mut DomainType,
+    global_time: usize,
+    chunk_size: usize,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert!(input.aabb().contains_aabb(output.aabb()));
+    if !bc.interior_is_unconstrained() {
+        apply(bc, stencil, input, output, global_time, chunk_size);
+        return;
+    }
+    let last = GRID_DIMENSION - 1;
+    let input_aabb = *input.aabb();
+    let output_aabb = *output.aabb();
+    let row_len = output_aabb.exclusive_bounds()[last] as usize;
+    let rows_per_chunk = (chunk_size / row_len).max(1);
+    let slopes = stencil.slopes();
+    let offsets = stencil.offsets();
+
+    // Cells in this range have all neighbors inside along the last dimension
+    let interior_min = input_aabb.bounds[(last, 0)] + slopes[(last, 0)];
+    let interior_max = input_aabb.bounds[(last, 1)] - slopes[(last, 1)];
+    let x_min = output_aabb.bounds[(last, 0)];
+    let x_max = output_aabb.bounds[(last, 1)];
+
+    output
+        .buffer_mut()
+        .par_chunks_mut(row_len * rows_per_chunk)
+        .enumerate()
+        .for_each(|(i, buffer_chunk): (usize, &mut [f64])| {
+            for (j, output_row) in buffer_chunk.chunks_mut(row_len).enumerate()
+            {
+                let row_index = i * rows_per_chunk + j;
+                let row_start =
+                    output_aabb.linear_to_coord(row_index * row_len);
+
+                // Do all neighbor rows exist in the input domain?
+                let rows_inside = offsets.iter().all(|o| {
+                    (0..last).all(|d| {
+                        let c = row_start[d] + o[d];
+                        input_aabb.bounds[(d, 0)] <= c
+                            && c <= input_aabb.bounds[(d, 1)]
+                    })
+                });
+                let (run_min, run_max) = if rows_inside {
+                    (x_min.max(interior_min), x_max.min(interior_max))
+                } else {
+                    (x_max + 1, x_max)
+                };
+
+                // Ends of the row, cell by cell
+                for (x, value_mut) in output_row.iter_mut().enumerate() {
+                    let mut world_coord = row_start;
+                    world_coord[last] = x_min + x as i32;
+                    if run_min <= world_coord[last]
+                        && world_coord[last] <= run_max
+                    {
+                        continue;
+                    }
+                    let args = gather_args(
+                        stencil,
+                        bc,
+                        input,
+                        &world_coord,
+                        global_time,
+                    );
+                    *value_mut = stencil.apply(&args);
+                }
+
+                // Interior run
+                if run_min <= run_max {
+                    let run_len = (run_max - run_min + 1) as usize;
+                    let input_buffer = input.buffer();
+                    let input_rows = std::array::from_fn(|n| {
+                        let mut c = row_start + offsets[n];
+                        c[last] = run_min + offsets[n][last];
+                        let start = input_aabb.coord_to_linear(&c);
+                        &input_buffer[start..start + run_len]
+                    });
+                    let run_start = (run_min - x_min) as usize;
+                    apply_run(
+                        stencil,
+                        &input_rows,
+                        &mut output_row[run_start..run_start + run_len],
+                    );
+                }
+            }
+        });
+}
+
+/// Whether every neighbor of every cell in `output_aabb`
+/// lies within `input_aabb`.
+/// Such applies don't need `BCCheck` when the check opts in with
+/// `BCCheck::interior_is_unconstrained` and its bound contains the input.
+pub fn is_interior<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input_aabb: &AABB<GRID_DIMENSION>,
+    output_aabb: &AABB<GRID_DIMENSION>,
+) -> bool
+where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    let mut growth = stencil.slopes();
+    let negative_slopes = -1 * growth.column(0);
+    growth.set_column(0, &negative_slopes);
+    input_aabb.contains_aabb(&output_aabb.add_bounds_diff(growth))
+}
+
+/// Like `apply_rows`, but without boundary conditions,
+/// every row is applied with the row kernel.
+/// Requires `is_interior` for the input and output domains,
+/// and a boundary condition that opts in,
+/// see `BCCheck::interior_is_unconstrained`.
+pub fn apply_interior<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &DomainType,
+    output: &mut DomainType,
+    chunk_size: usize,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    debug_assert!(is_interior(stencil, input.aabb(), output.aabb()));
+    let last = GRID_DIMENSION - 1;
+    let input_aabb = *input.aabb();
+    let output_aabb = *output.aabb();
+    let row_len = output_aabb.exclusive_bounds()[last] as usize;
+    let rows_per_chunk = (chunk_size / row_len).max(1);
+    let offsets = stencil.offsets();
+    let input_buffer = input.buffer();
+
+    output
+        .buffer_mut()
+        .par_chunks_mut(row_len * rows_per_chunk)
+        .enumerate()
+        .for_each(|(i, buffer_chunk): (usize, &mut [f64])| {
+            for (j, output_row) in buffer_chunk.chunks_mut(row_len).enumerate()
+            {
+                let row_index = i * rows_per_chunk + j;
+                let row_start =
+                    output_aabb.linear_to_coord(row_index * row_len);
+                let input_rows = std::array::from_fn(|n| {
+                    let start =
+                        input_aabb.coord_to_linear(&(row_start + offsets[n]));
+                    &input_buffer[start..start + row_len]
+                });
+                apply_run(stencil, &input_rows, output_row);
+            }
+        });
+}
+
+/// Apply `stencils[c]` to component `c` of every cell.
+/// Components are independent, each reads only its own
+/// component of the neighborhood.
+/// The boundary condition is shared by all components.
+pub fn apply_components<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    const COMPONENTS: usize,
+>(
+    bc: &BC,
+    stencils: &[StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>;
+         COMPONENTS],
+    input: &InterleavedDomain<GRID_DIMENSION, COMPONENTS>,
+    output: &mut InterleavedDomain<GRID_DIMENSION, COMPONENTS>,
+    global_time: usize,
+    chunk_size: usize,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert!(input.aabb().contains_aabb(output.aabb()));
+    let output_aabb = *output.aabb();
+    output
+        .buffer_mut()
+        .par_chunks_mut(chunk_size * COMPONENTS)
+        .enumerate()
+        .for_each(|(i, buffer_chunk): (usize, &mut [f64])| {
+            let offset = i * chunk_size;
+            for (j, cell) in buffer_chunk.chunks_mut(COMPONENTS).enumerate() {
+                let world_coord = output_aabb.linear_to_coord(offset + j);
+                for (c, stencil) in stencils.iter().enumerate() {
+                    let mut args = [0.0; NEIGHBORHOOD_SIZE];
+                    for (n, n_i) in stencil.offsets().iter().enumerate() {
+                        let n_world_coord = world_coord + n_i;
+                        args[n] = bc
+                            .check(&n_world_coord, global_time)
+                            .unwrap_or_else(|| input.view(&n_world_coord, c));
+                    }
+                    cell[c] = stencil.apply(&args);
+                }
+            }
+        });
+}
+
 #[cfg(test)]
 mod unit_test {
     use super::*;
     use float_cmp::assert_approx_eq;
     use nalgebra::matrix;
 
+    #[test]
+    fn apply_interior_test() {
+        let chunk_size = 50;
+        let stencil = Stencil::new(
+            [[0, 0], [-1, 0], [2, 0], [0, -2], [0, 1], [1, 1]],
+            |args: &[f64; 6]| {
+                0.5 * args[0] + 0.1 * args[1] + 0.1 * args[2] - 0.2 * args[3]
+                    + 0.3 * args[4]
+                    + 0.2 * args[5]
+            },
+        );
+        let input_bound = AABB::new(matrix![0, 29; 0, 39]);
+        let bc = ConstantCheck::new(-1.0, input_bound);
+        let mut input_domain = OwnedDomain::new(input_bound);
+        input_domain.par_set_values(
+            |c| ((c[0] * 7 + c[1] * 3) % 11) as f64,
+            chunk_size,
+        );
+
+        let output_bound = AABB::new(matrix![1, 27; 2, 37]);
+        assert!(is_interior(&stencil, &input_bound, &output_bound));
+        assert!(!is_interior(
+            &stencil,
+            &input_bound,
+            &AABB::new(matrix![1, 28; 2, 37])
+        ));
+        assert!(!is_interior(
+            &stencil,
+            &input_bound,
+            &AABB::new(matrix![1, 27; 1, 37])
+        ));
+
+        let mut checked = OwnedDomain::new(output_bound);
+        let mut interior = OwnedDomain::new(output_bound);
+        apply(&bc, &stencil, &input_domain, &mut checked, 0, chunk_size);
+        apply_interior(&stencil, &input_domain, &mut interior, chunk_size);
+        assert_eq!(interior.buffer(), checked.buffer());
+    }
+
+    #[test]
+    fn identity_test() {
+        let chunk_size = 7;
+        let identity: StencilF64<IdentityOperation, 2, 1> = Stencil::identity();
+        assert!(identity.is_identity());
+        assert_eq!(identity.extract_weights(), [1.0]);
+        assert!(!Stencil::new([[0, 0]], |args: &[f64; 1]| {
+            args[0] * args[0]
+        })
+        .is_identity());
+        assert!(
+            !Stencil::new([[1, 0]], |args: &[f64; 1]| args[0]).is_identity()
+        );
+
+        // Structural, returning the argument isn't enough
+        assert!(
+            !Stencil::new([[0, 0]], |args: &[f64; 1]| args[0]).is_identity()
+        );
+
+        let bound = AABB::new(matrix![0, 19; 0, 29]);
+        let mut input_domain = OwnedDomain::new(bound);
+        input_domain.par_set_values(|c| (c[0] * 100 + c[1]) as f64, chunk_size);
+        let bc = ConstantCheck::new(-1.0, bound);
+
+        // Same bounds, and a subdomain
+        let mut output_domain = OwnedDomain::new(bound);
+        apply(
+            &bc,
+            &identity,
+            &input_domain,
+            &mut output_domain,
+            0,
+            chunk_size,
+        );
+        assert_eq!(output_domain.buffer(), input_domain.buffer());
+        let sub_bound = AABB::new(matrix![3, 9; 5, 21]);
+        let mut sub_domain = OwnedDomain::new(sub_bound);
+        apply(
+            &bc,
+            &identity,
+            &input_domain,
+            &mut sub_domain,
+            0,
+            chunk_size,
+        );
+        for c in sub_bound.coord_iter() {
+            assert_eq!(sub_domain.view(&c), input_domain.view(&c));
+        }
+
+        // Checks with values inside the domain aren't skipped
+        let sponge = SpongeCheck::new(&input_domain, 2, 0.5, -1.0);
+        apply(
+            &sponge,
+            &identity,
+            &input_domain,
+            &mut output_domain,
+            0,
+            chunk_size,
+        );
+        for c in bound.coord_iter() {
+            assert_eq!(
+                output_domain.view(&c),
+                input_domain.view(&c) * sponge.factor(&c)
+            );
+        }
+
+        // FFT round trip is a no-op
+        let mut fft_input = OwnedDomain::new(bound);
+        fft_input
+            .buffer_mut()
+            .copy_from_slice(input_domain.buffer());
+        let mut fft_output = OwnedDomain::new(bound);
+        let mut solver = crate::fft_solver::PeriodicSolver::create(
+            &identity,
+            fft_output.buffer_mut(),
+            &bound,
+            5,
+            crate::fft_solver::PlanType::Estimate,
+            false,
+            chunk_size,
+        );
+        solver.apply(&mut fft_input, &mut fft_output);
+        for (a, b) in fft_output.buffer().iter().zip(input_domain.buffer()) {
+            assert_approx_eq!(f64, *a, *b, epsilon = 1e-9);
+        }
+    }
+
     #[test]
     fn par_stencil_test_1d_simple() {
         let stencil = Stencil::new([[0]], |args: &[f64; 1]| args[0]);
@@ -86,6 +616,71 @@ mod unit_test {
         }
     }
 
+    #[test]
+    fn apply_variable_test() {
+        let chunk_size = 7;
+        let bound = AABB::new(matrix![0, 99]);
+        let mut input_domain = OwnedDomain::new(bound);
+        input_domain
+            .par_set_values(|c| ((c[0] * c[0]) % 17) as f64, chunk_size);
+        let bc = ConstantCheck::new(2.0, bound);
+
+        // Diffusion coefficient ramps across the domain
+        let kappa = |c: &Coord<1>| 0.05 + 0.4 * c[0] as f64 / 99.0;
+        let stencil = VariableStencil::new(
+            [[-1], [0], [1]],
+            move |args: &[f64; 3], c: &Coord<1>| {
+                args[1] + kappa(c) * (args[0] - 2.0 * args[1] + args[2])
+            },
+        );
+        assert_eq!(stencil.slopes(), matrix![1, 1]);
+
+        let mut output_domain = OwnedDomain::new(bound);
+        apply_variable(
+            &bc,
+            &stencil,
+            &input_domain,
+            &mut output_domain,
+            0,
+            chunk_size,
+        );
+        for x in 0..100 {
+            let value = |i: i32| {
+                if bound.contains(&vector![i]) {
+                    input_domain.view(&vector![i])
+                } else {
+                    2.0
+                }
+            };
+            let k = kappa(&vector![x]);
+            let expected =
+                value(x) + k * (value(x - 1) - 2.0 * value(x) + value(x + 1));
+            assert_approx_eq!(f64, output_domain.view(&vector![x]), expected);
+        }
+
+        // A uniform coefficient matches the fixed stencil
+        let uniform = VariableStencil::new(
+            [[-1], [0], [1]],
+            |args: &[f64; 3], _: &Coord<1>| {
+                args[1] + 0.25 * (args[0] - 2.0 * args[1] + args[2])
+            },
+        );
+        let fixed = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let mut fixed_output = OwnedDomain::new(bound);
+        apply_variable(
+            &bc,
+            &uniform,
+            &input_domain,
+            &mut output_domain,
+            0,
+            chunk_size,
+        );
+        apply(&bc, &fixed, &input_domain, &mut fixed_output, 0, chunk_size);
+        for (a, b) in output_domain.buffer().iter().zip(fixed_output.buffer()) {
+            assert_approx_eq!(f64, *a, *b);
+        }
+    }
+
     // Throw an error if we hit boundary
     struct ErrorCheck {
         bounds: AABB<1>,
@@ -95,6 +690,10 @@ mod unit_test {
             assert!(self.bounds.contains(c));
             None
         }
+
+        fn interior_is_unconstrained(&self) -> bool {
+            true
+        }
     }
 
     #[test]
@@ -125,4 +724,284 @@ mod unit_test {
             assert_approx_eq!(f64, *i, 1.0);
         }
     }
+
+    #[test]
+    fn par_stencil_dyn_test() {
+        let chunk_size = 3;
+        let static_stencil =
+            Stencil::new([[-1], [0], [1]], |args: &[f64; 3]| {
+                (args[0] + args[1] + args[2]) / 3.0
+            });
+        let dynamic_stencil =
+            dyn_stencil([[-1], [0], [1]], |args: &[f64]| {
+                args.iter().sum::<f64>() / args.len() as f64
+            });
+
+        let bound = AABB::new(matrix![0, 20]);
+        let mut input_domain = OwnedDomain::new(bound);
+        let mut static_output = OwnedDomain::new(bound);
+        let mut dyn_output = OwnedDomain::new(bound);
+        input_domain
+            .par_set_values(|c: Coord<1>| (c[0] * c[0]) as f64, chunk_size);
+
+        let bc = ConstantCheck::new(1.0, bound);
+        apply(
+            &bc,
+            &static_stencil,
+            &input_domain,
+            &mut static_output,
+            0,
+            chunk_size,
+        );
+        apply(
+            &bc,
+            &dynamic_stencil,
+            &input_domain,
+            &mut dyn_output,
+            0,
+            chunk_size,
+        );
+        for (a, b) in static_output.buffer().iter().zip(dyn_output.buffer()) {
+            assert_approx_eq!(f64, *a, *b);
+        }
+    }
+
+    #[test]
+    fn par_stencil_edge_conservation_test() {
+        let chunk_size = 4;
+        let r = 0.2;
+        let stencil = Stencil::new([[-1], [0], [1]], |args: &[f64; 3]| {
+            args[1] + r * (args[0] - 2.0 * args[1] + args[2])
+        });
+        // No-flux edges, only exchange with neighbors that exist
+        let edge_op = |args: &[f64; 3], inside: &[bool; 3]| {
+            let mut result = args[1];
+            for i in [0, 2] {
+                if inside[i] {
+                    result += r * (args[i] - args[1]);
+                }
+            }
+            result
+        };
+
+        let bound = AABB::new(matrix![0, 30]);
+        let ic = |c: Coord<1>| if c[0] < 5 { 1.0 + c[0] as f64 } else { 0.0 };
+        let mut input_domain = OwnedDomain::new(bound);
+        let mut output_domain = OwnedDomain::new(bound);
+        input_domain.par_set_values(ic, chunk_size);
+        let initial_sum: f64 = input_domain.buffer().iter().sum();
+
+        for _ in 0..50 {
+            apply_with_edge(
+                &stencil,
+                &edge_op,
+                &input_domain,
+                &mut output_domain,
+                chunk_size,
+            );
+            std::mem::swap(&mut input_domain, &mut output_domain);
+        }
+        let edge_sum: f64 = input_domain.buffer().iter().sum();
+        assert_approx_eq!(f64, edge_sum, initial_sum, epsilon = 1e-10);
+
+        // Versus a constant zero BC, which loses mass at the edge
+        let bc = ConstantCheck::new(0.0, bound);
+        input_domain.par_set_values(ic, chunk_size);
+        for t in 0..50 {
+            apply(
+                &bc,
+                &stencil,
+                &input_domain,
+                &mut output_domain,
+                t,
+                chunk_size,
+            );
+            std::mem::swap(&mut input_domain, &mut output_domain);
+        }
+        let bc_sum: f64 = input_domain.buffer().iter().sum();
+        assert!(bc_sum < initial_sum - 1e-3);
+    }
+
+    #[test]
+    fn par_mode_test() {
+        let chunk_size = 8;
+        let stencil = Stencil::new([[-1], [0], [1]], |args: &[f64; 3]| {
+            0.25 * args[0] + 0.5 * args[1] + 0.25 * args[2]
+        });
+        let bound = AABB::new(matrix![0, 99]);
+        let mut input_domain = OwnedDomain::new(bound);
+        input_domain
+            .par_set_values(|c: Coord<1>| (c[0] * c[0]) as f64, chunk_size);
+        let bc = ConstantCheck::new(1.0, bound);
+
+        let mut auto_output = OwnedDomain::new(bound);
+        apply(
+            &bc,
+            &stencil,
+            &input_domain,
+            &mut auto_output,
+            0,
+            chunk_size,
+        );
+
+        // Serial mode runs every point on this thread
+        let serial_stencil =
+            Stencil::new([[-1], [0], [1]], |args: &[f64; 3]| {
+                assert!(rayon::current_thread_index().is_none());
+                0.25 * args[0] + 0.5 * args[1] + 0.25 * args[2]
+            });
+        let mut serial_output = OwnedDomain::new(bound);
+        apply_with_mode(
+            &bc,
+            &serial_stencil,
+            &input_domain,
+            &mut serial_output,
+            0,
+            chunk_size,
+            ParMode::Serial,
+        );
+
+        let mut threads_output = OwnedDomain::new(bound);
+        apply_with_mode(
+            &bc,
+            &stencil,
+            &input_domain,
+            &mut threads_output,
+            0,
+            chunk_size,
+            ParMode::Threads(2),
+        );
+
+        assert_eq!(auto_output.buffer(), serial_output.buffer());
+        assert_eq!(auto_output.buffer(), threads_output.buffer());
+    }
+
+    #[test]
+    fn apply_components_test() {
+        let bound = AABB::new(matrix![0, 59]);
+        let chunk_size = 7;
+        let steps = 20;
+        let bc = ConstantCheck::new(0.0, bound);
+        let stencils = [
+            crate::standard_stencils::heat_1d(1.0, 1.0, 0.4),
+            crate::standard_stencils::heat_1d(1.0, 1.0, 0.1),
+        ];
+        let ic = |c: Coord<1>| {
+            [
+                if c[0] == 20 { 1.0 } else { 0.0 },
+                if c[0] == 40 { 2.0 } else { 0.0 },
+            ]
+        };
+
+        let mut input = InterleavedDomain::<1, 2>::new(bound);
+        let mut output = InterleavedDomain::<1, 2>::new(bound);
+        input.par_set_values(ic, chunk_size);
+        for t in 0..steps {
+            apply_components(
+                &bc,
+                &stencils,
+                &input,
+                &mut output,
+                t,
+                chunk_size,
+            );
+            std::mem::swap(&mut input, &mut output);
+        }
+
+        // Each species matches a scalar solve at its own rate
+        for (c, stencil) in stencils.iter().enumerate() {
+            let mut scalar_input = OwnedDomain::new(bound);
+            let mut scalar_output = OwnedDomain::new(bound);
+            scalar_input.par_set_values(|coord| ic(coord)[c], chunk_size);
+            for t in 0..steps {
+                apply(
+                    &bc,
+                    stencil,
+                    &scalar_input,
+                    &mut scalar_output,
+                    t,
+                    chunk_size,
+                );
+                std::mem::swap(&mut scalar_input, &mut scalar_output);
+            }
+            let mut component = OwnedDomain::new(bound);
+            input.extract_component(c, &mut component);
+            assert_eq!(component.buffer(), scalar_input.buffer());
+        }
+
+        // No cross-talk, species 1 never reaches x = 0..10
+        for x in 0..10 {
+            assert_eq!(input.view(&vector![x], 1), 0.0);
+        }
+    }
+
+    #[test]
+    fn apply_rows_test() {
+        let bound = AABB::new(matrix![0, 30; -4, 17]);
+        let chunk_size = 50;
+        let bc = ConstantCheck::new(-0.5, bound);
+        let mut input = OwnedDomain::new(bound);
+        input.par_set_values(
+            |c| ((c[0] * 7 + c[1] * 3) % 11) as f64 * 0.1,
+            chunk_size,
+        );
+
+        let check = |output_aabb: AABB<2>| {
+            let stencil = Stencil::new(
+                [[0, 0], [-1, 0], [1, 0], [0, -2], [0, 1], [1, 1]],
+                |args: &[f64; 6]| {
+                    0.5 * args[0] + 0.1 * args[1] + 0.2 * args[2]
+                        - 0.3 * args[3]
+                        + 0.4 * args[4]
+                        + 0.05 * args[5]
+                },
+            );
+            let mut cell_output = OwnedDomain::new(output_aabb);
+            let mut row_output = OwnedDomain::new(output_aabb);
+            apply(&bc, &stencil, &input, &mut cell_output, 0, chunk_size);
+            apply_rows(&bc, &stencil, &input, &mut row_output, 0, chunk_size);
+            assert_eq!(cell_output.buffer(), row_output.buffer());
+
+            let heat =
+                crate::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.1);
+            apply(&bc, &heat, &input, &mut cell_output, 0, chunk_size);
+            apply_rows(&bc, &heat, &input, &mut row_output, 0, chunk_size);
+            assert_eq!(cell_output.buffer(), row_output.buffer());
+        };
+
+        // Whole domain, every row has boundary ends
+        check(bound);
+
+        // Shrunk like a frustrum step, and offset to one side
+        check(AABB::new(matrix![1, 29; -2, 16]));
+        check(AABB::new(matrix![0, 12; 10, 17]));
+    }
+
+    #[test]
+    fn apply_rows_in_domain_bc_test() {
+        let bound = AABB::new(matrix![0, 30; -4, 17]);
+        let chunk_size = 50;
+        let mut input = OwnedDomain::new(bound);
+        input.par_set_values(
+            |c| ((c[0] * 7 + c[1] * 3) % 11) as f64 * 0.1,
+            chunk_size,
+        );
+        let stencil =
+            crate::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.1);
+        let sponge = SpongeCheck::new(&input, 4, 0.25, -0.5);
+        assert!(!sponge.interior_is_unconstrained());
+
+        let mut cell_output = OwnedDomain::new(bound);
+        let mut row_output = OwnedDomain::new(bound);
+        apply(&sponge, &stencil, &input, &mut cell_output, 0, chunk_size);
+        apply_rows(&sponge, &stencil, &input, &mut row_output, 0, chunk_size);
+        assert_eq!(cell_output.buffer(), row_output.buffer());
+
+        // The sponge changes cells away from the faces too
+        let bc = ConstantCheck::new(-0.5, bound);
+        let mut constant_output = OwnedDomain::new(bound);
+        apply(&bc, &stencil, &input, &mut constant_output, 0, chunk_size);
+        let c = vector![3, 5];
+        assert_ne!(row_output.view(&c), constant_output.view(&c));
+    }
 }
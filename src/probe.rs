@@ -0,0 +1,114 @@
+//! Pointwise Probes
+//!
+//! Record the value at a single coordinate over time,
+//! much cheaper than full frame output for pointwise validation.
+
+use crate::domain::*;
+use crate::util::*;
+use std::io::Write;
+
+/// Value history at a single coordinate,
+/// update with `record` or `record_at` once per frame.
+#[derive(Clone, Debug)]
+pub struct Probe<const GRID_DIMENSION: usize> {
+    pub coord: Coord<GRID_DIMENSION>,
+    pub history: Vec<f64>,
+
+    /// Simulated time of each entry in `history`.
+    pub times: Vec<f64>,
+}
+
+impl<const GRID_DIMENSION: usize> Probe<GRID_DIMENSION> {
+    pub fn new(coord: Coord<GRID_DIMENSION>) -> Self {
+        Probe {
+            coord,
+            history: Vec::new(),
+            times: Vec::new(),
+        }
+    }
+
+    /// Push the current value at `coord` onto the history,
+    /// using the frame number as the time.
+    pub fn record<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        domain: &DomainType,
+    ) {
+        let frame = self.history.len() as f64;
+        self.record_at(domain, frame);
+    }
+
+    /// Push the current value at `coord` onto the history
+    /// with simulated `time`, i.e. `SolveClock::current_time`.
+    pub fn record_at<DomainType: DomainView<GRID_DIMENSION>>(
+        &mut self,
+        domain: &DomainType,
+        time: f64,
+    ) {
+        self.history.push(domain.view(&self.coord));
+        self.times.push(time);
+    }
+
+    /// Write history as csv with one `frame,time,value` row per record.
+    pub fn write_csv<P: AsRef<std::path::Path>>(&self, s: &P) {
+        println!("Writing csv: {:?}", s.as_ref());
+        let file = std::fs::File::create(s).unwrap();
+        let mut writer = std::io::BufWriter::new(file);
+        writeln!(writer, "frame,time,value").unwrap();
+        for (frame, (time, value)) in
+            self.times.iter().zip(self.history.iter()).enumerate()
+        {
+            writeln!(writer, "{},{},{}", frame, time, value).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn constant_probe_test() {
+        let aabb = AABB::new(matrix![0, 9; 0, 9]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|_| 3.5, 7);
+
+        let frames = 12;
+        let mut probe = Probe::new(vector![4, 7]);
+        for _ in 0..frames {
+            probe.record(&domain);
+        }
+        assert_eq!(probe.history.len(), frames);
+        for v in &probe.history {
+            assert_eq!(*v, 3.5);
+        }
+
+        let path = std::env::temp_dir().join("nhls_constant_probe_test.csv");
+        probe.write_csv(&path);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.lines().count(), frames + 1);
+        assert_eq!(csv.lines().nth(3).unwrap(), "2,2,3.5");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn probe_time_test() {
+        let aabb = AABB::new(matrix![0, 9]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| c[0] as f64, 7);
+
+        let mut clock = crate::clock::SolveClock::new(0.25);
+        let mut probe = Probe::new(vector![3]);
+        for _ in 0..4 {
+            clock.advance(2);
+            probe.record_at(&domain, clock.current_time());
+        }
+        assert_eq!(probe.times, vec![0.5, 1.0, 1.5, 2.0]);
+
+        let path = std::env::temp_dir().join("nhls_probe_time_test.csv");
+        probe.write_csv(&path);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(csv.lines().next().unwrap(), "frame,time,value");
+        assert_eq!(csv.lines().nth(2).unwrap(), "1,1,3");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+//! Raw Domain Dumps
+//!
+//! Domain buffers written as little-endian f64 values,
+//! in row-major or column-major order, without any header.
+//! Readers need the AABB and layout to make sense of the file.
+
+use crate::domain::*;
+use crate::sidecar::*;
+use crate::util::*;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// Order of values in written files.
+/// `RowMajor` is our linear order, with the last dimension fastest.
+/// `ColMajor` has the first dimension fastest, as Fortran and MATLAB expect.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    #[default]
+    RowMajor,
+    ColMajor,
+}
+
+impl Layout {
+    /// Our row-major linear index for the `index`th value in this layout.
+    pub fn to_row_major<const GRID_DIMENSION: usize>(
+        &self,
+        index: usize,
+        exclusive_bounds: &Coord<GRID_DIMENSION>,
+    ) -> usize {
+        match self {
+            Layout::RowMajor => index,
+            Layout::ColMajor => {
+                let mut coord = Coord::zero();
+                let mut index_accumulator = index;
+                for d in 0..GRID_DIMENSION {
+                    let n = exclusive_bounds[d] as usize;
+                    coord[d] = (index_accumulator % n) as i32;
+                    index_accumulator /= n;
+                }
+                indexing::coord_to_linear(&coord, exclusive_bounds)
+            }
+        }
+    }
+}
+
+/// Write the values of `domain` to `writer` in `layout` order,
+/// stopping at the first failed write.
+pub fn write_values<
+    W: Write,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    layout: Layout,
+    writer: &mut W,
+) -> std::io::Result<()> {
+    let exclusive_bounds = domain.aabb().exclusive_bounds();
+    let buffer = domain.buffer();
+    for i in 0..buffer.len() {
+        let value = buffer[layout.to_row_major(i, &exclusive_bounds)];
+        writer.write_all(&value.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Fill `domain` from little-endian f64 `bytes` in `layout` order.
+pub fn read_values<
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bytes: &[u8],
+    layout: Layout,
+    domain: &mut DomainType,
+) {
+    assert_eq!(
+        bytes.len(),
+        domain.aabb().buffer_size() * 8,
+        "ERROR: {} bytes do not match {}",
+        bytes.len(),
+        domain.aabb()
+    );
+    let exclusive_bounds = domain.aabb().exclusive_bounds();
+    let buffer = domain.buffer_mut();
+    for (i, chunk) in bytes.chunks(8).enumerate() {
+        buffer[layout.to_row_major(i, &exclusive_bounds)] =
+            f64::from_le_bytes(chunk.try_into().unwrap());
+    }
+}
+
+/// Write the values of `domain` to `path`.
+pub fn write_raw<
+    P: AsRef<Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    path: &P,
+    layout: Layout,
+) {
+    let mut writer =
+        std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+    write_values(domain, layout, &mut writer).unwrap();
+}
+
+/// `write_raw`, and the `sidecar` metadata next to it if given,
+/// see `sidecar_path`.
+pub fn write_raw_with_sidecar<
+    P: AsRef<Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    path: &P,
+    layout: Layout,
+    sidecar: Option<&FrameMetadata<GRID_DIMENSION>>,
+) {
+    write_raw(domain, path, layout);
+    write_sidecar(sidecar, path, domain.aabb());
+}
+
+/// Read a file written by `write_raw` for a domain with bounds `aabb`.
+pub fn read_raw<P: AsRef<Path>, const GRID_DIMENSION: usize>(
+    aabb: AABB<GRID_DIMENSION>,
+    path: &P,
+    layout: Layout,
+) -> OwnedDomain<GRID_DIMENSION> {
+    let mut bytes = Vec::new();
+    std::fs::File::open(path)
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .unwrap();
+    assert_eq!(
+        bytes.len(),
+        aabb.buffer_size() * 8,
+        "ERROR: raw file {:?} does not match {}",
+        path.as_ref(),
+        aabb
+    );
+    let mut result = OwnedDomain::new(aabb);
+    read_values(&bytes, layout, &mut result);
+    result
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn raw_round_trip_test() {
+        let aabb = AABB::new(matrix![-2, 5; 3, 7]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| (c[0] * 10 + c[1]) as f64 / 3.0, 4);
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_raw_test_{}.raw", std::process::id()));
+        write_raw(&domain, &path, Layout::RowMajor);
+        let read = read_raw(aabb, &path, Layout::RowMajor);
+        assert_eq!(read.buffer(), domain.buffer());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn col_major_test() {
+        let aabb = AABB::new(matrix![0, 2; 0, 3]);
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|c| (c[0] * 10 + c[1]) as f64, 4);
+
+        // First index fastest in the file
+        let mut bytes = Vec::new();
+        write_values(&domain, Layout::ColMajor, &mut bytes).unwrap();
+        let values: Vec<f64> = bytes
+            .chunks(8)
+            .map(|c| f64::from_le_bytes(c.try_into().unwrap()))
+            .collect();
+        for j in 0..4 {
+            for i in 0..3 {
+                assert_eq!(values[i + 3 * j], (i * 10 + j) as f64);
+            }
+        }
+
+        let mut path = std::env::temp_dir();
+        path.push(format!("nhls_raw_col_test_{}.raw", std::process::id()));
+        write_raw(&domain, &path, Layout::ColMajor);
+        let read = read_raw(aabb, &path, Layout::ColMajor);
+        assert_eq!(read.buffer(), domain.buffer());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,193 @@
+//! Startup Self Test
+//!
+//! A mismatched FFTW build or wisdom file can silently produce garbage.
+//! `selftest` runs a tiny transform and heat solve
+//! against hard-coded values, so users and CI can check
+//! the linkage before starting a long run.
+
+use crate::domain::*;
+use crate::fft_solver::*;
+use crate::solver::*;
+use crate::standard_stencils::*;
+use crate::util::*;
+
+/// Absolute tolerance for every comparison.
+pub const SELFTEST_TOLERANCE: f64 = 1e-12;
+
+/// The first mismatch found by `selftest`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SelfTestError {
+    /// Forward transform of `0, 1, ..., 7`.
+    FFTForward {
+        index: usize,
+        expected: c64,
+        actual: c64,
+    },
+
+    /// Normalized backward transform of the forward transform.
+    FFTRoundTrip {
+        index: usize,
+        expected: f64,
+        actual: f64,
+    },
+
+    /// One heat step of a delta, `solver` is either "direct" or "periodic".
+    HeatSolve {
+        solver: &'static str,
+        index: usize,
+        expected: f64,
+        actual: f64,
+    },
+}
+
+impl std::fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelfTestError::FFTForward {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "FFT forward mismatch at {}, expected {}, found {}",
+                index, expected, actual
+            ),
+            SelfTestError::FFTRoundTrip {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "FFT round trip mismatch at {}, expected {}, found {}",
+                index, expected, actual
+            ),
+            SelfTestError::HeatSolve {
+                solver,
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} heat solve mismatch at {}, expected {}, found {}",
+                solver, index, expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SelfTestError {}
+
+/// `FFT(0, 1, ..., 7)`, the non-redundant half.
+/// For k > 0, `X_k = -4 + 4i cot(pi k / 8)`.
+const FFT_EXPECTED: [(f64, f64); 5] = [
+    (28.0, 0.0),
+    (-4.0, 9.656_854_249_492_381),
+    (-4.0, 4.0),
+    (-4.0, 1.656_854_249_492_380_6),
+    (-4.0, 0.0),
+];
+
+/// One step of `heat_1d(1.0, 1.0, 0.25)` applied to a delta at 3.
+const HEAT_EXPECTED: [f64; 8] = [0.0, 0.0, 0.25, 0.5, 0.25, 0.0, 0.0, 0.0];
+
+/// Check FFTW and both solver paths against known values.
+/// Plans with `PlanType::Estimate`, so loaded wisdom is used
+/// but no measuring happens.
+pub fn selftest() -> Result<(), SelfTestError> {
+    fft_selftest()?;
+    heat_selftest()
+}
+
+fn fft_selftest() -> Result<(), SelfTestError> {
+    let plan = FFTPlan::new(&vector![8], PlanType::Estimate, false);
+    let mut real = AlignedVec::new(8);
+    let mut complex = AlignedVec::new(5);
+    for i in 0..8 {
+        real[i] = i as f64;
+    }
+    plan.forward(&mut real, &mut complex);
+    for (index, (re, im)) in FFT_EXPECTED.iter().enumerate() {
+        let expected = c64::new(*re, *im);
+        let actual = complex[index];
+        if (actual - expected).norm() > SELFTEST_TOLERANCE {
+            return Err(SelfTestError::FFTForward {
+                index,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    plan.backward(&mut complex, &mut real);
+    for index in 0..8 {
+        let expected = index as f64;
+        let actual = real[index] / 8.0;
+        if (actual - expected).abs() > SELFTEST_TOLERANCE {
+            return Err(SelfTestError::FFTRoundTrip {
+                index,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_heat<DomainType: DomainView<1>>(
+    solver: &'static str,
+    domain: &DomainType,
+) -> Result<(), SelfTestError> {
+    for (index, expected) in HEAT_EXPECTED.iter().enumerate() {
+        let actual = domain.buffer()[index];
+        if (actual - expected).abs() > SELFTEST_TOLERANCE {
+            return Err(SelfTestError::HeatSolve {
+                solver,
+                index,
+                expected: *expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn heat_selftest() -> Result<(), SelfTestError> {
+    let chunk_size = 8;
+    let aabb = AABB::new(matrix![0, 7]);
+    let stencil = heat_1d(1.0, 1.0, 0.25);
+    let delta = |c: Coord<1>| if c[0] == 3 { 1.0 } else { 0.0 };
+
+    // Direct, the delta never reaches the boundary
+    let bc = ConstantCheck::new(0.0, aabb);
+    let mut input = OwnedDomain::new(aabb);
+    let mut output = OwnedDomain::new(aabb);
+    input.par_set_values(delta, chunk_size);
+    box_apply(&bc, &stencil, &mut input, &mut output, 1, 0, chunk_size);
+    check_heat("direct", &output)?;
+
+    // Periodic, through the FFT convolution
+    let mut input = OwnedDomain::new(aabb);
+    let mut output = OwnedDomain::new(aabb);
+    let mut periodic_solver = PeriodicSolver::create(
+        &stencil,
+        output.buffer_mut(),
+        &aabb,
+        1,
+        PlanType::Estimate,
+        false,
+        chunk_size,
+    );
+    input.par_set_values(delta, chunk_size);
+    periodic_solver.apply(&mut input, &mut output);
+    check_heat("periodic", &output)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn selftest_test() {
+        assert_eq!(selftest(), Ok(()));
+    }
+}
@@ -0,0 +1,217 @@
+//! Frame Metadata Sidecars
+//!
+//! Small json files written next to output frames,
+//! so archived output records which step and setup produced it.
+
+use crate::util::*;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Describes a single output frame.
+#[derive(Clone, Debug)]
+pub struct FrameMetadata<const GRID_DIMENSION: usize> {
+    pub frame: usize,
+    pub step: usize,
+
+    /// Simulated time, `step * dt`.
+    pub time: f64,
+    pub bounds: AABB<GRID_DIMENSION>,
+
+    /// Name of the stencil used, i.e. `heat_2d`.
+    pub stencil: String,
+
+    /// See `APPlan::plan_hash`, if an AP solver was used.
+    pub plan_hash: Option<u64>,
+
+    /// See `init::rand_seeded`, if a random IC was used.
+    pub seed: Option<u64>,
+}
+
+impl<const GRID_DIMENSION: usize> FrameMetadata<GRID_DIMENSION> {
+    /// Metadata for frame `frame`,
+    /// the state after `frame * steps_per_image` steps.
+    pub fn new(
+        frame: usize,
+        steps_per_image: usize,
+        dt: f64,
+        bounds: AABB<GRID_DIMENSION>,
+        stencil: &str,
+    ) -> Self {
+        let step = frame * steps_per_image;
+        FrameMetadata {
+            frame,
+            step,
+            time: step as f64 * dt,
+            bounds,
+            stencil: stencil.to_string(),
+            plan_hash: None,
+            seed: None,
+        }
+    }
+
+    pub fn with_plan_hash(mut self, plan_hash: u64) -> Self {
+        self.plan_hash = Some(plan_hash);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        let bounds: Vec<String> = (0..GRID_DIMENSION)
+            .map(|d| {
+                format!(
+                    "[{}, {}]",
+                    self.bounds.bounds[(d, 0)],
+                    self.bounds.bounds[(d, 1)]
+                )
+            })
+            .collect();
+        let plan_hash = match self.plan_hash {
+            Some(h) => h.to_string(),
+            None => "null".to_string(),
+        };
+        let seed = match self.seed {
+            Some(s) => s.to_string(),
+            None => "null".to_string(),
+        };
+        let stencil = self.stencil.replace('\\', "\\\\").replace('"', "\\\"");
+        format!(
+            "{{\n  \"frame\": {},\n  \"step\": {},\n  \"time\": {:?},\n  \"bounds\": [{}],\n  \"stencil\": \"{}\",\n  \"plan_hash\": {},\n  \"seed\": {}\n}}\n",
+            self.frame,
+            self.step,
+            self.time,
+            bounds.join(", "),
+            stencil,
+            plan_hash,
+            seed
+        )
+    }
+
+    /// Write the sidecar for `frame_path`, see `sidecar_path`.
+    pub fn write<P: AsRef<Path>>(&self, frame_path: &P) {
+        let path = sidecar_path(frame_path);
+        let mut file = std::fs::File::create(path).unwrap();
+        file.write_all(self.to_json().as_bytes()).unwrap();
+    }
+}
+
+/// Used by the `_with_sidecar` writers,
+/// write `sidecar` next to `frame_path` if there is one.
+/// Panics if it doesn't describe a frame with `bounds`.
+pub fn write_sidecar<P: AsRef<Path>, const GRID_DIMENSION: usize>(
+    sidecar: Option<&FrameMetadata<GRID_DIMENSION>>,
+    frame_path: &P,
+    bounds: &AABB<GRID_DIMENSION>,
+) {
+    if let Some(metadata) = sidecar {
+        assert_eq!(
+            metadata.bounds,
+            *bounds,
+            "ERROR: sidecar for {:?} has bounds {}, but the frame has {}",
+            frame_path.as_ref(),
+            metadata.bounds,
+            bounds
+        );
+        metadata.write(frame_path);
+    }
+}
+
+/// The sidecar for `frame_0001.png` is `frame_0001.json`.
+pub fn sidecar_path<P: AsRef<Path>>(frame_path: &P) -> PathBuf {
+    frame_path.as_ref().with_extension("json")
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn sidecar_step_test() {
+        let steps_per_image = 16;
+        let bounds = AABB::new(matrix![0, 99; 0, 49]);
+        let mut dir = std::env::temp_dir();
+        dir.push("nhls_sidecar_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+
+        for k in [0, 1, 7] {
+            let mut frame_path = dir.clone();
+            frame_path.push(format!("frame_{:04}.png", k));
+            FrameMetadata::new(k, steps_per_image, 0.5, bounds, "heat_2d")
+                .with_plan_hash(42)
+                .with_seed(7)
+                .write(&frame_path);
+
+            let json =
+                std::fs::read_to_string(sidecar_path(&frame_path)).unwrap();
+            let step = k * steps_per_image;
+            assert!(json.contains(&format!("\"frame\": {},", k)));
+            assert!(json.contains(&format!("\"step\": {},", step)));
+            assert!(
+                json.contains(&format!("\"time\": {:?},", step as f64 * 0.5))
+            );
+            assert!(json.contains("\"bounds\": [[0, 99], [0, 49]],"));
+            assert!(json.contains("\"stencil\": \"heat_2d\","));
+            assert!(json.contains("\"plan_hash\": 42,"));
+            assert!(json.contains("\"seed\": 7"));
+        }
+    }
+
+    #[test]
+    fn writer_sidecar_test() {
+        use crate::domain::*;
+        use crate::npy::*;
+        use crate::raw::*;
+
+        let steps_per_image = 8;
+        let bounds = AABB::new(matrix![0, 9; 0, 4; 0, 2]);
+        let domain = OwnedDomain::new(bounds);
+        let mut dir = std::env::temp_dir();
+        dir.push("nhls_writer_sidecar_test");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir(&dir).unwrap();
+
+        let k = 3;
+        let metadata =
+            FrameMetadata::new(k, steps_per_image, 1.0, bounds, "heat_3d");
+        let frame_path = |extension: &str| {
+            let mut path = dir.clone();
+            path.push(format!("frame_{:04}.{}", k, extension));
+            path
+        };
+        let step = format!("\"step\": {},", k * steps_per_image);
+
+        let path = frame_path("raw");
+        write_raw_with_sidecar(&domain, &path, Layout::RowMajor, None);
+        assert!(!sidecar_path(&path).exists());
+        write_raw_with_sidecar(
+            &domain,
+            &path,
+            Layout::RowMajor,
+            Some(&metadata),
+        );
+        let json = std::fs::read_to_string(sidecar_path(&path)).unwrap();
+        assert!(json.contains(&step));
+        std::fs::remove_file(sidecar_path(&path)).unwrap();
+
+        let path = frame_path("npy");
+        write_npy_with_sidecar(
+            &domain,
+            &path,
+            Layout::RowMajor,
+            Some(&metadata),
+        );
+        let json = std::fs::read_to_string(sidecar_path(&path)).unwrap();
+        assert!(json.contains(&step));
+        std::fs::remove_file(sidecar_path(&path)).unwrap();
+
+        let path = frame_path("vtk");
+        crate::vtk::write_vtk3d_with_sidecar(&domain, &path, Some(&metadata));
+        let json = std::fs::read_to_string(sidecar_path(&path)).unwrap();
+        assert!(json.contains(&step));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,8 @@
 use crate::domain::*;
+use crate::par_slice;
 use crate::par_stencil;
 use crate::stencil::*;
+use rayon::prelude::*;
 
 pub fn box_apply<
     BC,
@@ -30,6 +32,200 @@ pub fn box_apply<
     par_stencil::apply(bc, stencil, input, output, global_time, chunk_size);
 }
 
+/// Like `box_apply`, for stencils whose operation depends on position.
+/// See `VariableStencil`.
+pub fn box_apply_variable<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    stencil: &VariableStencil<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &mut DomainType,
+    output: &mut DomainType,
+    steps: usize,
+    mut global_time: usize,
+    chunk_size: usize,
+) where
+    Operation: VariableStencilOperation<GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert_eq!(input.aabb(), output.aabb());
+    for _ in 0..steps - 1 {
+        global_time += 1;
+        par_stencil::apply_variable(
+            bc,
+            stencil,
+            input,
+            output,
+            global_time,
+            chunk_size,
+        );
+        std::mem::swap(input, output);
+    }
+    global_time += 1;
+    par_stencil::apply_variable(
+        bc,
+        stencil,
+        input,
+        output,
+        global_time,
+        chunk_size,
+    );
+}
+
+/// Norm used to rescale fields in the `_normalized` solvers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Norm {
+    /// Square root of the sum of squares.
+    L2,
+
+    /// Sum of values.
+    Mass,
+}
+
+impl Norm {
+    pub fn compute<const GRID_DIMENSION: usize, DomainType>(
+        &self,
+        domain: &DomainType,
+        chunk_size: usize,
+    ) -> f64
+    where
+        DomainType: DomainView<GRID_DIMENSION>,
+    {
+        let chunks = domain.buffer().par_chunks(chunk_size);
+        match self {
+            Norm::L2 => chunks
+                .map(|c| c.iter().map(|x| x * x).sum::<f64>())
+                .sum::<f64>()
+                .sqrt(),
+            Norm::Mass => chunks.map(|c| c.iter().sum::<f64>()).sum(),
+        }
+    }
+
+    /// Rescale `domain` to unit norm.
+    pub fn normalize<const GRID_DIMENSION: usize, DomainType>(
+        &self,
+        domain: &mut DomainType,
+        chunk_size: usize,
+    ) where
+        DomainType: DomainView<GRID_DIMENSION>,
+    {
+        let norm = self.compute(&*domain, chunk_size);
+        assert!(
+            norm != 0.0 && norm.is_finite(),
+            "ERROR: Can't normalize field with {:?} norm {}",
+            self,
+            norm
+        );
+        par_slice::div(domain.buffer_mut(), norm, chunk_size);
+    }
+}
+
+/// Like `box_apply`, but the output is rescaled to unit `norm`
+/// after each step.
+/// With a diffusion stencil this is imaginary time propagation,
+/// relaxing the field toward the lowest mode.
+#[allow(clippy::too_many_arguments)]
+pub fn box_apply_normalized<
+    BC,
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &mut DomainType,
+    output: &mut DomainType,
+    steps: usize,
+    mut global_time: usize,
+    chunk_size: usize,
+    norm: Norm,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert_eq!(input.aabb(), output.aabb());
+    for _ in 0..steps - 1 {
+        global_time += 1;
+        par_stencil::apply(bc, stencil, input, output, global_time, chunk_size);
+        norm.normalize(output, chunk_size);
+        std::mem::swap(input, output);
+    }
+    global_time += 1;
+    par_stencil::apply(bc, stencil, input, output, global_time, chunk_size);
+    norm.normalize(output, chunk_size);
+}
+
+/// Like `box_apply`, but alternates between two stencils by step parity.
+/// The step advancing `global_time` from `t` to `t + 1`
+/// uses `even_stencil` when `t % 2 == 0`, otherwise `odd_stencil`,
+/// so a solve split across calls keeps the same cycle.
+/// Any sizing based on slopes, i.e. input regions for a frustrum,
+/// should use the max of both stencils' slopes per side.
+#[allow(clippy::too_many_arguments)]
+pub fn box_apply_parity<
+    BC,
+    EvenOperation,
+    OddOperation,
+    const GRID_DIMENSION: usize,
+    const EVEN_NEIGHBORHOOD_SIZE: usize,
+    const ODD_NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    bc: &BC,
+    even_stencil: &StencilF64<
+        EvenOperation,
+        GRID_DIMENSION,
+        EVEN_NEIGHBORHOOD_SIZE,
+    >,
+    odd_stencil: &StencilF64<
+        OddOperation,
+        GRID_DIMENSION,
+        ODD_NEIGHBORHOOD_SIZE,
+    >,
+    input: &mut DomainType,
+    output: &mut DomainType,
+    steps: usize,
+    mut global_time: usize,
+    chunk_size: usize,
+) where
+    EvenOperation: StencilOperation<f64, EVEN_NEIGHBORHOOD_SIZE>,
+    OddOperation: StencilOperation<f64, ODD_NEIGHBORHOOD_SIZE>,
+    BC: BCCheck<GRID_DIMENSION>,
+{
+    debug_assert_eq!(input.aabb(), output.aabb());
+    for step in 0..steps {
+        if step > 0 {
+            std::mem::swap(input, output);
+        }
+        let even = global_time % 2 == 0;
+        global_time += 1;
+        if even {
+            par_stencil::apply(
+                bc,
+                even_stencil,
+                input,
+                output,
+                global_time,
+                chunk_size,
+            );
+        } else {
+            par_stencil::apply(
+                bc,
+                odd_stencil,
+                input,
+                output,
+                global_time,
+                chunk_size,
+            );
+        }
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -75,6 +271,89 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn box_apply_normalized_test() {
+        let chunk_size = 10;
+        let bound = AABB::new(matrix![0, 19]);
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let bc = ConstantCheck::new(0.0, bound);
+        let mut input_domain = OwnedDomain::new(bound);
+        let mut output_domain = OwnedDomain::new(bound);
+        input_domain.par_set_values(|c| (c[0] % 7) as f64 + 1.0, chunk_size);
+
+        box_apply_normalized(
+            &bc,
+            &stencil,
+            &mut input_domain,
+            &mut output_domain,
+            2000,
+            0,
+            chunk_size,
+            Norm::L2,
+        );
+        assert_approx_eq!(
+            f64,
+            Norm::L2.compute(&output_domain, chunk_size),
+            1.0,
+            epsilon = 1e-12
+        );
+
+        // Lowest Dirichlet mode, sin(pi (x + 1) / 21)
+        let pi = std::f64::consts::PI;
+        let mode_norm = (21.0f64 / 2.0).sqrt();
+        for x in 0..20 {
+            let expected = (pi * (x + 1) as f64 / 21.0).sin() / mode_norm;
+            assert_approx_eq!(
+                f64,
+                output_domain.view(&vector![x]),
+                expected,
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn box_apply_parity_test() {
+        let chunk_size = 10;
+        let bound = AABB::new(matrix![0, 49]);
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let bc = ConstantCheck::new(0.5, bound);
+        let ic = |c: Coord<1>| ((c[0] * 17) % 11) as f64;
+
+        let mut input_domain = OwnedDomain::new(bound);
+        let mut output_domain = OwnedDomain::new(bound);
+        input_domain.par_set_values(ic, chunk_size);
+        box_apply(
+            &bc,
+            &stencil,
+            &mut input_domain,
+            &mut output_domain,
+            7,
+            3,
+            chunk_size,
+        );
+
+        let mut parity_input = OwnedDomain::new(bound);
+        let mut parity_output = OwnedDomain::new(bound);
+        parity_input.par_set_values(ic, chunk_size);
+        box_apply_parity(
+            &bc,
+            &stencil,
+            &stencil,
+            &mut parity_input,
+            &mut parity_output,
+            7,
+            3,
+            chunk_size,
+        );
+
+        for (expected, actual) in
+            output_domain.buffer().iter().zip(parity_output.buffer())
+        {
+            assert_approx_eq!(f64, *actual, *expected);
+        }
+    }
+
     #[test]
     fn test_1d_simple() {
         let stencil = Stencil::new([[0]], |args: &[f64; 1]| args[0]);
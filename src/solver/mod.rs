@@ -1,5 +1,7 @@
 pub mod direct;
 pub mod periodic_direct;
+pub mod single;
 
 pub use direct::*;
 pub use periodic_direct::*;
+pub use single::*;
@@ -1,5 +1,6 @@
 use crate::domain::*;
 use crate::par_stencil;
+use crate::solver::*;
 use crate::stencil::*;
 
 /// Global time doesn't matter for periodic solves
@@ -40,6 +41,33 @@ pub fn direct_periodic_apply<
     par_stencil::apply(&bc, stencil, input, output, GLOBAL_TIME, chunk_size);
 }
 
+/// Like `direct_periodic_apply`, but the output is rescaled to unit `norm`
+/// after each step, see `box_apply_normalized`.
+pub fn direct_periodic_apply_normalized<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    stencil: &StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+    input: &mut DomainType,
+    output: &mut DomainType,
+    steps: usize,
+    chunk_size: usize,
+    norm: Norm,
+) where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    debug_assert_eq!(input.aabb(), output.aabb());
+    for _ in 0..steps - 1 {
+        direct_periodic_apply(stencil, input, output, 1, chunk_size);
+        norm.normalize(output, chunk_size);
+        std::mem::swap(input, output);
+    }
+    direct_periodic_apply(stencil, input, output, 1, chunk_size);
+    norm.normalize(output, chunk_size);
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -173,4 +201,32 @@ mod unit_tests {
             );
         }
     }
+
+    #[test]
+    fn normalized_test() {
+        let chunk_size = 8;
+        let bound = AABB::new(matrix![0, 31]);
+        let stencil = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+
+        // Noisy, positive IC
+        let mut input_domain = OwnedDomain::new(bound);
+        let mut output_domain = OwnedDomain::new(bound);
+        input_domain.par_set_values(
+            |coord: Coord<1>| ((coord[0] * 37 + 11) % 17) as f64 / 17.0 + 0.5,
+            chunk_size,
+        );
+
+        direct_periodic_apply_normalized(
+            &stencil,
+            &mut input_domain,
+            &mut output_domain,
+            3000,
+            chunk_size,
+            Norm::L2,
+        );
+        let expected = 1.0 / 32.0f64.sqrt();
+        for x in output_domain.buffer() {
+            assert_approx_eq!(f64, *x, expected, epsilon = 1e-10);
+        }
+    }
 }
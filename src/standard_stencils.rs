@@ -1,5 +1,12 @@
 use crate::stencil::*;
 
+/// Single neighbor at offset zero with factor 1.0,
+/// useful for checking solver plumbing independent of stencil math.
+pub fn identity_nd<const GRID_DIMENSION: usize>(
+) -> StencilF64<IdentityOperation, GRID_DIMENSION, 1> {
+    Stencil::identity()
+}
+
 pub fn heat_1d(
     dt: f64,
     dx: f64,
@@ -69,3 +76,29 @@ pub fn heat_3d(
         },
     )
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::util::*;
+    use float_cmp::assert_approx_eq;
+
+    #[test]
+    fn heat_2d_test() {
+        let (dt, dx, dy, k_x, k_y) = (0.5, 1.0, 2.0, 0.3, 0.8);
+        let stencil = heat_2d(dt, dx, dy, k_x, k_y);
+        assert_eq!(stencil.slopes(), matrix![1, 1; 1, 1]);
+
+        // g(k) = 1 - 4 r_x sin^2(k_x / 2) - 4 r_y sin^2(k_y / 2)
+        let r_x = k_x * dt / (dx * dx);
+        let r_y = k_y * dt / (dy * dy);
+        for k in [vector![0.0, 0.0], vector![0.4, -1.3], vector![3.0, 0.9]] {
+            let g = stencil.fourier_symbol(&k);
+            let s_x = (k[0] / 2.0).sin();
+            let s_y = (k[1] / 2.0).sin();
+            let expected = 1.0 - 4.0 * r_x * s_x * s_x - 4.0 * r_y * s_y * s_y;
+            assert_approx_eq!(f64, g.re, expected, epsilon = 1e-14);
+            assert_approx_eq!(f64, g.im, 0.0, epsilon = 1e-14);
+        }
+    }
+}
@@ -4,12 +4,31 @@ use crate::util::*;
 pub trait StencilOperation<NumType: NumTrait, const NEIGHBORHOOD_SIZE: usize> =
     Fn(&[NumType; NEIGHBORHOOD_SIZE]) -> NumType + Sync;
 
+/// Operations that also see the world coordinate being updated,
+/// see `VariableStencil`.
+pub trait VariableStencilOperation<
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+> = Fn(&[f64; NEIGHBORHOOD_SIZE], &Coord<GRID_DIMENSION>) -> f64 + Sync;
+
+/// Operations that see the neighborhood as a slice,
+/// so one closure can handle any neighborhood size.
+pub trait DynStencilOperation<NumType: NumTrait> =
+    Fn(&[NumType]) -> NumType + Sync;
+
 pub type StencilF64<
     Operation,
     const GRID_DIMENSION: usize,
     const NEIGHBORHOOD_SIZE: usize,
 > = Stencil<f64, Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>;
 
+/// Single precision stencils, see `box_apply_f32` and `PeriodicSolverF32`.
+pub type StencilF32<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+> = Stencil<f32, Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>;
+
 /// Stencils are the combination of an operation and neighbors
 pub struct Stencil<
     NumType: NumTrait,
@@ -22,6 +41,9 @@ pub struct Stencil<
     pub operation: Operation,
     pub offsets: [Coord<GRID_DIMENSION>; NEIGHBORHOOD_SIZE],
     pub num_type: std::marker::PhantomData<NumType>,
+
+    /// Only set by `Stencil::identity`.
+    identity: bool,
 }
 
 impl<
@@ -44,9 +66,16 @@ where
             }),
             operation,
             num_type: std::marker::PhantomData,
+            identity: false,
         }
     }
 
+    /// Whether this stencil was created by `Stencil::identity`,
+    /// operations that happen to return their argument don't count.
+    pub fn is_identity(&self) -> bool {
+        self.identity
+    }
+
     /// For linear stencils, we can extract the weight for a neighbor
     /// by passing in 1.0 for that neighbor and 0.0 for the others.
     pub fn extract_weights(&self) -> [NumType; NEIGHBORHOOD_SIZE] {
@@ -60,23 +89,41 @@ where
         weights
     }
 
+    /// For linear stencils, whether the origin offset has a non-zero weight.
+    /// Diffusion stencils without one are almost always a typo.
+    pub fn has_origin_term(&self) -> bool {
+        let weights = self.extract_weights();
+        let mut origin_weight = NumType::zero();
+        for (offset, weight) in self.offsets.iter().zip(weights) {
+            if *offset == Coord::zeros() {
+                origin_weight = origin_weight + weight;
+            }
+        }
+        !origin_weight.is_zero()
+    }
+
+    /// Print a warning if `has_origin_term` is false.
+    pub fn warn_missing_origin_term(&self) {
+        if !self.has_origin_term() {
+            eprintln!(
+                "WARNING: stencil has no origin term, check the stencil weights"
+            );
+        }
+    }
+
     pub fn offsets(&self) -> &[Coord<GRID_DIMENSION>; NEIGHBORHOOD_SIZE] {
         &self.offsets
     }
 
     pub fn slopes(&self) -> Bounds<GRID_DIMENSION> {
-        let mut result = Bounds::zero();
-        for neighbor in self.offsets {
-            for d in 0..GRID_DIMENSION {
-                let neighbor_d = neighbor[d];
-                if neighbor_d > 0 {
-                    result[(d, 1)] = result[(d, 1)].max(neighbor_d);
-                } else {
-                    result[(d, 0)] = result[(d, 0)].max(-neighbor_d);
-                }
-            }
-        }
-        result
+        offset_slopes(&self.offsets)
+    }
+
+    /// Furthest reach of the stencil in each dimension,
+    /// the halo thickness needed for one step.
+    pub fn radius(&self) -> Coord<GRID_DIMENSION> {
+        let slopes = self.slopes();
+        Coord::from_fn(|d, _| slopes[(d, 0)].max(slopes[(d, 1)]))
     }
 
     pub fn apply(&self, args: &[NumType; NEIGHBORHOOD_SIZE]) -> NumType {
@@ -84,6 +131,188 @@ where
     }
 }
 
+impl<
+        Operation,
+        const GRID_DIMENSION: usize,
+        const NEIGHBORHOOD_SIZE: usize,
+    > StencilF64<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>
+where
+    Operation: StencilOperation<f64, NEIGHBORHOOD_SIZE>,
+{
+    /// For linear stencils, the amplification factor for wavenumber `k`,
+    /// `g(k) = sum_j w_j * exp(i k . o_j)`.
+    /// A single step multiplies the mode `exp(i k . x)` by `g(k)`,
+    /// so `|g(k)| <= 1` for all `k` is the von Neumann stability condition.
+    pub fn fourier_symbol(
+        &self,
+        k: &nalgebra::SVector<f64, GRID_DIMENSION>,
+    ) -> c64 {
+        let weights = self.extract_weights();
+        let mut result = c64::new(0.0, 0.0);
+        for (offset, weight) in self.offsets.iter().zip(weights) {
+            let phase = k.dot(&offset.cast::<f64>());
+            result += c64::from_polar(weight, phase);
+        }
+        result
+    }
+}
+
+/// How far the offsets reach on each side of each dimension.
+fn offset_slopes<const GRID_DIMENSION: usize>(
+    offsets: &[Coord<GRID_DIMENSION>],
+) -> Bounds<GRID_DIMENSION> {
+    let mut result = Bounds::zero();
+    for neighbor in offsets {
+        for d in 0..GRID_DIMENSION {
+            let neighbor_d = neighbor[d];
+            if neighbor_d > 0 {
+                result[(d, 1)] = result[(d, 1)].max(neighbor_d);
+            } else {
+                result[(d, 0)] = result[(d, 0)].max(-neighbor_d);
+            }
+        }
+    }
+    result
+}
+
+/// Stencils whose operation also receives the world coordinate,
+/// so coefficients can vary in space, i.e. heterogeneous media.
+/// The FFT solvers rely on the same weights everywhere,
+/// so these only work with `par_stencil::apply_variable`
+/// and `box_apply_variable`.
+pub struct VariableStencil<
+    Operation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+> where
+    Operation: VariableStencilOperation<GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+{
+    pub operation: Operation,
+    pub offsets: [Coord<GRID_DIMENSION>; NEIGHBORHOOD_SIZE],
+}
+
+impl<
+        Operation,
+        const GRID_DIMENSION: usize,
+        const NEIGHBORHOOD_SIZE: usize,
+    > VariableStencil<Operation, GRID_DIMENSION, NEIGHBORHOOD_SIZE>
+where
+    Operation: VariableStencilOperation<GRID_DIMENSION, NEIGHBORHOOD_SIZE>,
+{
+    pub fn new(
+        offsets: [[i32; GRID_DIMENSION]; NEIGHBORHOOD_SIZE],
+        operation: Operation,
+    ) -> Self {
+        VariableStencil {
+            offsets: std::array::from_fn(|i| {
+                Coord::from_column_slice(&offsets[i])
+            }),
+            operation,
+        }
+    }
+
+    pub fn offsets(&self) -> &[Coord<GRID_DIMENSION>; NEIGHBORHOOD_SIZE] {
+        &self.offsets
+    }
+
+    pub fn slopes(&self) -> Bounds<GRID_DIMENSION> {
+        offset_slopes(&self.offsets)
+    }
+
+    pub fn apply(
+        &self,
+        args: &[f64; NEIGHBORHOOD_SIZE],
+        world_coord: &Coord<GRID_DIMENSION>,
+    ) -> f64 {
+        (self.operation)(args, world_coord)
+    }
+}
+
+/// Operation of `Stencil::identity`.
+pub type IdentityOperation = fn(&[f64; 1]) -> f64;
+
+impl<const GRID_DIMENSION: usize>
+    StencilF64<IdentityOperation, GRID_DIMENSION, 1>
+{
+    /// A single origin neighbor with weight one.
+    /// `par_stencil::apply` copies rather than gathers for these,
+    /// if the boundary condition opts in,
+    /// see `BCCheck::interior_is_unconstrained`.
+    pub fn identity() -> Self {
+        let operation: IdentityOperation = |args: &[f64; 1]| args[0];
+        let mut result = Stencil::new([[0; GRID_DIMENSION]], operation);
+        result.identity = true;
+        result
+    }
+}
+
+/// Create a stencil from a slice based operation.
+/// The operation is wrapped in a fixed size closure,
+/// so the solvers keep their const generic fast path.
+pub fn dyn_stencil<
+    NumType: NumTrait,
+    DynOperation,
+    const GRID_DIMENSION: usize,
+    const NEIGHBORHOOD_SIZE: usize,
+>(
+    offsets: [[i32; GRID_DIMENSION]; NEIGHBORHOOD_SIZE],
+    operation: DynOperation,
+) -> Stencil<
+    NumType,
+    impl StencilOperation<NumType, NEIGHBORHOOD_SIZE>,
+    GRID_DIMENSION,
+    NEIGHBORHOOD_SIZE,
+>
+where
+    DynOperation: DynStencilOperation<NumType>,
+{
+    Stencil::new(offsets, move |args: &[NumType; NEIGHBORHOOD_SIZE]| {
+        operation(args.as_slice())
+    })
+}
+
+/// Create a linear 2D stencil from a dense 3x3 weight grid.
+/// `weights[i][j]` is the weight for offset `[i - 1, j - 1]`,
+/// so the grid reads with the first dimension down the rows.
+/// Slopes are the half-width, 1 on every side.
+pub fn from_3x3(
+    weights: [[f64; 3]; 3],
+) -> StencilF64<impl StencilOperation<f64, 9>, 2, 9> {
+    let offsets =
+        std::array::from_fn(|n| [(n / 3) as i32 - 1, (n % 3) as i32 - 1]);
+    let flat: [f64; 9] = std::array::from_fn(|n| weights[n / 3][n % 3]);
+    Stencil::new(offsets, move |args: &[f64; 9]| {
+        let mut result = 0.0;
+        for n in 0..9 {
+            result += flat[n] * args[n];
+        }
+        result
+    })
+}
+
+/// Create a linear 3D stencil from a dense 3x3x3 weight grid.
+/// `weights[i][j][k]` is the weight for offset `[i - 1, j - 1, k - 1]`.
+pub fn from_3x3x3(
+    weights: [[[f64; 3]; 3]; 3],
+) -> StencilF64<impl StencilOperation<f64, 27>, 3, 27> {
+    let offsets = std::array::from_fn(|n| {
+        [
+            (n / 9) as i32 - 1,
+            ((n / 3) % 3) as i32 - 1,
+            (n % 3) as i32 - 1,
+        ]
+    });
+    let flat: [f64; 27] =
+        std::array::from_fn(|n| weights[n / 9][(n / 3) % 3][n % 3]);
+    Stencil::new(offsets, move |args: &[f64; 27]| {
+        let mut result = 0.0;
+        for n in 0..27 {
+            result += flat[n] * args[n];
+        }
+        result
+    })
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -132,4 +361,141 @@ mod unit_tests {
             assert_eq!(w, matrix![1, 1; 3, 2]);
         }
     }
+
+    fn average(args: &[f64]) -> f64 {
+        args.iter().sum::<f64>() / args.len() as f64
+    }
+
+    #[test]
+    fn radius_test() {
+        let heat = crate::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+        assert_eq!(heat.radius(), vector![1]);
+
+        let s = Stencil::new([[-6], [-3], [0], [1]], |args: &[f64; 4]| {
+            args[0] + args[1] + args[2] + args[3]
+        });
+        assert_eq!(s.radius(), vector![6]);
+
+        let s = Stencil::new([[0, 2], [-1, 0]], |args: &[f64; 2]| {
+            args[0] + args[1]
+        });
+        assert_eq!(s.radius(), vector![1, 2]);
+    }
+
+    #[test]
+    fn dyn_stencil_test() {
+        {
+            let d = dyn_stencil([[-1], [0], [1]], average);
+            let s = Stencil::new([[-1], [0], [1]], |args: &[f64; 3]| {
+                (args[0] + args[1] + args[2]) / 3.0
+            });
+            let args = [1.0, 5.0, -3.5];
+            assert_approx_eq!(f64, d.apply(&args), s.apply(&args));
+            assert_eq!(d.slopes(), s.slopes());
+        }
+
+        {
+            let d = dyn_stencil(
+                [[-1, 0], [0, 0], [1, 0], [0, 2], [0, -3]],
+                average,
+            );
+            let w = d.extract_weights();
+            for n in 0..5 {
+                assert_approx_eq!(f64, w[n], 0.2);
+            }
+        }
+    }
+
+    #[test]
+    fn has_origin_term_test() {
+        let heat = crate::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+        assert!(heat.has_origin_term());
+
+        let shift = Stencil::new([[-1], [1]], |args: &[f64; 2]| {
+            0.5 * args[0] + 0.5 * args[1]
+        });
+        assert!(!shift.has_origin_term());
+
+        // Origin present but with zero weight
+        let zero_origin = Stencil::new([[-1], [0]], |args: &[f64; 2]| args[0]);
+        assert!(!zero_origin.has_origin_term());
+    }
+
+    #[test]
+    fn from_3x3_test() {
+        let laplacian =
+            from_3x3([[0.0, 1.0, 0.0], [1.0, -4.0, 1.0], [0.0, 1.0, 0.0]]);
+        let expected_offsets = [
+            [-1, -1],
+            [-1, 0],
+            [-1, 1],
+            [0, -1],
+            [0, 0],
+            [0, 1],
+            [1, -1],
+            [1, 0],
+            [1, 1],
+        ];
+        for n in 0..9 {
+            assert_eq!(
+                laplacian.offsets()[n],
+                Coord::from_column_slice(&expected_offsets[n])
+            );
+        }
+        assert_eq!(laplacian.slopes(), matrix![1, 1; 1, 1]);
+
+        // f = x^2 + y^2 has laplacian 4 everywhere
+        let args: [f64; 9] = std::array::from_fn(|n| {
+            let o = laplacian.offsets()[n] + vector![3, -2];
+            (o[0] * o[0] + o[1] * o[1]) as f64
+        });
+        assert_approx_eq!(f64, laplacian.apply(&args), 4.0);
+    }
+
+    #[test]
+    fn from_3x3x3_test() {
+        let mut weights = [[[0.0; 3]; 3]; 3];
+        weights[0][1][2] = 2.0;
+        weights[1][1][1] = 0.5;
+        let s = from_3x3x3(weights);
+        assert_eq!(s.slopes(), matrix![1, 1; 1, 1; 1, 1]);
+        let w = s.extract_weights();
+        for n in 0..27 {
+            let expected = if s.offsets()[n] == vector![-1, 0, 1] {
+                2.0
+            } else if s.offsets()[n] == vector![0, 0, 0] {
+                0.5
+            } else {
+                0.0
+            };
+            assert_approx_eq!(f64, w[n], expected);
+        }
+    }
+
+    #[test]
+    fn fourier_symbol_test() {
+        // Mass conservation
+        let heat = crate::standard_stencils::heat_1d(1.0, 1.0, 0.25);
+        let g = heat.fourier_symbol(&vector![0.0]);
+        assert_approx_eq!(f64, g.re, 1.0);
+        assert_approx_eq!(f64, g.im, 0.0);
+
+        // g(k) = 1 - 4 r sin^2(k / 2)
+        for k in [0.3, 1.0, std::f64::consts::PI] {
+            let g = heat.fourier_symbol(&vector![k]);
+            let s = (k / 2.0).sin();
+            assert_approx_eq!(f64, g.re, 1.0 - s * s, epsilon = 1e-14);
+            assert_approx_eq!(f64, g.im, 0.0, epsilon = 1e-14);
+        }
+
+        // Symmetric stencils have real symbols
+        let heat = crate::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.1);
+        let g = heat.fourier_symbol(&vector![0.7, -2.1]);
+        assert_approx_eq!(f64, g.im, 0.0, epsilon = 1e-14);
+
+        // But shifts are not
+        let shift = Stencil::new([[1]], |args: &[f64; 1]| args[0]);
+        let g = shift.fourier_symbol(&vector![0.5]);
+        assert_approx_eq!(f64, g.im, 0.5f64.sin());
+    }
 }
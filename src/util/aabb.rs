@@ -13,6 +13,7 @@ pub type Bounds<const DIMENSION: usize> =
 /// This class is responsible for alot of indexing operations,
 /// where we map between a linear buffer and coordinates.
 #[derive(Hash, Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AABB<const DIMENSION: usize> {
     pub bounds: Bounds<DIMENSION>,
 }
@@ -100,13 +101,55 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         }
     }
 
+    /// Element wise add the bounds diff, checking for overflow.
+    pub fn checked_add_bounds_diff(
+        &self,
+        diff: Bounds<DIMENSION>,
+    ) -> Result<Self, GeometryOverflow> {
+        let mut bounds = self.bounds;
+        for (b, d) in bounds.iter_mut().zip(diff.iter()) {
+            *b = b.checked_add(*d).ok_or(GeometryOverflow)?;
+        }
+        Ok(Self::new(bounds))
+    }
+
     /// Element wise add the bounds diff.
+    /// Panics on overflow rather than wrapping in release builds.
+    #[track_caller]
     pub fn add_bounds_diff(&self, diff: Bounds<DIMENSION>) -> Self {
-        Self::new(self.bounds + diff)
+        self.checked_add_bounds_diff(diff).unwrap_or_else(|e| {
+            panic!("ERROR: {}, aabb: {}, diff: {:?}", e, self, diff)
+        })
+    }
+
+    /// Grow every face outward by `amount`, negative amounts erode.
+    #[track_caller]
+    pub fn pad(&self, amount: i32) -> Self {
+        self.pad_per_dim(&Coord::from_element(amount))
+    }
+
+    /// Grow the faces of each dimension outward by `amounts[d]`,
+    /// negative amounts erode.
+    #[track_caller]
+    pub fn pad_per_dim(&self, amounts: &Coord<DIMENSION>) -> Self {
+        let mut diff = Bounds::zero();
+        for d in 0..DIMENSION {
+            diff[(d, 0)] = -amounts[d];
+            diff[(d, 1)] = amounts[d];
+        }
+        let result = self.add_bounds_diff(diff);
+        debug_assert!(
+            result.check_validity(),
+            "ERROR: padding {} by {:?} is invalid",
+            self,
+            amounts.as_slice()
+        );
+        result
     }
 
     /// Find the coord within bound assuming periodic boundary conditions.
     /// Assumes that coords are no more than one box away!
+    /// See `periodic_coord_wrapping` for any distance.
     pub fn periodic_coord(&self, coord: &Coord<DIMENSION>) -> Coord<DIMENSION> {
         let mut result = Coord::zero();
         for d in 0..DIMENSION {
@@ -122,6 +165,20 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         result
     }
 
+    /// Like `periodic_coord`, but coords may be any number of boxes away.
+    pub fn periodic_coord_wrapping(
+        &self,
+        coord: &Coord<DIMENSION>,
+    ) -> Coord<DIMENSION> {
+        let mut result = Coord::zero();
+        for d in 0..DIMENSION {
+            let min = self.bounds[(d, 0)] as i64;
+            let len = self.bounds[(d, 1)] as i64 - min + 1;
+            result[d] = (min + (coord[d] as i64 - min).rem_euclid(len)) as i32;
+        }
+        result
+    }
+
     // TODO: can we return a view instead of allocating?
     /// Return min corner.
     pub fn min(&self) -> Coord<DIMENSION> {
@@ -134,6 +191,17 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         self.bounds.column(1).into()
     }
 
+    /// Integer midpoint of the min and max corners.
+    /// Even extents have two middle coords, this rounds down to the lower,
+    /// i.e. `[0, 9]` has center `4`.
+    pub fn center(&self) -> Coord<DIMENSION> {
+        Coord::from_fn(|d, _| {
+            let min = self.bounds[(d, 0)] as i64;
+            let max = self.bounds[(d, 1)] as i64;
+            (min + max).div_euclid(2) as i32
+        })
+    }
+
     /// Check that max >= min
     pub fn check_validity(&self) -> bool {
         for d in 0..DIMENSION {
@@ -154,8 +222,48 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         (0..self.buffer_size()).map(|i| self.linear_to_coord(i))
     }
 
+    /// Iterator over the `2^DIMENSION` corners,
+    /// bit `d` of the corner index picks max over min in dimension `d`.
+    /// Corners repeat along axes with extent one.
+    pub fn corners(
+        &self,
+    ) -> impl Iterator<Item = Coord<DIMENSION>> + use<DIMENSION> {
+        let bounds = self.bounds;
+        (0..1usize << DIMENSION).map(move |corner| {
+            Coord::from_fn(|d, _| bounds[(d, (corner >> d) & 1)])
+        })
+    }
+
+    /// Iterator over the r2c complex buffer for a real domain of our size,
+    /// pairing each linear index with its wavevector,
+    /// in cycles per domain length.
+    /// The last axis only stores frequencies `0..=n/2`,
+    /// all non-negative, the other axes are signed,
+    /// see `indexing::signed_frequency`.
+    pub fn fft_freq_iter(
+        &self,
+    ) -> impl Iterator<Item = (usize, nalgebra::SVector<f64, DIMENSION>)>
+           + use<DIMENSION> {
+        let size = self.exclusive_bounds();
+        let mut complex_size = size;
+        complex_size[DIMENSION - 1] = size[DIMENSION - 1] / 2 + 1;
+        (0..complex_buffer_size(&size)).map(move |i| {
+            let index = linear_to_coord(i, &complex_size);
+            let wavevector = nalgebra::SVector::from_fn(|d, _| {
+                if d == DIMENSION - 1 {
+                    index[d] as f64
+                } else {
+                    signed_frequency(index[d], size[d]) as f64
+                }
+            });
+            (i, wavevector)
+        })
+    }
+
     /// Given a bounding box within self,
     /// return decomposition of remaining coordinate space.
+    /// If center reaches our bound on a side, as on degenerate axes,
+    /// that part is empty and fails `check_validity`.
     /// Used for recursion during aperiodic algorithm.
     /// Until generic_const_exprs is stabilized,
     /// we need to return a nested array.
@@ -168,11 +276,9 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         for d in 0..DIMENSION {
             result[d][0] = remaining_bounds;
             result[d][0].bounds[(d, 1)] = center.bounds[(d, 0)] - 1;
-            debug_assert!(result[d][0].check_validity());
 
             result[d][1] = remaining_bounds;
             result[d][1].bounds[(d, 0)] = center.bounds[(d, 1)] + 1;
-            debug_assert!(result[d][1].check_validity());
 
             remaining_bounds.bounds[(d, 0)] = center.bounds[(d, 0)];
             remaining_bounds.bounds[(d, 1)] = center.bounds[(d, 1)];
@@ -186,6 +292,19 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
         self.exclusive_bounds().min()
     }
 
+    /// Like `min_size_len`, but only over axes the slopes reach along.
+    /// Degenerate axes, i.e. extent 1 in a 2D problem padded to 3D,
+    /// have zero slopes and so never limit a periodic solve.
+    /// Returns `i32::MAX` if no axis is sloped.
+    pub fn min_sloped_size_len(&self, slopes: &Bounds<DIMENSION>) -> i32 {
+        let exclusive_bounds = self.exclusive_bounds();
+        (0..DIMENSION)
+            .filter(|d| slopes[(*d, 0)] + slopes[(*d, 1)] > 0)
+            .map(|d| exclusive_bounds[d])
+            .min()
+            .unwrap_or(i32::MAX)
+    }
+
     /// Using stencil slopes, find the number of steps and resulting AABB
     /// that approximatley shrinks the original bound by some ratio.
     /// This one is hard to explain, maybe a sign it needs to be
@@ -198,7 +317,21 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
     ) -> (usize, AABB<DIMENSION>) {
         debug_assert!(ratio < 1.0);
         let inclusive_sides = self.bounds.column(1) - self.bounds.column(0);
-        let (min_side_d, min_side_len) = inclusive_sides.argmin();
+
+        // Axes with zero slope don't shrink, so they don't limit steps
+        let mut min_side = None;
+        for d in 0..DIMENSION {
+            if slopes[(d, 0)] + slopes[(d, 1)] == 0 {
+                continue;
+            }
+            match min_side {
+                Some((_, len)) if len <= inclusive_sides[d] => {}
+                _ => min_side = Some((d, inclusive_sides[d])),
+            }
+        }
+        let Some((min_side_d, min_side_len)) = min_side else {
+            return (0, *self);
+        };
         let scaled_side_len = ((min_side_len as f64) * ratio) as i32;
         let diff = min_side_len - scaled_side_len;
         let min_side_slope = slopes[(min_side_d, 0)] + slopes[(min_side_d, 1)];
@@ -225,6 +358,113 @@ impl<const DIMENSION: usize> AABB<DIMENSION> {
             .set_column(1, &cell_bounds.bounds.column(1).add_scalar(-1));
         cell_bounds
     }
+
+    /// Split into a regular grid of `counts[0] x counts[1] x ...` boxes.
+    /// Each dimension is divided as evenly as possible,
+    /// with remainders going to the first blocks.
+    /// Boxes are returned in row-major order, last dimension fastest.
+    pub fn partition(&self, counts: &[usize; DIMENSION]) -> Vec<Self> {
+        let exclusive_bounds = self.exclusive_bounds();
+        let mut block_counts = Coord::zero();
+        for d in 0..DIMENSION {
+            assert!(
+                counts[d] > 0 && counts[d] <= exclusive_bounds[d] as usize,
+                "ERROR: Can't partition {} into {} blocks on dimension {}",
+                self,
+                counts[d],
+                d
+            );
+            block_counts[d] = counts[d] as i32;
+        }
+
+        let n_blocks = real_buffer_size(&block_counts);
+        let mut result = Vec::with_capacity(n_blocks);
+        for block_index in 0..n_blocks {
+            let block_coord = linear_to_coord(block_index, &block_counts);
+            let mut bounds = self.bounds;
+            for d in 0..DIMENSION {
+                let i = block_coord[d];
+                let base = exclusive_bounds[d] / block_counts[d];
+                let remainder = exclusive_bounds[d] % block_counts[d];
+                let start = self.bounds[(d, 0)] + i * base + i.min(remainder);
+                let len = base + if i < remainder { 1 } else { 0 };
+                bounds[(d, 0)] = start;
+                bounds[(d, 1)] = start + len - 1;
+            }
+            result.push(AABB::new(bounds));
+        }
+        result
+    }
+
+    /// Returns the union if the two boxes are face adjacent
+    /// and identical in all other dimensions, so the union is a box.
+    /// Overlapping or diagonal boxes return None.
+    pub fn try_merge(&self, other: &Self) -> Option<Self> {
+        let mut merge_dimension = None;
+        for d in 0..DIMENSION {
+            if self.bounds[(d, 0)] == other.bounds[(d, 0)]
+                && self.bounds[(d, 1)] == other.bounds[(d, 1)]
+            {
+                continue;
+            }
+            if merge_dimension.is_some() {
+                return None;
+            }
+            merge_dimension = Some(d);
+        }
+
+        // Identical boxes are not face adjacent
+        let d = merge_dimension?;
+        let mut bounds = self.bounds;
+        if self.bounds[(d, 1)].checked_add(1) == Some(other.bounds[(d, 0)]) {
+            bounds[(d, 1)] = other.bounds[(d, 1)];
+        } else if other.bounds[(d, 1)].checked_add(1)
+            == Some(self.bounds[(d, 0)])
+        {
+            bounds[(d, 0)] = other.bounds[(d, 0)];
+        } else {
+            return None;
+        }
+        Some(AABB::new(bounds))
+    }
+
+    /// The overlap of two boxes, `None` if they are disjoint.
+    /// Touching boxes share their face cells, so intersect.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        let min = self.min().sup(&other.min());
+        let max = self.max().inf(&other.max());
+        let result = AABB::new(Bounds::from_columns(&[min, max]));
+        result.check_validity().then_some(result)
+    }
+
+    /// The smallest box containing both.
+    pub fn union(&self, other: &Self) -> Self {
+        let min = self.min().inf(&other.min());
+        let max = self.max().sup(&other.max());
+        AABB::from_mm(min, max)
+    }
+
+    /// Scale the extent of each dimension by `factor`, keeping min fixed.
+    /// i.e. [0, 99] refined by 2 is [0, 199].
+    /// Used for grid refinement studies.
+    #[track_caller]
+    pub fn refine(&self, factor: i32) -> Self {
+        assert!(factor > 0, "ERROR: refine factor must be positive");
+        let exclusive_bounds = self.exclusive_bounds();
+        let mut bounds = self.bounds;
+        for d in 0..DIMENSION {
+            bounds[(d, 1)] = exclusive_bounds[d]
+                .checked_mul(factor)
+                .and_then(|e| e.checked_add(self.bounds[(d, 0)] - 1))
+                .unwrap_or_else(|| {
+                    panic!(
+                        "ERROR: {}, aabb: {}, factor: {}",
+                        GeometryOverflow, self, factor
+                    )
+                });
+        }
+        AABB::new(bounds)
+    }
 }
 
 #[cfg(test)]
@@ -232,6 +472,51 @@ mod unit_tests {
     use super::*;
     use nalgebra::{matrix, vector};
 
+    #[test]
+    fn fft_freq_iter_test() {
+        let aabb = AABB::new(matrix![0, 3; 0, 5]);
+        let freqs: Vec<_> = aabb.fft_freq_iter().collect();
+        assert_eq!(freqs.len(), aabb.complex_buffer_size());
+        for (expected_i, (i, _)) in freqs.iter().enumerate() {
+            assert_eq!(*i, expected_i);
+        }
+
+        // 4 by 6 real is 4 by 4 complex
+        assert_eq!(freqs[0].1, vector![0.0, 0.0]);
+        assert_eq!(freqs[3].1, vector![0.0, 3.0]);
+        assert_eq!(freqs[4].1, vector![1.0, 0.0]);
+        assert_eq!(freqs[8].1, vector![-2.0, 0.0]);
+        assert_eq!(freqs[14].1, vector![-1.0, 2.0]);
+
+        // Matches FFTW's r2c layout, a cosine peaks at its wavevector
+        let n = vector![8, 6];
+        let aabb = AABB::new(matrix![0, 7; 0, 5]);
+        let plan = crate::fft_solver::FFTPlan::new(
+            &n,
+            crate::fft_solver::PlanType::Estimate,
+            false,
+        );
+        let mut real = fftw::array::AlignedVec::new(aabb.buffer_size());
+        let mut complex =
+            fftw::array::AlignedVec::new(aabb.complex_buffer_size());
+        for (i, value) in real.iter_mut().enumerate() {
+            let c = aabb.linear_to_coord(i);
+            let phase = 2.0
+                * std::f64::consts::PI
+                * (-3.0 * c[0] as f64 / 8.0 + 2.0 * c[1] as f64 / 6.0);
+            *value = phase.cos();
+        }
+        plan.forward(&mut real, &mut complex);
+        for (i, k) in aabb.fft_freq_iter() {
+            let magnitude = complex[i].norm();
+            if k == vector![-3.0, 2.0] {
+                assert!((magnitude - 24.0).abs() < 1e-9);
+            } else {
+                assert!(magnitude < 1e-9, "{} at {:?}", magnitude, k);
+            }
+        }
+    }
+
     #[test]
     fn buffer_size_test() {
         {
@@ -368,6 +653,43 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    fn periodic_coord_wrapping_test() {
+        let aabb = AABB::new(matrix![13, 16; -2, 0]);
+
+        // Agrees with periodic_coord within one box
+        for c in AABB::new(matrix![9, 20; -5, 3]).coord_iter() {
+            assert_eq!(
+                aabb.periodic_coord_wrapping(&c),
+                aabb.periodic_coord(&c)
+            );
+        }
+
+        // Offsets larger than the box in both directions
+        assert_eq!(
+            aabb.periodic_coord_wrapping(&vector![13 + 7, 0]),
+            vector![16, 0]
+        );
+        assert_eq!(
+            aabb.periodic_coord_wrapping(&vector![13 - 7, -2]),
+            vector![14, -2]
+        );
+        assert_eq!(
+            aabb.periodic_coord_wrapping(&vector![13 + 40, 9]),
+            vector![13, 0]
+        );
+        assert_eq!(
+            aabb.periodic_coord_wrapping(&vector![13 - 41, -2 - 10]),
+            vector![16, 0]
+        );
+        for c in aabb.coord_iter() {
+            for k in [-5, -2, 2, 5] {
+                let far = c + vector![4 * k, 3 * k];
+                assert_eq!(aabb.periodic_coord_wrapping(&far), c);
+            }
+        }
+    }
+
     #[test]
     fn contains_aabb_test() {
         {
@@ -492,4 +814,288 @@ mod unit_tests {
             );
         }
     }
+
+    #[test]
+    fn center_test() {
+        assert_eq!(AABB::new(matrix![0, 10]).center(), vector![5]);
+        assert_eq!(AABB::new(matrix![0, 9]).center(), vector![4]);
+        assert_eq!(AABB::new(matrix![-9, 0]).center(), vector![-5]);
+        assert_eq!(AABB::new(matrix![3, 3]).center(), vector![3]);
+        assert_eq!(AABB::new(matrix![-1, 4; 2, 7]).center(), vector![1, 4]);
+        assert_eq!(
+            AABB::new(matrix![0, 999; 0, 0; -8, -2]).center(),
+            vector![499, 0, -5]
+        );
+        let b = AABB::new(matrix![i32::MIN, i32::MAX]);
+        assert_eq!(b.center(), vector![-1]);
+        assert!(b.contains(&b.center()));
+    }
+
+    #[test]
+    fn corners_test() {
+        let b = AABB::new(matrix![-1, 4; 2, 7]);
+        let corners: Vec<Coord<2>> = b.corners().collect();
+        assert_eq!(
+            corners,
+            vec![vector![-1, 2], vector![4, 2], vector![-1, 7], vector![4, 7]]
+        );
+
+        let b = AABB::new(matrix![0, 9; 3, 5; -8, -2]);
+        let corners: Vec<Coord<3>> = b.corners().collect();
+        assert_eq!(corners.len(), 8);
+        for (i, c) in corners.iter().enumerate() {
+            assert!(b.contains(c));
+            assert!(!corners[i + 1..].contains(c));
+        }
+    }
+
+    #[test]
+    fn degenerate_axis_test() {
+        let b = AABB::new(matrix![0, 99; 0, 99; 0, 0]);
+        assert_eq!(b.buffer_size(), 10000);
+        assert_eq!(b.complex_buffer_size(), 10000);
+        assert_eq!(b.min_size_len(), 1);
+
+        // Zero slopes on the degenerate axis
+        let slopes = matrix![1, 1; 1, 1; 0, 0];
+        assert_eq!(b.min_sloped_size_len(&slopes), 100);
+        assert_eq!(b.min_sloped_size_len(&Bounds::zeros()), i32::MAX);
+        let (steps, center) = b.shrink(0.5, slopes, None);
+        assert_eq!(steps, 25);
+        assert_eq!(center, AABB::new(matrix![25, 74; 25, 74; 0, 0]));
+
+        // Degenerate sides of the decomposition are empty
+        let d = b.decomposition(&center);
+        for side in 0..2 {
+            assert!(d[0][side].check_validity());
+            assert!(d[1][side].check_validity());
+            assert!(!d[2][side].check_validity());
+        }
+    }
+
+    #[test]
+    fn add_bounds_diff_overflow_test() {
+        let a = AABB::new(matrix![0, 10; -5, 5]);
+        let diff = matrix![-1, 1; -2, 2];
+        assert_eq!(
+            a.checked_add_bounds_diff(diff),
+            Ok(AABB::new(matrix![-1, 11; -7, 7]))
+        );
+
+        let b = AABB::new(matrix![0, i32::MAX - 1]);
+        assert_eq!(
+            b.checked_add_bounds_diff(matrix![0, 2]),
+            Err(GeometryOverflow)
+        );
+        let c = AABB::new(matrix![i32::MIN + 1, 0]);
+        assert_eq!(
+            c.checked_add_bounds_diff(matrix![-2, 0]),
+            Err(GeometryOverflow)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn add_bounds_diff_overflow_panic() {
+        let b = AABB::new(matrix![0, i32::MAX - 1]);
+        b.add_bounds_diff(matrix![0, 2]);
+    }
+
+    #[test]
+    fn pad_test() {
+        let a = AABB::new(matrix![0, 10; -5, 5]);
+        assert_eq!(a.pad(0), a);
+        assert_eq!(a.pad(2), AABB::new(matrix![-2, 12; -7, 7]));
+        assert_eq!(a.pad(-3), AABB::new(matrix![3, 7; -2, 2]));
+        assert_eq!(a.pad(2).pad(-2), a);
+        assert_eq!(
+            a.pad_per_dim(&vector![1, -5]),
+            AABB::new(matrix![-1, 11; 0, 0])
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn pad_erode_invalid_test() {
+        let a = AABB::new(matrix![0, 4]);
+        a.pad(-3);
+    }
+
+    fn test_partition<const DIMENSION: usize>(
+        bounds: &AABB<DIMENSION>,
+        counts: &[usize; DIMENSION],
+    ) -> Vec<AABB<DIMENSION>> {
+        let blocks = bounds.partition(counts);
+        assert_eq!(blocks.len(), counts.iter().product::<usize>());
+
+        let mut coord_set = std::collections::HashSet::new();
+        for b in &blocks {
+            assert!(b.check_validity());
+            for c in b.coord_iter() {
+                assert!(!coord_set.contains(&c));
+                coord_set.insert(c);
+            }
+        }
+
+        for c in bounds.coord_iter() {
+            assert!(coord_set.contains(&c));
+        }
+        assert_eq!(bounds.buffer_size(), coord_set.len());
+        blocks
+    }
+
+    #[test]
+    fn partition_test() {
+        {
+            let bounds = AABB::new(matrix![0, 9; 10, 29]);
+            let blocks = test_partition(&bounds, &[2, 4]);
+            assert_eq!(blocks[0], AABB::new(matrix![0, 4; 10, 14]));
+            assert_eq!(blocks[1], AABB::new(matrix![0, 4; 15, 19]));
+            assert_eq!(blocks[4], AABB::new(matrix![5, 9; 10, 14]));
+        }
+
+        {
+            let bounds = AABB::new(matrix![0, 9]);
+            let blocks = test_partition(&bounds, &[3]);
+            assert_eq!(
+                blocks,
+                vec![
+                    AABB::new(matrix![0, 3]),
+                    AABB::new(matrix![4, 6]),
+                    AABB::new(matrix![7, 9])
+                ]
+            );
+        }
+
+        {
+            let bounds = AABB::new(matrix![-3, 7; 0, 12; 5, 9]);
+            test_partition(&bounds, &[4, 5, 2]);
+            test_partition(&bounds, &[1, 13, 5]);
+        }
+    }
+
+    #[test]
+    fn refine_test() {
+        assert_eq!(
+            AABB::new(matrix![0, 99]).refine(2),
+            AABB::new(matrix![0, 199])
+        );
+        assert_eq!(
+            AABB::new(matrix![0, 9; 0, 4]).refine(1),
+            AABB::new(matrix![0, 9; 0, 4])
+        );
+
+        let aabb = AABB::new(matrix![-5, 4; 10, 29; 3, 3]);
+        for factor in [2, 3, 4] {
+            let refined = aabb.refine(factor);
+            assert_eq!(refined.min(), aabb.min());
+            assert_eq!(
+                refined.exclusive_bounds(),
+                aabb.exclusive_bounds() * factor
+            );
+            assert_eq!(
+                refined.max() - refined.min(),
+                (aabb.exclusive_bounds() * factor).add_scalar(-1)
+            );
+        }
+    }
+
+    #[test]
+    fn intersect_union_test() {
+        // Disjoint
+        {
+            let a = AABB::new(matrix![0, 4]);
+            let b = AABB::new(matrix![6, 9]);
+            assert_eq!(a.intersect(&b), None);
+            assert_eq!(a.union(&b), AABB::new(matrix![0, 9]));
+        }
+
+        {
+            let a = AABB::new(matrix![0, 4; 0, 4]);
+            let b = AABB::new(matrix![2, 8; 5, 9]);
+            assert_eq!(a.intersect(&b), None);
+            assert_eq!(b.intersect(&a), None);
+            assert_eq!(a.union(&b), AABB::new(matrix![0, 8; 0, 9]));
+        }
+
+        // Touching
+        {
+            let a = AABB::new(matrix![0, 4; 0, 4]);
+            let b = AABB::new(matrix![4, 8; 2, 3]);
+            assert_eq!(a.intersect(&b), Some(AABB::new(matrix![4, 4; 2, 3])));
+            assert_eq!(a.union(&b), AABB::new(matrix![0, 8; 0, 4]));
+        }
+
+        // Nested
+        {
+            let outer = AABB::new(matrix![2, 20; 5, 19; 40, 60]);
+            let inner = AABB::new(matrix![6, 14; 10, 13; 47, 53]);
+            assert_eq!(outer.intersect(&inner), Some(inner));
+            assert_eq!(inner.intersect(&outer), Some(inner));
+            assert_eq!(outer.union(&inner), outer);
+            assert_eq!(inner.union(&outer), outer);
+        }
+
+        // Partially overlapping
+        {
+            let a = AABB::new(matrix![0, 9; 0, 9; 0, 9]);
+            let b = AABB::new(matrix![5, 14; -5, 4; 3, 6]);
+            let expected = AABB::new(matrix![5, 9; 0, 4; 3, 6]);
+            assert_eq!(a.intersect(&b), Some(expected));
+            assert_eq!(b.intersect(&a), Some(expected));
+            assert_eq!(a.union(&b), AABB::new(matrix![0, 14; -5, 9; 0, 9]));
+        }
+    }
+
+    #[test]
+    fn try_merge_test() {
+        // x adjacent, same y
+        {
+            let a = AABB::new(matrix![0, 4; 2, 8]);
+            let b = AABB::new(matrix![5, 9; 2, 8]);
+            let expected = Some(AABB::new(matrix![0, 9; 2, 8]));
+            assert_eq!(a.try_merge(&b), expected);
+            assert_eq!(b.try_merge(&a), expected);
+        }
+
+        // Diagonal
+        {
+            let a = AABB::new(matrix![0, 4; 0, 4]);
+            let b = AABB::new(matrix![5, 9; 5, 9]);
+            assert_eq!(a.try_merge(&b), None);
+        }
+
+        // Overlapping
+        {
+            let a = AABB::new(matrix![0, 5; 0, 4]);
+            let b = AABB::new(matrix![5, 9; 0, 4]);
+            assert_eq!(a.try_merge(&b), None);
+            assert_eq!(a.try_merge(&a), None);
+        }
+
+        // Gap
+        {
+            let a = AABB::new(matrix![0, 3; 0, 4]);
+            let b = AABB::new(matrix![5, 9; 0, 4]);
+            assert_eq!(a.try_merge(&b), None);
+        }
+
+        // Adjacent but different extents
+        {
+            let a = AABB::new(matrix![0, 4; 0, 4]);
+            let b = AABB::new(matrix![5, 9; 0, 5]);
+            assert_eq!(a.try_merge(&b), None);
+        }
+
+        // Partitions merge back
+        {
+            let bounds = AABB::new(matrix![0, 9; 0, 5; -3, 3]);
+            let blocks = bounds.partition(&[1, 1, 3]);
+            let merged = blocks[0]
+                .try_merge(&blocks[1])
+                .and_then(|m| m.try_merge(&blocks[2]));
+            assert_eq!(merged, Some(bounds));
+        }
+    }
 }
@@ -22,6 +22,18 @@ pub fn complex_buffer_size<const DIMENSION: usize>(
     accumulator
 }
 
+/// Signed frequency of FFT output `index` along an axis of length `n`,
+/// with the upper half wrapping to negative frequencies.
+/// An even length's Nyquist frequency is reported as `-n / 2`, like numpy.
+pub fn signed_frequency(index: i32, n: i32) -> i32 {
+    debug_assert!(0 <= index && index < n);
+    if index < (n + 1) / 2 {
+        index
+    } else {
+        index - n
+    }
+}
+
 pub fn coord_to_linear<const GRID_DIMENSION: usize>(
     coord: &Coord<GRID_DIMENSION>,
     exclusive_bounds: &Coord<GRID_DIMENSION>,
@@ -15,6 +15,46 @@ pub trait NumTrait = Num + Copy + Send + Sync;
 pub type Coord<const GRID_DIMENSION: usize> =
     nalgebra::SVector<i32, { GRID_DIMENSION }>;
 
+/// Geometry arithmetic left the range of `i32`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GeometryOverflow;
+
+impl std::fmt::Display for GeometryOverflow {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> Result<(), std::fmt::Error> {
+        write!(f, "geometry arithmetic overflowed i32")
+    }
+}
+
+impl std::error::Error for GeometryOverflow {}
+
+/// Element wise `steps * slopes`, checking for overflow.
+pub fn checked_scale_bounds<const GRID_DIMENSION: usize>(
+    steps: usize,
+    slopes: &Bounds<GRID_DIMENSION>,
+) -> Result<Bounds<GRID_DIMENSION>, GeometryOverflow> {
+    let i_steps = i32::try_from(steps).map_err(|_| GeometryOverflow)?;
+    let mut result = *slopes;
+    for v in result.iter_mut() {
+        *v = v.checked_mul(i_steps).ok_or(GeometryOverflow)?;
+    }
+    Ok(result)
+}
+
+/// Element wise `steps * slopes`.
+/// Panics on overflow rather than wrapping in release builds.
+#[track_caller]
+pub fn scale_bounds<const GRID_DIMENSION: usize>(
+    steps: usize,
+    slopes: &Bounds<GRID_DIMENSION>,
+) -> Bounds<GRID_DIMENSION> {
+    checked_scale_bounds(steps, slopes).unwrap_or_else(|e| {
+        panic!("ERROR: {}, steps: {}, slopes: {:?}", e, steps, slopes)
+    })
+}
+
 #[inline]
 pub fn flip_sloped<const GRID_DIMENSION: usize>(
     sloped: &Bounds<GRID_DIMENSION>,
@@ -58,4 +98,24 @@ mod unit_tests {
     fn slopes_to_diff() {
         {}
     }
+
+    #[test]
+    fn scale_bounds_test() {
+        let slopes = matrix![1, 2; 0, 3];
+        assert_eq!(scale_bounds(10, &slopes), matrix![10, 20; 0, 30]);
+
+        // 100k steps with a wide stencil
+        let wide = matrix![30000, 30000];
+        assert_eq!(checked_scale_bounds(100000, &wide), Err(GeometryOverflow));
+        assert_eq!(
+            checked_scale_bounds(usize::MAX, &slopes),
+            Err(GeometryOverflow)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn scale_bounds_overflow() {
+        scale_bounds(100000, &matrix![30000, 30000]);
+    }
 }
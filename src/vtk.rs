@@ -1,4 +1,5 @@
 use crate::domain::*;
+use crate::sidecar::*;
 use nalgebra::vector;
 use vtkio::model::*;
 
@@ -78,3 +79,153 @@ pub fn write_vtk3d<P: AsRef<std::path::Path>, DomainType: DomainView<3>>(
     .export(s)
     .unwrap();
 }
+
+/// `write_vtk3d`, and the `sidecar` metadata next to it if given,
+/// see `sidecar_path`.
+pub fn write_vtk3d_with_sidecar<
+    P: AsRef<std::path::Path>,
+    DomainType: DomainView<3>,
+>(
+    domain: &DomainType,
+    s: &P,
+    sidecar: Option<&FrameMetadata<3>>,
+) {
+    write_vtk3d(domain, s);
+    write_sidecar(sidecar, s, domain.aabb());
+}
+
+/// `write_vtk`, and the `sidecar` metadata next to it if given,
+/// see `sidecar_path`.
+pub fn write_vtk_with_sidecar<
+    P: AsRef<std::path::Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    s: &P,
+    sidecar: Option<&FrameMetadata<GRID_DIMENSION>>,
+) {
+    write_vtk(domain, s);
+    write_sidecar(sidecar, s, domain.aabb());
+}
+
+/// Write any dimension domain as VTK structured points.
+/// Missing dimensions are padded with extent 1,
+/// so 1D and 2D domains become degenerate 3D grids.
+pub fn write_vtk<
+    P: AsRef<std::path::Path>,
+    const GRID_DIMENSION: usize,
+    DomainType: DomainView<GRID_DIMENSION>,
+>(
+    domain: &DomainType,
+    s: &P,
+) {
+    assert!(GRID_DIMENSION <= 3);
+    println!("Writing vtk: {:?}", s.as_ref());
+    let aabb = domain.aabb();
+    let exclusive_bounds = aabb.exclusive_bounds();
+
+    let mut dims = [1u32; 3];
+    let mut origin = [0.0f32; 3];
+    for d in 0..GRID_DIMENSION {
+        dims[d] = exclusive_bounds[d] as u32;
+        origin[d] = aabb.bounds[(d, 0)] as f32;
+    }
+
+    // VTK points are ordered with the first axis fastest,
+    // our buffers have the last axis fastest.
+    let buffer_size = aabb.buffer_size();
+    let mut data = Vec::with_capacity(buffer_size);
+    for k in 0..dims[2] as i32 {
+        for j in 0..dims[1] as i32 {
+            for i in 0..dims[0] as i32 {
+                let index = [i, j, k];
+                let mut coord = aabb.min();
+                for d in 0..GRID_DIMENSION {
+                    coord[d] += index[d];
+                }
+                data.push(domain.view(&coord) as f32);
+            }
+        }
+    }
+    assert_eq!(data.len(), buffer_size);
+
+    Vtk {
+        version: Version::Auto,
+        title: String::new(),
+        byte_order: ByteOrder::LittleEndian,
+        file_path: None,
+        data: DataSet::ImageData {
+            extent: Extent::Dims(dims),
+            origin,
+            spacing: [1.0; 3],
+            meta: None,
+            pieces: vec![Piece::Inline(Box::new(ImageDataPiece {
+                extent: Extent::Dims(dims),
+                data: Attributes {
+                    point: vec![Attribute::DataArray(DataArray {
+                        name: "values".to_string(),
+                        elem: ElementType::Scalars {
+                            num_comp: 1,
+                            lookup_table: None,
+                        },
+                        data: IOBuffer::F32(data),
+                    })],
+                    cell: vec![],
+                },
+            }))],
+        },
+    }
+    .export(s)
+    .unwrap();
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::util::*;
+
+    fn dimensions_header<P: AsRef<std::path::Path>>(path: &P) -> String {
+        let bytes = std::fs::read(path).unwrap();
+        String::from_utf8_lossy(&bytes)
+            .lines()
+            .find(|l| l.starts_with("DIMENSIONS"))
+            .unwrap()
+            .trim()
+            .to_string()
+    }
+
+    fn check_dimensions<const GRID_DIMENSION: usize>(
+        aabb: AABB<GRID_DIMENSION>,
+        name: &str,
+    ) {
+        let mut domain = OwnedDomain::new(aabb);
+        domain.par_set_values(|coord| coord.sum() as f64, 2);
+
+        let path = std::env::temp_dir().join(name);
+        write_vtk(&domain, &path);
+
+        let exclusive_bounds = aabb.exclusive_bounds();
+        let mut dims = [1; 3];
+        for d in 0..GRID_DIMENSION {
+            dims[d] = exclusive_bounds[d];
+        }
+        let expected =
+            format!("DIMENSIONS {} {} {}", dims[0], dims[1], dims[2]);
+        assert_eq!(dimensions_header(&path), expected);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn write_vtk_dimensions() {
+        check_dimensions(AABB::new(matrix![0, 9]), "nhls_write_vtk_1d.vtk");
+        check_dimensions(
+            AABB::new(matrix![0, 9; -2, 4]),
+            "nhls_write_vtk_2d.vtk",
+        );
+        check_dimensions(
+            AABB::new(matrix![0, 9; 0, 4; 3, 5]),
+            "nhls_write_vtk_3d.vtk",
+        );
+    }
+}
@@ -35,9 +35,11 @@ fn heat_1d_ap_compare() {
     // Create AP Solver
     let planner_params = PlannerParameters {
         plan_type: PlanType::Estimate,
+        plan_fallback: false,
         cutoff: 40,
         ratio: 0.5,
         chunk_size,
+        max_steps: None,
     };
     let fft_solver =
         APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
@@ -93,9 +95,11 @@ fn heat_2d_ap_compare() {
     // Create AP Solver
     let planner_params = PlannerParameters {
         plan_type: PlanType::Estimate,
+        plan_fallback: false,
         cutoff: 40,
         ratio: 0.5,
         chunk_size,
+        max_steps: None,
     };
     let fft_solver =
         APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
@@ -120,3 +124,1023 @@ fn heat_2d_ap_compare() {
         );
     }
 }
+
+#[test]
+fn ap_repeated_solve_stress() {
+    // Repeat a solve with many boundary tasks,
+    // every run must write exactly the same output.
+    // Run with different `--test-threads` / RAYON_NUM_THREADS
+    // values to shake out races in the boundary solves.
+    let grid_bound = AABB::new(matrix![0, 99; 0, 99]);
+    let n_steps = 50;
+    let chunk_size = 10;
+    let iterations = 25;
+
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+
+    let mut ic_domain = OwnedDomain::new(grid_bound);
+    normal_ic_2d(&mut ic_domain, chunk_size);
+
+    let mut buffer_1 = OwnedDomain::new(grid_bound);
+    let mut buffer_2 = OwnedDomain::new(grid_bound);
+    let mut input_domain = buffer_1.as_slice_domain();
+    let mut output_domain = buffer_2.as_slice_domain();
+
+    let mut expected: Option<Vec<f64>> = None;
+    for _ in 0..iterations {
+        input_domain.par_set_subdomain(&ic_domain, chunk_size);
+
+        solver.apply(&mut input_domain, &mut output_domain, 0);
+
+        match &expected {
+            None => expected = Some(output_domain.buffer().to_vec()),
+            Some(e) => assert_eq!(e.as_slice(), output_domain.buffer()),
+        }
+    }
+}
+
+#[test]
+fn ap_subregion_test() {
+    let chunk_size = 100;
+    let n_steps = 60;
+    let full_bound = AABB::new(matrix![0, 79; 0, 79]);
+    let window = AABB::new(matrix![20, 59; 15, 64]);
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+
+    let ic = |c: Coord<2>| ((c[0] - 40) * (c[0] - 40) + c[1]) as f64 / 100.0;
+    let mut full_domain = OwnedDomain::new(full_bound);
+    full_domain.par_set_values(ic, chunk_size);
+
+    let bc =
+        exterior_check(&full_domain, window, &stencil.slopes(), chunk_size);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let fft_solver =
+        APSolver::new(&bc, &stencil, window, n_steps, &planner_params);
+    assert_eq!(*fft_solver.aabb(), window);
+
+    let mut fft_buffer_1 = OwnedDomain::new(window);
+    let mut fft_buffer_2 = OwnedDomain::new(window);
+    let mut fft_input_domain = fft_buffer_1.as_slice_domain();
+    let mut fft_output_domain = fft_buffer_2.as_slice_domain();
+    fft_solver.apply_subregion(
+        &mut full_domain,
+        &mut fft_input_domain,
+        &mut fft_output_domain,
+        0,
+    );
+
+    // Direct solve of the window with the same boundary
+    let mut direct_input_domain = OwnedDomain::new(window);
+    let mut direct_output_domain = OwnedDomain::new(window);
+    direct_input_domain.par_set_values(ic, chunk_size);
+    box_apply(
+        &bc,
+        &stencil,
+        &mut direct_input_domain,
+        &mut direct_output_domain,
+        n_steps,
+        0,
+        chunk_size,
+    );
+
+    for c in full_bound.coord_iter() {
+        if window.contains(&c) {
+            assert_approx_eq!(
+                f64,
+                full_domain.view(&c),
+                direct_output_domain.view(&c),
+                epsilon = 1e-10
+            );
+        } else {
+            // Exterior stays pinned to the loaded values
+            assert_eq!(full_domain.view(&c), ic(c));
+        }
+    }
+
+    // The window actually evolved
+    let center = vector![40, 40];
+    assert!((full_domain.view(&center) - ic(center)).abs() > 1e-3);
+}
+
+#[test]
+fn heat_3d_ap_compare() {
+    // Uneven extents give boundary solves of different sizes
+    let grid_bound = AABB::new(matrix![0, 59; 0, 23; 0, 41]);
+
+    let n_steps = 30;
+
+    let chunk_size = 100;
+
+    let stencil =
+        nhls::standard_stencils::heat_3d(1.0, 1.0, 1.0, 1.0, 0.1, 0.1, 0.1);
+
+    // Create domains
+    let buffer_size = grid_bound.buffer_size();
+    let mut direct_input_domain = OwnedDomain::new(grid_bound);
+    let mut direct_output_domain = OwnedDomain::new(grid_bound);
+    let mut fft_buffer_1 = OwnedDomain::new(grid_bound);
+    let mut fft_buffer_2 = OwnedDomain::new(grid_bound);
+    let mut fft_input_domain = fft_buffer_1.as_slice_domain();
+    let mut fft_output_domain = fft_buffer_2.as_slice_domain();
+
+    normal_ic_3d(&mut direct_input_domain, chunk_size);
+    normal_ic_3d(&mut fft_input_domain, chunk_size);
+
+    // Create BC
+    let bc = ConstantCheck::new(1.0, grid_bound);
+
+    // Create AP Solver
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 10,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let fft_solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+    fft_solver.apply(&mut fft_input_domain, &mut fft_output_domain, 0);
+
+    box_apply(
+        &bc,
+        &stencil,
+        &mut direct_input_domain,
+        &mut direct_output_domain,
+        n_steps,
+        0,
+        chunk_size,
+    );
+
+    for i in 0..buffer_size {
+        assert_approx_eq!(
+            f64,
+            fft_output_domain.buffer()[i],
+            direct_output_domain.buffer()[i],
+            epsilon = 0.0000000000001
+        );
+    }
+}
+
+#[test]
+fn ap_node_dump_test() {
+    // Node dumps are disabled in release builds
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    let grid_bound = AABB::new(matrix![0, 199]);
+    let n_steps = 50;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+
+    let mut dump_dir = std::env::temp_dir();
+    dump_dir.push(format!("nhls_node_dump_{}", std::process::id()));
+    std::fs::create_dir_all(&dump_dir).unwrap();
+
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params)
+            .with_node_dump(&dump_dir);
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
+    normal_ic_1d(&mut input_domain, chunk_size);
+    solver.apply(&mut input_domain, &mut output_domain, 0);
+
+    // Every node under the first root solve gets a dump
+    let plan = &solver.plan;
+    let repeat_node = plan.unwrap_repeat_node(plan.root);
+    let mut expected = Vec::new();
+    let mut stack = vec![repeat_node.node];
+    while let Some(node_id) = stack.pop() {
+        expected.push(node_id);
+        if let PlanNode::PeriodicSolve(p) = plan.get_node(node_id) {
+            stack.extend(p.boundary_nodes.clone());
+            stack.extend(p.time_cut);
+        }
+    }
+    assert!(expected.len() > 1);
+    let node_dump = solver.node_dump.as_ref().unwrap();
+    for node_id in expected {
+        let path = node_dump.node_path(0, node_id);
+        let size = std::fs::metadata(&path).unwrap().len() as usize;
+
+        // The root periodic solve keeps the full domain
+        let output_aabb = if node_id == repeat_node.node {
+            grid_bound
+        } else {
+            *plan.node_output_aabb(node_id)
+        };
+        assert_eq!(size, output_aabb.buffer_size() * 8);
+    }
+
+    std::fs::remove_dir_all(&dump_dir).unwrap();
+}
+
+#[test]
+fn ap_cloned_plan_test() {
+    let grid_bound = AABB::new(matrix![0, 299]);
+    let n_steps = 120;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let planner_result =
+        create_ap_plan(&stencil, grid_bound, n_steps, &planner_params);
+
+    // Mutating a clone leaves the original alone
+    {
+        let mut clone = planner_result.clone();
+        let root = clone.plan.root;
+        if let PlanNode::Repeat(repeat_node) = &mut clone.plan.nodes[root] {
+            repeat_node.n += 1;
+        }
+        assert_ne!(clone.plan.plan_hash(), planner_result.plan.plan_hash());
+    }
+
+    let clone = planner_result.clone();
+    assert_eq!(clone.plan.plan_hash(), planner_result.plan.plan_hash());
+    let original_solver = APSolver::from_planner_result(
+        &bc,
+        &stencil,
+        planner_result,
+        &planner_params,
+    );
+    let clone_solver =
+        APSolver::from_planner_result(&bc, &stencil, clone, &planner_params);
+
+    let mut original_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut original_input, mut original_output) =
+        original_buffer.split_halves();
+    let mut clone_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut clone_input, mut clone_output) = clone_buffer.split_halves();
+    normal_ic_1d(&mut original_input, chunk_size);
+    normal_ic_1d(&mut clone_input, chunk_size);
+
+    original_solver.apply(&mut original_input, &mut original_output, 0);
+    clone_solver.apply(&mut clone_input, &mut clone_output, 0);
+    assert_eq!(original_output.buffer(), clone_output.buffer());
+}
+
+#[test]
+fn ap_cancellable_test() {
+    let grid_bound = AABB::new(matrix![0, 299]);
+    let n_steps = 60;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+
+    // Reference state after one frame
+    let mut reference_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut reference_input, mut reference_output) =
+        reference_buffer.split_halves();
+    normal_ic_1d(&mut reference_input, chunk_size);
+    solver.apply(&mut reference_input, &mut reference_output, 0);
+
+    // Frame loop, cancelled after the first frame
+    let cancel = std::sync::atomic::AtomicBool::new(false);
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
+    normal_ic_1d(&mut input_domain, chunk_size);
+    let mut global_time = 0;
+    let mut frames = 0;
+    for _ in 0..4 {
+        let steps = solver.apply_cancellable(
+            &mut input_domain,
+            &mut output_domain,
+            global_time,
+            &cancel,
+        );
+        global_time += steps;
+        if steps < n_steps {
+            assert_eq!(steps, 0);
+            break;
+        }
+        frames += 1;
+        std::mem::swap(&mut input_domain, &mut output_domain);
+        cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+    assert_eq!(frames, 1);
+    assert_eq!(global_time, n_steps);
+
+    // The cancelled call leaves the last frame in output_domain
+    assert_eq!(output_domain.buffer(), reference_output.buffer());
+}
+
+fn json_usize(json: &str, key: &str) -> usize {
+    let field = format!("\"{}\": ", key);
+    let start = json.find(&field).unwrap() + field.len();
+    let end =
+        start + json[start..].find(|c: char| !c.is_ascii_digit()).unwrap();
+    json[start..end].parse().unwrap()
+}
+
+#[test]
+fn ap_report_test() {
+    let grid_bound = AABB::new(matrix![0, 99; 0, 99]);
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver = APSolver::new(&bc, &stencil, grid_bound, 100, &planner_params);
+
+    let mut path = std::env::temp_dir();
+    path.push(format!("nhls_report_{}.json", std::process::id()));
+    solver.write_report(&path);
+    let report = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(json_usize(&report, "node_count"), solver.plan.len());
+    assert_eq!(
+        json_usize(&report, "scratch_bytes"),
+        solver.scratch_space.size
+    );
+    assert_eq!(report.matches("\"type\": ").count(), solver.plan.len());
+    assert_eq!(
+        report.matches("\"input_offset\": ").count(),
+        solver.node_scratch_descriptors.len()
+    );
+    assert!(report.contains("\"cutoff\": 20"));
+    assert!(report.contains("\"grid\": [[0, 99], [0, 99]]"));
+}
+
+#[test]
+fn ap_degenerate_axis_test() {
+    // A 2D problem padded into 3D solves like the 2D problem
+    let grid_bound_2d = AABB::new(matrix![0, 99; 0, 99]);
+    let grid_bound_3d = AABB::new(matrix![0, 99; 0, 99; 0, 0]);
+    let n_steps = 60;
+    let chunk_size = 100;
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let ic = |x: i32, y: i32| {
+        let dx = (x - 40) as f64 / 10.0;
+        let dy = (y - 55) as f64 / 15.0;
+        (-dx * dx - dy * dy).exp()
+    };
+
+    // Reference 2D direct solve
+    let stencil_2d = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc_2d = ConstantCheck::new(0.5, grid_bound_2d);
+    let mut input_2d = OwnedDomain::new(grid_bound_2d);
+    let mut output_2d = OwnedDomain::new(grid_bound_2d);
+    input_2d.par_set_values(|c| ic(c[0], c[1]), chunk_size);
+    box_apply(
+        &bc_2d,
+        &stencil_2d,
+        &mut input_2d,
+        &mut output_2d,
+        n_steps,
+        0,
+        chunk_size,
+    );
+
+    // Same stencil with zero width along z
+    let stencil_3d = nhls::stencil::Stencil::new(
+        [[0, 0, 0], [-1, 0, 0], [1, 0, 0], [0, -1, 0], [0, 1, 0]],
+        |args: &[f64; 5]| {
+            let middle = args[0];
+            middle
+                + 0.2 * (args[1] - 2.0 * middle + args[2])
+                + 0.2 * (args[4] - 2.0 * middle + args[3])
+        },
+    );
+    assert_eq!(stencil_3d.slopes(), matrix![1, 1; 1, 1; 0, 0]);
+    let bc_3d = ConstantCheck::new(0.5, grid_bound_3d);
+    let solver = APSolver::new(
+        &bc_3d,
+        &stencil_3d,
+        grid_bound_3d,
+        n_steps,
+        &planner_params,
+    );
+    let mut buffer = OwnedDomain::new_paired(grid_bound_3d);
+    let (mut input_3d, mut output_3d) = buffer.split_halves();
+    input_3d.par_set_values(|c| ic(c[0], c[1]), chunk_size);
+    solver.apply(&mut input_3d, &mut output_3d, 0);
+
+    for (expected, actual) in output_2d.buffer().iter().zip(output_3d.buffer())
+    {
+        assert_approx_eq!(f64, *actual, *expected, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn ap_try_new_test() {
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size: 100,
+        max_steps: None,
+    };
+    let budget = 1 << 20;
+
+    // Small plan fits, and matches the allocated scratch
+    let small_bound = AABB::new(matrix![0, 49; 0, 49]);
+    let bc = ConstantCheck::new(1.0, small_bound);
+    let solver = APSolver::try_new(
+        &bc,
+        &stencil,
+        small_bound,
+        20,
+        &planner_params,
+        Some(budget),
+    )
+    .unwrap();
+    assert_eq!(
+        APScratchBuilder::scratch_bytes(&solver.plan),
+        solver.scratch_space.size
+    );
+    assert!(solver.scratch_space.size <= budget);
+
+    // Large plan does not
+    let large_bound = AABB::new(matrix![0, 999; 0, 999]);
+    let bc = ConstantCheck::new(1.0, large_bound);
+    let result = APSolver::try_new(
+        &bc,
+        &stencil,
+        large_bound,
+        100,
+        &planner_params,
+        Some(budget),
+    );
+    match result {
+        Err(AllocError::ExceedsBudget {
+            required,
+            budget: b,
+        }) => {
+            assert!(required > budget);
+            assert_eq!(b, budget);
+        }
+        _ => panic!("expected ExceedsBudget"),
+    }
+}
+
+#[test]
+fn ap_run_node_test() {
+    let grid_bound = AABB::new(matrix![0, 99; 0, 99]);
+    let n_steps = 40;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+
+    // Find a direct solve somewhere in the boundary
+    let (node_id, direct_node) = solver
+        .plan
+        .nodes
+        .iter()
+        .enumerate()
+        .find_map(|(id, node)| match node {
+            PlanNode::DirectSolve(direct) => Some((id, direct.clone())),
+            _ => None,
+        })
+        .expect("plan has no direct solves");
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_2d(&mut input, chunk_size);
+    solver.run_node(node_id, &input, &mut output, 0);
+
+    let mut direct_input = OwnedDomain::new(grid_bound);
+    let mut direct_output = OwnedDomain::new(grid_bound);
+    normal_ic_2d(&mut direct_input, chunk_size);
+    box_apply(
+        &bc,
+        &stencil,
+        &mut direct_input,
+        &mut direct_output,
+        direct_node.steps,
+        0,
+        chunk_size,
+    );
+
+    // The frustrum is exact over its output region
+    direct_node.output_aabb.coord_iter().for_each(|coord| {
+        assert_approx_eq!(
+            f64,
+            output.view(&coord),
+            direct_output.view(&coord),
+            epsilon = 1e-12
+        );
+    });
+}
+
+#[test]
+fn ap_output_bound_test() {
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let grid_bound = AABB::new(matrix![0, 99; 0, 79]);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size: 100,
+        max_steps: None,
+    };
+
+    let solver = APSolver::new(&bc, &stencil, grid_bound, 77, &planner_params);
+    let output_bound = solver.output_bound();
+    assert_eq!(output_bound, grid_bound);
+
+    // The periodic output is shrunk,
+    // the boundary solves' outputs make up the difference
+    let repeat_solve = solver.plan.unwrap_repeat_node(solver.plan.root);
+    let last_id = repeat_solve.next.unwrap_or(repeat_solve.node);
+    let last = solver.plan.unwrap_periodic_node(last_id);
+    assert!(last.output_aabb.buffer_size() < output_bound.buffer_size());
+    let boundary_size: usize = last
+        .boundary_nodes
+        .clone()
+        .map(|id| solver.plan.node_output_aabb(id).buffer_size())
+        .sum();
+    assert_eq!(
+        last.output_aabb.buffer_size() + boundary_size,
+        output_bound.buffer_size()
+    );
+}
+
+#[test]
+fn ap_horizons_test() {
+    let grid_bound = AABB::new(matrix![0, 59; 0, 59]);
+    let steps_per_image = 15;
+    let horizons = 3;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: Some(steps_per_image),
+    };
+    let dt = 0.1;
+    let solver = APSolver::new(
+        &bc,
+        &stencil,
+        grid_bound,
+        horizons * steps_per_image,
+        &planner_params,
+    )
+    .with_dt(dt);
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_2d(&mut input, chunk_size);
+    let mut states = Vec::new();
+    solver.apply_with_horizons(
+        &mut input,
+        &mut output,
+        0,
+        steps_per_image,
+        |steps, time, domain| {
+            assert_approx_eq!(f64, time, steps as f64 * dt, ulps = 2);
+            states.push((steps, domain.buffer().to_vec()))
+        },
+    );
+    assert_eq!(states.len(), horizons);
+    assert_eq!(states.last().unwrap().1, output.buffer());
+
+    // Independent solves to each horizon
+    for (i, (steps, state)) in states.iter().enumerate() {
+        assert_eq!(*steps, (i + 1) * steps_per_image);
+        let mut direct_input = OwnedDomain::new(grid_bound);
+        let mut direct_output = OwnedDomain::new(grid_bound);
+        normal_ic_2d(&mut direct_input, chunk_size);
+        box_apply(
+            &bc,
+            &stencil,
+            &mut direct_input,
+            &mut direct_output,
+            *steps,
+            0,
+            chunk_size,
+        );
+        for (actual, expected) in state.iter().zip(direct_output.buffer()) {
+            assert_approx_eq!(f64, *actual, *expected, epsilon = 1e-12);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "falls inside a root solve")]
+fn ap_horizons_unaligned_test() {
+    let grid_bound = AABB::new(matrix![0, 59; 0, 59]);
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+
+    // Root solves of 10 steps can't stop at step 15
+    let solver = APSolverBuilder::new(grid_bound, 45)
+        .plan_type(PlanType::Estimate)
+        .cutoff(20)
+        .max_steps(Some(10))
+        .build(&bc, &stencil);
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    solver.apply_with_horizons(&mut input, &mut output, 0, 15, |_, _, _| {});
+}
+
+#[test]
+fn ap_coalesce_repeats_test() {
+    let grid_bound = AABB::new(matrix![0, 199]);
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+
+    // Find the central solve's steps, then plan exactly two of them
+    let central_steps = {
+        let planner_result =
+            create_ap_plan(&stencil, grid_bound, 1000, &planner_params);
+        let plan = &planner_result.plan;
+        plan.unwrap_periodic_node(plan.unwrap_repeat_node(plan.root).node)
+            .steps
+    };
+    let mut planner_result = create_ap_plan(
+        &stencil,
+        grid_bound,
+        2 * central_steps,
+        &planner_params,
+    );
+    let inner = planner_result
+        .plan
+        .unwrap_repeat_node(planner_result.plan.root);
+    assert_eq!(inner.n, 2);
+    assert!(inner.next.is_none());
+
+    // Nest it in another repeat, for six central solves
+    let plan = &mut planner_result.plan;
+    plan.nodes.push(PlanNode::Repeat(RepeatNode {
+        n: 3,
+        node: plan.root,
+        next: None,
+    }));
+    plan.root = plan.nodes.len() - 1;
+    let nested_len = plan.len();
+    plan.coalesce_repeats();
+    assert_eq!(plan.len(), nested_len - 1);
+    let root = plan.unwrap_repeat_node(plan.root);
+    assert_eq!(root.n, 6);
+    assert_eq!(plan.unwrap_periodic_node(root.node).steps, central_steps);
+    plan.verify(&grid_bound).unwrap();
+
+    let solver = APSolver::from_planner_result(
+        &bc,
+        &stencil,
+        planner_result,
+        &planner_params,
+    );
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_1d(&mut input, chunk_size);
+    solver.apply(&mut input, &mut output, 0);
+
+    let mut direct_input = OwnedDomain::new(grid_bound);
+    let mut direct_output = OwnedDomain::new(grid_bound);
+    normal_ic_1d(&mut direct_input, chunk_size);
+    box_apply(
+        &bc,
+        &stencil,
+        &mut direct_input,
+        &mut direct_output,
+        6 * central_steps,
+        0,
+        chunk_size,
+    );
+    for (actual, expected) in output.buffer().iter().zip(direct_output.buffer())
+    {
+        assert_approx_eq!(f64, *actual, *expected, epsilon = 1e-12);
+    }
+}
+
+#[test]
+fn ap_callback_test() {
+    let grid_bound = AABB::new(matrix![0, 99]);
+    let n_steps = 130;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+    let repeat_solve = solver.plan.unwrap_repeat_node(solver.plan.root);
+    let expected_calls = repeat_solve.n + repeat_solve.next.map_or(0, |_| 1);
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_1d(&mut input, chunk_size);
+    let mut progress = Vec::new();
+    solver.apply_with_callback(&mut input, &mut output, 0, |done, total| {
+        progress.push((done, total))
+    });
+    assert_eq!(progress.len(), expected_calls);
+    assert!(progress.windows(2).all(|w| w[0].0 < w[1].0));
+    assert!(progress.iter().all(|(_, total)| *total == n_steps));
+    assert_eq!(progress.last().unwrap().0, n_steps);
+
+    let mut plain_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut plain_input, mut plain_output) = plain_buffer.split_halves();
+    normal_ic_1d(&mut plain_input, chunk_size);
+    solver.apply(&mut plain_input, &mut plain_output, 0);
+    assert_eq!(output.buffer(), plain_output.buffer());
+}
+
+#[test]
+fn ap_checked_test() {
+    let grid_bound = AABB::new(matrix![0, 299]);
+    let n_steps = 120;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+
+    // Finite solves match apply
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_1d(&mut input, chunk_size);
+    assert_eq!(solver.apply_checked(&mut input, &mut output, 0), Ok(()));
+    let mut plain_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut plain_input, mut plain_output) = plain_buffer.split_halves();
+    normal_ic_1d(&mut plain_input, chunk_size);
+    solver.apply(&mut plain_input, &mut plain_output, 0);
+    assert_eq!(output.buffer(), plain_output.buffer());
+
+    // An infinite boundary blows up from the left edge
+    let bc = ConstantCheck::new(f64::INFINITY, grid_bound);
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_1d(&mut input, chunk_size);
+    let root_node = solver.plan.unwrap_repeat_node(solver.plan.root).node;
+    match solver.apply_checked(&mut input, &mut output, 0) {
+        Err(SolveError::NonFinite { node_id, coord }) => {
+            assert_eq!(node_id, root_node);
+            assert_eq!(coord, vector![0]);
+        }
+        Ok(()) => panic!("ERROR: expected a non-finite value"),
+    }
+    assert!(!output.buffer()[0].is_finite());
+}
+
+#[test]
+fn ap_builder_test() {
+    let grid_bound = AABB::new(matrix![0, 299]);
+    let n_steps = 120;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let solver =
+        APSolver::new(&bc, &stencil, grid_bound, n_steps, &planner_params);
+    let built = APSolverBuilder::new(grid_bound, n_steps)
+        .plan_type(PlanType::Estimate)
+        .cutoff(20)
+        .chunk_size(chunk_size)
+        .build(&bc, &stencil);
+    assert_eq!(built.plan.plan_hash(), solver.plan.plan_hash());
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    let mut built_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut built_input, mut built_output) = built_buffer.split_halves();
+    normal_ic_1d(&mut input, chunk_size);
+    normal_ic_1d(&mut built_input, chunk_size);
+    solver.apply(&mut input, &mut output, 0);
+    built.apply(&mut built_input, &mut built_output, 0);
+    assert_eq!(output.buffer(), built_output.buffer());
+}
+
+#[test]
+fn ap_timing_report_test() {
+    let grid_bound = AABB::new(matrix![0, 999; 0, 999]);
+    let n_steps = 50;
+    let chunk_size = 1000;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let plain = APSolverBuilder::new(grid_bound, n_steps)
+        .plan_type(PlanType::Estimate)
+        .cutoff(20)
+        .chunk_size(chunk_size)
+        .build(&bc, &stencil);
+    assert!(plain.timing_report().is_none());
+    let solver = APSolverBuilder::new(grid_bound, n_steps)
+        .plan_type(PlanType::Estimate)
+        .cutoff(20)
+        .chunk_size(chunk_size)
+        .instrumented(true)
+        .build(&bc, &stencil);
+    assert_eq!(solver.timing_report(), Some(TimingReport::default()));
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    normal_ic_2d(&mut input, chunk_size);
+
+    // On one thread the categories can't overlap
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+    let start = std::time::Instant::now();
+    pool.install(|| solver.apply(&mut input, &mut output, 0));
+    let elapsed = start.elapsed();
+
+    let report = solver.timing_report().unwrap();
+    assert!(report.forward_fft > std::time::Duration::ZERO);
+    assert!(report.multiply > std::time::Duration::ZERO);
+    assert!(report.backward_fft > std::time::Duration::ZERO);
+    assert!(report.direct > std::time::Duration::ZERO);
+    assert!(report.copy > std::time::Duration::ZERO);
+    assert!(report.total() <= elapsed);
+
+    // Per node reports split the totals up by node
+    let zero = std::time::Duration::ZERO;
+    let nodes = solver.node_timing_reports().unwrap();
+    assert_eq!(nodes.len(), solver.plan.nodes.len());
+    for (node_id, node) in solver.plan.nodes.iter().enumerate() {
+        let node_report = &nodes[node_id];
+        match node {
+            PlanNode::PeriodicSolve(_) => {
+                assert!(node_report.forward_fft > zero);
+                assert!(node_report.backward_fft > zero);
+                assert_eq!(node_report.direct, zero);
+            }
+            PlanNode::DirectSolve(_) => {
+                assert!(node_report.direct > zero);
+                assert_eq!(node_report.forward_fft, zero);
+            }
+            PlanNode::Repeat(_) => {
+                assert_eq!(*node_report, TimingReport::default());
+            }
+        }
+    }
+    let node_total: std::time::Duration = nodes.iter().map(|n| n.total()).sum();
+    assert_eq!(node_total, report.total());
+
+    solver.reset_timing();
+    assert_eq!(solver.timing_report(), Some(TimingReport::default()));
+}
+
+#[test]
+fn report_json_test() {
+    let grid_bound = AABB::new(matrix![0, 99; 0, 199]);
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let builder = APSolverBuilder::new(grid_bound, 20)
+        .plan_type(PlanType::Estimate)
+        .cutoff(20);
+    let solver = builder.build(&bc, &stencil);
+    assert_eq!(solver.cutoffs, vector![20, 20]);
+    assert!(solver.report_json().contains("\"cutoffs\": [20, 20]"));
+
+    let solver = builder.cutoffs(vector![10, 30]).build(&bc, &stencil);
+    assert_eq!(solver.cutoffs, vector![10, 30]);
+    assert!(solver.report_json().contains("\"cutoffs\": [10, 30]"));
+    assert!(solver.report_json().contains("\"ratio\": 0.5,"));
+
+    // Non-finite values aren't valid JSON
+    let mut solver = solver;
+    solver.params.ratio = f64::NAN;
+    let json = solver.report_json();
+    assert!(json.contains("\"ratio\": null,"));
+    #[cfg(feature = "serde")]
+    serde_json::from_str::<serde_json::Value>(&json).unwrap();
+}
+
+#[test]
+fn identity_preserves_field() {
+    let grid_bound = AABB::new(matrix![0, 63; -10, 40]);
+    let n_steps = 10;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::identity_nd::<2>();
+    let bc = ConstantCheck::new(-1.0, grid_bound);
+
+    // Cap steps so the root repeats and has a remainder solve
+    let solver = APSolverBuilder::new(grid_bound, n_steps)
+        .plan_type(PlanType::Estimate)
+        .chunk_size(chunk_size)
+        .max_steps(Some(3))
+        .build(&bc, &stencil);
+    let repeat = solver.plan.unwrap_repeat_node(solver.plan.root);
+    assert_eq!(repeat.n, 3);
+    assert!(repeat.next.is_some());
+
+    let mut initial = OwnedDomain::new(grid_bound);
+    initial.par_set_values(
+        |c: Coord<2>| (c[0] * 31 + c[1] * 17).rem_euclid(97) as f64 / 97.0,
+        chunk_size,
+    );
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input, mut output) = buffer.split_halves();
+    input.par_from_superset(&initial, chunk_size);
+    for t in 0..3 {
+        solver.apply(&mut input, &mut output, t * n_steps);
+        std::mem::swap(&mut input, &mut output);
+    }
+
+    // Only FFT round off separates the result from the initial field
+    for (a, b) in input.buffer().iter().zip(initial.buffer().iter()) {
+        assert_approx_eq!(f64, *a, *b, epsilon = 1e3 * f64::EPSILON);
+    }
+}
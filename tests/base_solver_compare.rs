@@ -36,6 +36,7 @@ fn heat_1d_p_compare() {
         &grid_bound,
         n_steps,
         plan_type,
+        false,
         chunk_size,
     );
     periodic_solver.apply(&mut fft_input_domain, &mut fft_output_domain);
@@ -87,6 +88,7 @@ fn heat_2d_p_compare() {
         &grid_bound,
         n_steps,
         plan_type,
+        false,
         chunk_size,
     );
     periodic_solver.apply(&mut fft_input_domain, &mut fft_output_domain);
@@ -151,17 +153,44 @@ fn periodic_compare() {
             &bound,
             steps,
             plan_type,
+            false,
             chunk_size,
         );
         periodic_solver.apply(&mut domain_b_input, &mut domain_b_output);
 
-        for i in 0..n_r {
-            assert_approx_eq!(
-                f64,
-                domain_a_output.buffer()[i],
-                domain_b_output.buffer()[i],
-                epsilon = 0.0000000000001
-            );
-        }
+        // The FFT places stencil weights at the mirrored offsets,
+        // since convolution evaluates `g[x - y]` while the stencil
+        // reads `input[x + offset]`. With that the two solves agree
+        // up to FFT roundoff, which grows like `eps * log(n) * max|u|`.
+        // Inputs here reach 99, so a fixed absolute epsilon of 1e-13
+        // sits right at the roundoff floor. Comparing relative to the
+        // largest reference value gives a tolerance that doesn't
+        // depend on the magnitude of the initial condition.
+        let (max_abs, max_rel) =
+            diff_norms(domain_a_output.buffer(), domain_b_output.buffer());
+        assert!(
+            max_rel < 1e-12,
+            "periodic mismatch, max abs diff: {}, max rel diff: {}",
+            max_abs,
+            max_rel
+        );
+    }
+}
+
+/// Returns the max absolute difference between `expected` and `actual`,
+/// and that difference relative to the largest magnitude in `expected`.
+fn diff_norms(expected: &[f64], actual: &[f64]) -> (f64, f64) {
+    assert_eq!(expected.len(), actual.len());
+    let mut max_abs = 0.0f64;
+    let mut max_value = 0.0f64;
+    for (e, a) in expected.iter().zip(actual.iter()) {
+        max_abs = max_abs.max((e - a).abs());
+        max_value = max_value.max(e.abs());
     }
+    let max_rel = if max_value > 0.0 {
+        max_abs / max_value
+    } else {
+        max_abs
+    };
+    (max_abs, max_rel)
 }
@@ -0,0 +1,94 @@
+//! Manufactured solution check for the 1D heat equation.
+//!
+//! On the periodic unit interval, `u(x, t) = exp(-4 pi^2 k t) sin(2 pi x)`
+//! solves `u_t = k u_xx` with no source term.
+//! We hold `r = k dt / dx^2` fixed while refining,
+//! so the explicit stencil should converge at second order in `dx`.
+
+use nhls::domain::*;
+use nhls::fft_solver::*;
+use nhls::solver::*;
+use nhls::util::*;
+use std::f64::consts::PI;
+
+const K: f64 = 1.0;
+const R: f64 = 0.25;
+const FINAL_TIME: f64 = 1.0 / 256.0;
+const CHUNK_SIZE: usize = 16;
+
+fn exact(x: f64, t: f64) -> f64 {
+    (-4.0 * PI * PI * K * t).exp() * (2.0 * PI * x).sin()
+}
+
+/// Solve on `n` cells up to `FINAL_TIME`,
+/// returns the max error for the direct and FFT solvers.
+fn mms_errors(n: usize) -> (f64, f64) {
+    let dx = 1.0 / n as f64;
+    let dt = R * dx * dx;
+    let steps = (FINAL_TIME / dt).round() as usize;
+    assert!((steps as f64 * dt - FINAL_TIME).abs() < 1e-15);
+
+    let stencil = nhls::standard_stencils::heat_1d(dt, dx, K);
+    let bound = AABB::new(matrix![0, n as i32 - 1]);
+    let ic = move |c: Coord<1>| exact(c[0] as f64 * dx, 0.0);
+
+    let mut direct_input = OwnedDomain::new(bound);
+    let mut direct_output = OwnedDomain::new(bound);
+    direct_input.par_set_values(ic, CHUNK_SIZE);
+    direct_periodic_apply(
+        &stencil,
+        &mut direct_input,
+        &mut direct_output,
+        steps,
+        CHUNK_SIZE,
+    );
+
+    let mut fft_input = OwnedDomain::new(bound);
+    let mut fft_output = OwnedDomain::new(bound);
+    fft_input.par_set_values(ic, CHUNK_SIZE);
+    let mut periodic_solver = PeriodicSolver::create(
+        &stencil,
+        fft_output.buffer_mut(),
+        &bound,
+        steps,
+        PlanType::Estimate,
+        false,
+        CHUNK_SIZE,
+    );
+    periodic_solver.apply(&mut fft_input, &mut fft_output);
+
+    let mut direct_error = 0.0f64;
+    let mut fft_error = 0.0f64;
+    for c in bound.coord_iter() {
+        let expected = exact(c[0] as f64 * dx, FINAL_TIME);
+        direct_error =
+            direct_error.max((direct_output.view(&c) - expected).abs());
+        fft_error = fft_error.max((fft_output.view(&c) - expected).abs());
+    }
+    (direct_error, fft_error)
+}
+
+#[test]
+fn mms_heat_1d_convergence() {
+    let sizes = [32, 64, 128];
+    let errors: Vec<(f64, f64)> =
+        sizes.iter().map(|n| mms_errors(*n)).collect();
+
+    for i in 1..sizes.len() {
+        let (direct_coarse, fft_coarse) = errors[i - 1];
+        let (direct_fine, fft_fine) = errors[i];
+        let direct_rate = (direct_coarse / direct_fine).log2();
+        let fft_rate = (fft_coarse / fft_fine).log2();
+        println!(
+            "n: {} -> {}, direct error: {:e}, rate: {:.3}, fft error: {:e}, rate: {:.3}",
+            sizes[i - 1],
+            sizes[i],
+            direct_fine,
+            direct_rate,
+            fft_fine,
+            fft_rate
+        );
+        assert!(direct_rate > 1.8, "direct rate {}", direct_rate);
+        assert!(fft_rate > 1.8, "fft rate {}", fft_rate);
+    }
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "serde")]
+
+use nhls::domain::*;
+use nhls::fft_solver::*;
+use nhls::init::*;
+use nhls::util::*;
+
+#[test]
+fn plan_json_round_trip_test() {
+    let grid_bound = AABB::new(matrix![0, 299]);
+    let n_steps = 120;
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_1d(1.0, 1.0, 0.5);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 20,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+    let planner_result =
+        create_ap_plan(&stencil, grid_bound, n_steps, &planner_params);
+
+    let mut path = std::env::temp_dir();
+    path.push(format!("nhls_plan_json_{}.json", std::process::id()));
+    planner_result.plan.save_json(&path);
+    let loaded = APPlan::<1>::load_json(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.plan_hash(), planner_result.plan.plan_hash());
+    assert_eq!(loaded.to_json(), planner_result.plan.to_json());
+
+    // The loaded plan solves the same as the original,
+    // given convolutions rebuilt for the same stencil and aabb
+    let mut loaded_result = planner_result.clone();
+    loaded_result.plan = loaded;
+    let original_solver = APSolver::from_planner_result(
+        &bc,
+        &stencil,
+        planner_result,
+        &planner_params,
+    );
+    let loaded_solver = APSolver::from_planner_result(
+        &bc,
+        &stencil,
+        loaded_result,
+        &planner_params,
+    );
+
+    let mut original_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut original_input, mut original_output) =
+        original_buffer.split_halves();
+    let mut loaded_buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut loaded_input, mut loaded_output) = loaded_buffer.split_halves();
+    normal_ic_1d(&mut original_input, chunk_size);
+    normal_ic_1d(&mut loaded_input, chunk_size);
+
+    original_solver.apply(&mut original_input, &mut original_output, 0);
+    loaded_solver.apply(&mut loaded_input, &mut loaded_output, 0);
+    assert_eq!(original_output.buffer(), loaded_output.buffer());
+}
@@ -0,0 +1,86 @@
+#![cfg(feature = "tracing")]
+
+use nhls::domain::*;
+use nhls::fft_solver::*;
+use nhls::init::*;
+use nhls::util::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::span;
+
+/// Records the name of every span created.
+struct SpanCapture {
+    names: Arc<Mutex<Vec<&'static str>>>,
+    next_id: AtomicU64,
+}
+
+impl tracing::Subscriber for SpanCapture {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        self.names.lock().unwrap().push(span.metadata().name());
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, _event: &tracing::Event<'_>) {}
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+#[test]
+fn ap_solve_spans() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = SpanCapture {
+        names: names.clone(),
+        next_id: AtomicU64::new(1),
+    };
+
+    let grid_bound = AABB::new(matrix![0, 99; 0, 99]);
+    let chunk_size = 100;
+    let stencil = nhls::standard_stencils::heat_2d(1.0, 1.0, 1.0, 0.2, 0.2);
+    let bc = ConstantCheck::new(1.0, grid_bound);
+    let planner_params = PlannerParameters {
+        plan_type: PlanType::Estimate,
+        plan_fallback: false,
+        cutoff: 40,
+        ratio: 0.5,
+        chunk_size,
+        max_steps: None,
+    };
+
+    let mut buffer = OwnedDomain::new_paired(grid_bound);
+    let (mut input_domain, mut output_domain) = buffer.split_halves();
+    normal_ic_2d(&mut input_domain, chunk_size);
+
+    // Spans on rayon worker threads go to the global default,
+    // so we only check the ones created on this thread.
+    tracing::subscriber::with_default(subscriber, || {
+        let solver =
+            APSolver::new(&bc, &stencil, grid_bound, 50, &planner_params);
+        solver.apply(&mut input_domain, &mut output_domain, 0);
+    });
+
+    let names = names.lock().unwrap();
+    for expected in [
+        "ap_planner_finish",
+        "ap_solve",
+        "periodic_solve",
+        "fft_convolution",
+    ] {
+        assert!(
+            names.contains(&expected),
+            "missing span {}, got {:?}",
+            expected,
+            names
+        );
+    }
+    assert_eq!(names.iter().filter(|n| **n == "ap_solve").count(), 1);
+}